@@ -1,4 +1,4 @@
-/**
+/*!
  * rel AST (Abstract Syntax Tree) definitions
  */
 
@@ -90,7 +90,22 @@ pub struct FieldNode {
     pub computed_value: Option<ExpressionNode>, // For computed fields like tax: number = subtotal * taxRate
     pub validations: Vec<ValidationNode>,
     pub conditionals: Vec<ConditionalNode>,
+    pub discriminator: Option<String>, // @discriminator(kind) above a union of schemas
     pub location: SourceLocation, // Source location for error reporting
+    pub comments: Box<FieldComments>, // Only populated when the lexer retains comments
+}
+
+/// `#` comments attached to a field, kept so the formatter can print them back
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FieldComments {
+    /// Comment lines directly above the field
+    pub leading: Vec<String>,
+    /// Comment on the same line, after the field
+    pub trailing: Option<String>,
+    /// Comments between the last field of a block and its closing brace
+    pub dangling: Vec<String>,
+    /// Whether a blank line separates the field, comments included, from what came before
+    pub blank_line: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -117,7 +132,16 @@ pub enum TypeNode {
 
     // Complex types
     Union(Vec<TypeNode>),
+    // Object types merged into one: User & Timestamps & { note: string }
+    Intersection(Vec<TypeNode>),
     Generic(String, Vec<TypeNode>),
+    // Typed dictionaries: record<string, Address>, Map<uuid, number>
+    Record {
+        key: Box<TypeNode>,
+        value: Box<TypeNode>,
+    },
+    // Fixed-length arrays: [number, number], [string, int, boolean?, ...string[]]
+    Tuple(Vec<TupleElement>),
 
     // Constrained types (with validation constraints)
     Constrained {
@@ -135,10 +159,18 @@ pub enum TypeNode {
     InlineObject(Vec<FieldNode>),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct TupleElement {
+    pub element_type: TypeNode,
+    pub optional: bool, // `boolean?`
+    pub rest: bool,     // `...string[]`; element_type is the array type
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConstraintNode {
     pub constraint_type: ConstraintType,
     pub value: Option<ExpressionNode>,
+    pub location: SourceLocation, // Location of the constraint name
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -147,6 +179,9 @@ pub enum ConstraintType {
     Max,
     MinLength,
     MaxLength,
+    MinItems,
+    MaxItems,
+    Unique,
     Matches,
     Contains,
     StartsWith,
@@ -179,6 +214,9 @@ pub struct ConditionalNode {
     pub else_value: Option<TypeNode>,
     pub then_fields: Vec<FieldNode>,  // Store actual fields from then block
     pub else_fields: Vec<FieldNode>,  // Store actual fields from else block
+    pub location: SourceLocation, // Location of the condition, after `when`
+    pub comments: Vec<String>, // `#` lines directly above `when`, for the formatter
+    pub blank_line: bool, // A blank line above `when` and its comments, for the formatter
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -301,25 +339,90 @@ pub enum UnaryOperator {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ImportNode {
     pub path: String,
-    pub items: Vec<String>,
+    pub items: Vec<ImportItem>,
+    /// Namespace of `import * as billing from "..."`, whose names are then
+    /// written `billing.Invoice`; such an import has no items
+    pub alias: Option<String>,
+    pub location: SourceLocation, // Location of the `import` keyword
+}
+
+impl ImportNode {
+    /// Names this import brings into scope: the local name of each item, or the namespace
+    pub fn local_names(&self) -> Vec<&str> {
+        match &self.alias {
+            Some(namespace) => vec![namespace.as_str()],
+            None => self.items.iter().map(ImportItem::local_name).collect(),
+        }
+    }
+}
+
+/// One name in `import { User as AuthUser }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportItem {
+    /// The name the imported module exports
+    pub name: String,
+    /// The name used in the importing file, when different
     pub alias: Option<String>,
 }
 
+impl ImportItem {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), alias: None }
+    }
+
+    pub fn local_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl std::fmt::Display for ImportItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "{} as {}", self.name, alias),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExportNode {
     pub items: Vec<String>,
+    /// Module the items are re-exported from: `export { User } from "./user.rel"`
+    pub from: Option<String>,
+    /// `export * from "./billing.rel"`: everything that module exports
+    pub all: bool,
+    pub location: SourceLocation, // Location of the `export` keyword
+}
+
+impl ExportNode {
+    /// `export A, B` of names declared or imported in this file
+    pub fn new(items: Vec<String>) -> Self {
+        Self { items, from: None, all: false, location: SourceLocation::unknown() }
+    }
+
+    /// The import a re-export performs, so it resolves like one
+    pub fn as_import(&self) -> Option<ImportNode> {
+        self.from.as_ref().map(|path| ImportNode {
+            path: path.clone(),
+            items: self.items.iter().map(ImportItem::new).collect(),
+            alias: None,
+            location: self.location.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct EnumNode {
     pub name: String,
     pub values: Vec<String>,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TypeAliasNode {
     pub name: String,
     pub type_definition: TypeNode,
+    pub location: SourceLocation,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -347,6 +450,7 @@ pub struct ParseError {
 #[derive(Debug, Clone, PartialEq)]
 pub struct CommentNode {
     pub text: String,
+    pub blank_after: bool, // Followed by a blank line in the source
 }
 
 // New: Variable declaration node
@@ -0,0 +1,206 @@
+/*!
+ * Build Cache - skip files whose source and imports have not changed
+ *
+ * `.rel-cache/manifest.json` records, for every compiled input, the content
+ * hash of each file in its import graph and of the output it produced, along
+ * with the warnings it raised. A file is up to date when all of those hashes
+ * still match, and its warnings are reported again from the cache; the whole
+ * cache is dropped when the compiler version or the build settings change.
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::Diagnostic;
+
+pub const CACHE_DIR: &str = ".rel-cache";
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    version: String,
+    fingerprint: String,
+    entries: BTreeMap<PathBuf, Entry>,
+}
+
+/// What one input needed and produced on its last successful build
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    /// Every file in the import graph, the input included, with its hash
+    dependencies: BTreeMap<PathBuf, String>,
+    output: PathBuf,
+    output_hash: String,
+    /// Warnings from that build, replayed while the entry stays fresh
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Diagnostic>,
+}
+
+pub struct BuildCache {
+    dir: PathBuf,
+    fingerprint: String,
+    entries: BTreeMap<PathBuf, Entry>,
+    /// Hashes read during this build, so shared imports are hashed once
+    hashes: HashMap<PathBuf, Option<String>>,
+    dirty: bool,
+}
+
+impl BuildCache {
+    /// Load the cache in `dir`; `fingerprint` describes the settings that
+    /// affect output, and any mismatch starts from an empty cache
+    pub fn load(dir: &Path, fingerprint: String) -> Self {
+        let manifest: Manifest = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        let valid = manifest.version == env!("CARGO_PKG_VERSION") && manifest.fingerprint == fingerprint;
+        Self {
+            dir: dir.to_path_buf(),
+            dirty: !valid && !manifest.entries.is_empty(),
+            entries: if valid { manifest.entries } else { BTreeMap::new() },
+            fingerprint,
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// Whether `input` was built into `output` and nothing it depends on has
+    /// changed since, including the output file itself
+    pub fn is_fresh(&mut self, input: &Path, output: &Path) -> bool {
+        let Some(entry) = self.entries.get(&canonical(input)).cloned() else {
+            return false;
+        };
+
+        entry.output == output
+            && self.hash_file(output).as_ref() == Some(&entry.output_hash)
+            && entry.dependencies.iter().all(|(path, hash)| self.hash_file(path).as_ref() == Some(hash))
+    }
+
+    /// The import graph recorded for `input` by its last successful build
+    pub fn dependencies(&self, input: &Path) -> Option<Vec<PathBuf>> {
+        self.entries.get(&canonical(input)).map(|entry| entry.dependencies.keys().cloned().collect())
+    }
+
+    /// The warnings `input` raised on its last successful build
+    pub fn warnings(&self, input: &Path) -> Vec<Diagnostic> {
+        self.entries.get(&canonical(input)).map(|entry| entry.warnings.clone()).unwrap_or_default()
+    }
+
+    /// Remember a successful build of `input` and the warnings it raised;
+    /// `dependencies` are canonical paths
+    pub fn record(&mut self, input: &Path, dependencies: &[PathBuf], output: &Path, output_code: &str, warnings: &[Diagnostic]) {
+        let mut hashes = BTreeMap::new();
+        for dependency in dependencies {
+            match self.hash_file(dependency) {
+                Some(hash) => {
+                    hashes.insert(dependency.clone(), hash);
+                }
+                // A file that vanished mid-build can't be trusted later
+                None => return self.forget(input),
+            }
+        }
+
+        let output_hash = content_hash(output_code.as_bytes());
+        self.hashes.insert(output.to_path_buf(), Some(output_hash.clone()));
+        self.entries.insert(canonical(input), Entry {
+            dependencies: hashes,
+            output: output.to_path_buf(),
+            output_hash,
+            warnings: warnings.to_vec(),
+        });
+        self.dirty = true;
+    }
+
+    /// Drop `input`, so a failed build is retried next time
+    pub fn forget(&mut self, input: &Path) {
+        if self.entries.remove(&canonical(input)).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Write the manifest back, if anything changed
+    pub fn save(&self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let manifest = Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            fingerprint: self.fingerprint.clone(),
+            entries: self.entries.clone(),
+        };
+        let text = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(self.dir.join(MANIFEST_FILE), text)
+    }
+
+    fn hash_file(&mut self, path: &Path) -> Option<String> {
+        self.hashes
+            .entry(path.to_path_buf())
+            .or_insert_with(|| fs::read(path).ok().map(|bytes| content_hash(&bytes)))
+            .clone()
+    }
+}
+
+/// 64-bit FNV-1a, stable across platforms and compiler versions
+pub fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Write `contents` unless the file already holds exactly that, so unchanged
+/// outputs keep their timestamps; returns whether the file was written
+pub fn write_if_changed(path: &Path, contents: &str) -> io::Result<bool> {
+    if fs::read(path).is_ok_and(|existing| existing == contents.as_bytes()) {
+        return Ok(false);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(true)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_goes_stale_when_an_import_changes() {
+        let dir = std::env::temp_dir().join(format!("rel-cache-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let input = dir.join("user.rel");
+        let shared = dir.join("shared.rel");
+        let output = dir.join("user.ts");
+        fs::write(&input, "import { Id } from \"./shared.rel\"").unwrap();
+        fs::write(&shared, "export type Id = string").unwrap();
+        fs::write(&output, "export const User = {};").unwrap();
+
+        let mut cache = BuildCache::load(&dir.join(CACHE_DIR), "settings".to_string());
+        assert!(!cache.is_fresh(&input, &output));
+        cache.record(&input, &[shared.clone(), input.clone()], &output, "export const User = {};", &[]);
+        cache.save().unwrap();
+
+        let mut cache = BuildCache::load(&dir.join(CACHE_DIR), "settings".to_string());
+        assert!(cache.is_fresh(&input, &output));
+
+        fs::write(&shared, "export type Id = number").unwrap();
+        let mut cache = BuildCache::load(&dir.join(CACHE_DIR), "settings".to_string());
+        assert!(!cache.is_fresh(&input, &output));
+
+        let mut other_settings = BuildCache::load(&dir.join(CACHE_DIR), "other".to_string());
+        assert!(!other_settings.is_fresh(&input, &output));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
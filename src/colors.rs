@@ -1,4 +1,4 @@
-/**
+/*!
  * ANSI Color Codes for Terminal Output
  */
 
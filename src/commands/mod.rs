@@ -1,4 +1,4 @@
-/**
+/*!
  * CLI Command Handlers
  *
  * This module contains the handlers for all CLI commands.
@@ -12,6 +12,9 @@ use crate::lexer;
 use crate::parser;
 use crate::generator;
 use crate::compiler;
+use crate::colors;
+use crate::config;
+use crate::diagnostics::{self, Diagnostic, DiagnosticsFormat};
 
 #[derive(Parser)]
 #[command(name = "rel")]
@@ -26,17 +29,34 @@ pub struct Cli {
 pub enum Commands {
     /// Compile .rel files to TypeScript
     Build {
-        /// Input .rel file or directory
+        /// Input .rel file or directory (defaults to "schemas" in rel.json)
         #[arg(short, long)]
-        input: PathBuf,
+        input: Option<PathBuf>,
 
-        /// Output directory for generated TypeScript files
+        /// Output directory for generated TypeScript files (overrides "output" in rel.json)
         #[arg(short, long)]
         output: Option<PathBuf>,
 
         /// Watch mode - rebuild on file changes
         #[arg(long)]
         watch: bool,
+
+        /// Rebuild every file, ignoring the .rel-cache directory
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Number of files to compile at once (defaults to the number of CPUs)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        /// Write every output directly into the output directory, named by
+        /// PATTERN with {name}, {dir} and {path} (default "{name}")
+        #[arg(long, value_name = "PATTERN", num_args = 0..=1, default_missing_value = "{name}")]
+        flatten: Option<String>,
+
+        /// How to report diagnostics: human, json or sarif
+        #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+        diagnostics_format: DiagnosticsFormat,
     },
     /// Initialize a new rel project
     Init {
@@ -46,25 +66,47 @@ pub enum Commands {
     },
     /// Check .rel files without generating output
     Check {
-        /// Input .rel file or directory
+        /// Input .rel file or directory (defaults to "schemas" in rel.json)
         #[arg(short, long)]
-        input: PathBuf,
+        input: Option<PathBuf>,
+
+        /// How to report diagnostics: human, json or sarif
+        #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+        diagnostics_format: DiagnosticsFormat,
+    },
+    /// Remove generated files whose .rel sources were deleted or renamed
+    Clean {
+        /// Input .rel file or directory (defaults to "schemas" in rel.json)
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        /// Output directory to clean (overrides "output" in rel.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Remove every generated file and the build cache
+        #[arg(long)]
+        all: bool,
     },
     /// Watch mode - rebuild on file changes
     Watch {
-        /// Input .rel file or directory
+        /// Input .rel file or directory (defaults to "schemas" in rel.json)
         #[arg(short, long)]
-        input: PathBuf,
+        input: Option<PathBuf>,
 
-        /// Output directory for generated TypeScript files
+        /// Output directory for generated TypeScript files (overrides "output" in rel.json)
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
     /// Validate .rel files without generating output
     Validate {
-        /// Input .rel file or directory
+        /// Input .rel file or directory (defaults to "schemas" in rel.json)
         #[arg(short, long)]
-        input: PathBuf,
+        input: Option<PathBuf>,
+
+        /// How to report diagnostics: human, json or sarif
+        #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+        diagnostics_format: DiagnosticsFormat,
     },
     /// Test the lexer with sample input
     TestLexer {
@@ -87,36 +129,112 @@ pub enum Commands {
         #[arg(short, long)]
         input: PathBuf,
     },
+    /// Import schemas from another format into .rel
+    Import {
+        #[command(subcommand)]
+        format: ImportFormat,
+    },
+    /// Infer a .rel schema from sample JSON payloads
+    Infer {
+        /// Sample JSON files (a top-level array is read as several samples)
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// Name of the generated schema
+        #[arg(short, long)]
+        name: String,
+
+        /// Output .rel file (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Largest set of repeated string values turned into a literal union
+        #[arg(long, default_value_t = 5)]
+        max_literals: usize,
+    },
+    /// Rewrite .rel files in canonical format
+    Fmt {
+        /// Input .rel file or directory
+        #[arg(short, long, default_value = ".")]
+        input: PathBuf,
+
+        /// Report unformatted files and fail instead of rewriting them
+        #[arg(long)]
+        check: bool,
+    },
+    /// Start the language server (LSP over stdio)
+    Lsp,
+}
+
+#[derive(Subcommand)]
+pub enum ImportFormat {
+    /// Convert a JSON Schema document into .rel declarations
+    JsonSchema {
+        /// Input JSON Schema file
+        input: PathBuf,
+
+        /// Output .rel file (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 pub fn execute_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     match cli.command {
-        Commands::Build { input, output, watch } => {
-            let options = compiler::CompilerOptions {
-                input_dir: input,
-                output_dir: output,
-                watch,
-            };
-
-            let compiler = compiler::relCompiler::new(options);
-            compiler.compile()?;
+        Commands::Build { input, output, watch, no_cache, jobs, flatten, diagnostics_format } => {
+            let mut options = config::compiler_options(input, output, watch, diagnostics_format)?;
+            options.incremental = !no_cache;
+            if let Some(jobs) = jobs {
+                options.jobs = jobs;
+            }
+            if let Some(pattern) = flatten {
+                config::validate_flatten(&pattern).map_err(|e| format!("--flatten: {}", e))?;
+                options.config.flatten = Some(pattern);
+            }
+            if options.watch {
+                if !diagnostics_format.is_human() {
+                    return Err("--watch reports diagnostics as human-readable text only".into());
+                }
+                crate::watch::watch_files(options)?;
+            } else {
+                let compiler = compiler::relCompiler::new(options);
+                report_diagnostics(compiler.compile(), diagnostics_format)?;
+            }
         }
         Commands::Init { dir } => {
             let dir = dir.unwrap_or_else(|| PathBuf::from("."));
             println!("Initializing rel project in: {:?}", dir);
             crate::project::init_project(&dir)?;
         }
-        Commands::Check { input } => {
-            println!("Checking rel files in: {:?}", input);
-            crate::validation::check_files(&input)?;
+        Commands::Check { input, diagnostics_format } => {
+            let options = config::compiler_options(input, None, false, diagnostics_format)?;
+            if diagnostics_format.is_human() {
+                println!("Checking rel files in: {:?}", options.input_dir);
+            }
+            report_diagnostics(crate::validation::check_files(options), diagnostics_format)?;
+        }
+        Commands::Validate { input, diagnostics_format } => {
+            let options = config::compiler_options(input, None, false, diagnostics_format)?;
+            if diagnostics_format.is_human() {
+                println!("Validating rel files in: {:?}", options.input_dir);
+            }
+            report_diagnostics(crate::validation::validate_files(options), diagnostics_format)?;
         }
-        Commands::Validate { input } => {
-            println!("Validating rel files in: {:?}", input);
-            crate::validation::validate_files(&input)?;
+        Commands::Clean { input, output, all } => {
+            let options = config::compiler_options(input, output, false, DiagnosticsFormat::Human)?;
+            let removed = compiler::relCompiler::new(options).clean(all)?;
+            for path in &removed {
+                println!("Removed {}", colors::path(&path.display().to_string()));
+            }
+            if removed.is_empty() {
+                println!("Nothing to clean");
+            } else {
+                println!("{}", colors::success(&format!("Removed {} generated file(s)", removed.len())));
+            }
         }
         Commands::Watch { input, output } => {
-            println!("Watching rel files in: {:?} for changes", input);
-            crate::watch::watch_files(&input, output.as_ref())?;
+            let options = config::compiler_options(input, output, true, DiagnosticsFormat::Human)?;
+            crate::watch::watch_files(options)?;
         }
         Commands::TestLexer { input } => {
             test_lexer(&input);
@@ -128,13 +246,43 @@ pub fn execute_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             test_generator(&input);
         }
         Commands::Run { input } => {
-            crate::run::run_file(&input)?;
+            report_diagnostics(crate::run::run_file(&input).map(|()| Vec::new()), DiagnosticsFormat::Human)?;
+        }
+        Commands::Import { format } => match format {
+            ImportFormat::JsonSchema { input, output } => {
+                crate::importer::import_json_schema(&input, output.as_ref())?;
+            }
+        },
+        Commands::Infer { inputs, name, output, max_literals } => {
+            crate::infer::infer_files(&inputs, &name, output.as_ref(), max_literals)?;
+        }
+        Commands::Fmt { input, check } => {
+            crate::formatter::format_files(&input, check)?;
+        }
+        Commands::Lsp => {
+            crate::lsp::run_server()?;
         }
     }
 
     Ok(())
 }
 
+/// Report the diagnostics a command produced: its warnings on success or
+/// everything it failed with. Machine-readable formats always print a report.
+fn report_diagnostics(
+    result: Result<Vec<Diagnostic>, Box<dyn std::error::Error>>,
+    format: DiagnosticsFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let found = match &result {
+        Ok(warnings) => warnings.as_slice(),
+        Err(e) => diagnostics::diagnostics_of(e.as_ref()),
+    };
+    if !found.is_empty() || !format.is_human() {
+        diagnostics::report(found, format);
+    }
+    result.map(|_| ())
+}
+
 // ============================================================================
 // SECTION: Test Commands
 // ============================================================================
@@ -1,8 +1,8 @@
-/**
+/*!
  * rel Compiler - Main compiler class that orchestrates the compilation process
  */
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -11,14 +11,32 @@ use crate::lexer::Lexer;
 use crate::parser::Parser;
 use crate::generator::TypeScriptGenerator;
 use crate::resolver::ModuleResolver;
-use crate::validation;
 use crate::colors;
-
-#[derive(Debug)]
+use crate::config::{self, ProjectConfig};
+use crate::cache::{self, BuildCache};
+use crate::manifest::BuildManifest;
+use crate::parallel;
+use crate::session::{BuildSession, BuildSummary, DependencyGraph};
+use crate::diagnostics::{Diagnostic, DiagnosticCode, DiagnosticsError, DiagnosticsFormat};
+
+#[derive(Debug, Clone)]
 pub struct CompilerOptions {
     pub input_dir: PathBuf,
     pub output_dir: Option<PathBuf>,
     pub watch: bool,
+    pub diagnostics_format: DiagnosticsFormat,
+    /// Skip files whose source and imports are unchanged since the last build
+    pub incremental: bool,
+    /// Files compiled at once; results are still reported in file order
+    pub jobs: usize,
+    /// Settings from rel.json (defaults when the project has none)
+    pub config: ProjectConfig,
+}
+
+/// Output of one file, before it is written
+struct Generated {
+    code: String,
+    warnings: Vec<Diagnostic>,
 }
 
 pub struct relCompiler {
@@ -30,42 +48,226 @@ impl relCompiler {
         Self { options }
     }
 
-    pub fn compile(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("rel Compiler starting...");
-        
-        let input_path = &self.options.input_dir;
-        
-        // Check if input is a file or directory
-        let rel_files = if input_path.is_file() {
+    /// Compile every input once; on success returns the warnings that were reported
+    pub fn compile(&self) -> Result<Vec<Diagnostic>, Box<dyn std::error::Error>> {
+        self.progress("rel Compiler starting...".to_string());
+
+        let summary = self.session().build()?;
+        if summary.compiled + summary.up_to_date == 0 {
+            self.progress("No .rel files found".to_string());
+            return Ok(Vec::new());
+        }
+
+        self.progress(format!("\n{} {}", colors::dim("Summary:"), summary));
+        if summary.failed() {
+            return Err(DiagnosticsError::new("Compilation failed", summary.diagnostics).into());
+        }
+
+        self.progress(format!("\n{}", colors::success("✓ Compilation completed successfully!")));
+        Ok(summary.diagnostics)
+    }
+
+    /// A build session over every input, for one build or repeated rebuilds
+    pub fn session(&self) -> BuildSession<'_> {
+        let resolver = ModuleResolver::new(self.options.input_dir.clone()).with_config(&self.options.config);
+        BuildSession::new(self, resolver)
+    }
+
+    /// Compile `files` with the modules `resolver` has already parsed, recording
+    /// in `graph` what each one imports so rebuilds know which files a change
+    /// affects. Failing files are reported in the summary, not as an error.
+    pub(crate) fn compile_files(
+        &self,
+        files: &[PathBuf],
+        resolver: &mut ModuleResolver,
+        graph: &mut DependencyGraph,
+    ) -> Result<BuildSummary, Box<dyn std::error::Error>> {
+        // Sorted so output and diagnostics never depend on directory order
+        let mut rel_files = files.to_vec();
+        rel_files.sort();
+        rel_files.dedup();
+        self.check_outputs(&rel_files)?;
+
+        let mut cache = self.load_cache();
+        let mut manifest = BuildManifest::load(&self.output_root());
+        let mut stale = Vec::new();
+        let mut replayed = Vec::new();
+        let mut up_to_date = 0;
+        for file_path in rel_files {
+            let output_path = self.get_output_path(&file_path)?;
+            manifest.record(&output_path, &file_path);
+            let fresh = cache.as_mut().and_then(|cache| match cache.is_fresh(&file_path, &output_path) {
+                true => cache.dependencies(&file_path),
+                false => None,
+            });
+            if let Some(dependencies) = fresh {
+                graph.record(&file_path, dependencies);
+                replayed.extend(cache.as_ref().map(|cache| cache.warnings(&file_path)).unwrap_or_default());
+                up_to_date += 1;
+            } else {
+                stale.push((file_path, output_path));
+            }
+        }
+
+        // Inputs are parsed in parallel, then each import graph is resolved
+        // with shared imports parsed only once
+        let jobs = self.options.jobs;
+        let inputs: Vec<PathBuf> = stale.iter().map(|(file_path, _)| file_path.clone()).collect();
+        resolver.preload(&inputs, jobs);
+        let resolved: Vec<_> = inputs.iter().map(|file_path| resolver.resolve_dependencies(file_path)).collect();
+        for (file_path, dependencies) in inputs.iter().zip(&resolved) {
+            match dependencies {
+                Ok(dependencies) => graph.record(file_path, dependencies.clone()),
+                Err(_) => graph.remove(file_path),
+            }
+        }
+
+        // With the graphs known, every input is generated independently
+        let work: Vec<_> = inputs.iter().zip(&resolved).collect();
+        let results = parallel::map(&work, jobs, |(file_path, dependencies)| match dependencies {
+            Ok(dependencies) => self.generate(resolver, file_path, dependencies),
+            Err(diagnostics) => Err(DiagnosticsError::new("Dependency resolution failed", diagnostics.clone())),
+        });
+
+        // Report and write in file order, collecting diagnostics so every failing file is reported;
+        // files that were up to date report the warnings they raised when they were built
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        for diagnostic in replayed {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+        for (((file_path, output_path), dependencies), result) in stale.iter().zip(&resolved).zip(results) {
+            self.progress(format!("\n{} {}", colors::info("Compiling"), colors::path(&file_path.display().to_string())));
+            graph.set_failed(file_path, result.is_err());
+
+            let found = match result {
+                Ok(generated) => {
+                    let written = cache::write_if_changed(output_path, &generated.code)?;
+                    let status = if written { colors::success("Generated") } else { colors::dim("Unchanged") };
+                    self.progress(format!("  {} {}", status, colors::path(&output_path.display().to_string())));
+
+                    if let (Some(cache), Ok(dependencies)) = (cache.as_mut(), dependencies) {
+                        cache.record(file_path, dependencies, output_path, &generated.code, &generated.warnings);
+                    }
+                    generated.warnings
+                }
+                Err(error) => {
+                    if let Some(cache) = cache.as_mut() {
+                        cache.forget(file_path);
+                    }
+                    self.progress(format!("  {}", colors::error(&error.to_string())));
+                    error.diagnostics
+                }
+            };
+
+            // A broken shared import is reported once, not once per importer
+            for diagnostic in found {
+                if !diagnostics.contains(&diagnostic) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+
+        if let Some(cache) = &cache {
+            if let Err(e) = cache.save() {
+                self.progress(colors::warning(&format!("Could not write build cache: {}", e)));
+            }
+        }
+
+        // Outputs of deleted or renamed sources
+        let removed = manifest.prune()?;
+        for output_path in &removed {
+            self.progress(format!("  {} {}", colors::dim("Removed"), colors::path(&output_path.display().to_string())));
+        }
+        manifest.save()?;
+
+        Ok(BuildSummary {
+            compiled: stale.len(),
+            up_to_date,
+            removed: removed.len(),
+            diagnostics,
+            ..Default::default()
+        })
+    }
+
+    /// Input files or directories: those from rel.json, or the one given directly
+    pub fn inputs(&self) -> Vec<PathBuf> {
+        if self.options.config.inputs.is_empty() {
+            vec![self.options.input_dir.clone()]
+        } else {
+            self.options.config.inputs.clone()
+        }
+    }
+
+    /// The .rel files named by one input, which may be a file or a directory
+    pub fn input_files(&self, input_path: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        if input_path.is_file() {
             // Single file mode
             if input_path.extension().and_then(|s| s.to_str()) == Some("rel") {
-                println!("Input file: {:?}", input_path);
-                vec![input_path.clone()]
+                self.progress(format!("Input file: {:?}", input_path));
+                Ok(vec![input_path.to_path_buf()])
             } else {
-                return Err(format!("Input file must have .rel extension: {:?}", input_path).into());
+                Err(format!("Input file must have .rel extension: {:?}", input_path).into())
             }
         } else if input_path.is_dir() {
             // Directory mode
-            println!("Input directory: {:?}", input_path);
+            self.progress(format!("Input directory: {:?}", input_path));
             let files = self.find_rel_files(input_path)?;
-            println!("Found {} .rel files", files.len());
-            
-            if files.is_empty() {
-                println!("No .rel files found in {:?}", input_path);
-                return Ok(());
-            }
-            files
+            self.progress(format!("Found {} .rel files", files.len()));
+            Ok(files)
         } else {
-            return Err(format!("Input path does not exist: {:?}", input_path).into());
-        };
+            Err(format!("Input path does not exist: {:?}", input_path).into())
+        }
+    }
 
-        // Compile each file
-        for file_path in rel_files {
-            self.compile_file(&file_path)?;
+    /// The build cache, kept next to rel.json or otherwise in the output directory
+    fn load_cache(&self) -> Option<BuildCache> {
+        if !self.options.incremental {
+            return None;
         }
 
-        println!("\n{}", colors::success("✓ Compilation completed successfully!"));
-        Ok(())
+        // Settings that change output without touching any .rel file
+        let config = &self.options.config;
+        let fingerprint = format!(
+            "{:?} {:?} {:?} {:?}",
+            config.targets, config.aliases, config.paths, config.lint,
+        );
+        Some(BuildCache::load(&self.cache_dir(), fingerprint))
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        let config = &self.options.config;
+        match &config.path {
+            Some(_) => config.root.join(cache::CACHE_DIR),
+            None => self.output_root().join(cache::CACHE_DIR),
+        }
+    }
+
+    /// Delete generated files whose sources are gone; with `all`, delete every
+    /// generated file along with the manifest and the build cache. Returns the
+    /// deleted outputs.
+    pub fn clean(&self, all: bool) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let mut manifest = BuildManifest::load(&self.output_root());
+        if !all {
+            let removed = manifest.prune()?;
+            manifest.save()?;
+            return Ok(removed);
+        }
+
+        let removed = manifest.clean_all()?;
+        let cache_dir = self.cache_dir();
+        if cache_dir.is_dir() {
+            fs::remove_dir_all(cache_dir)?;
+        }
+        Ok(removed)
+    }
+
+    /// Progress output, silenced when stdout carries machine-readable diagnostics
+    pub(crate) fn progress(&self, message: String) {
+        if self.options.diagnostics_format.is_human() {
+            println!("{}", message);
+        }
     }
 
     pub fn find_rel_files(&self, dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
@@ -90,77 +292,115 @@ impl relCompiler {
         }
 
         visit_dir(dir, &mut files)?;
+        files.retain(|file| self.options.config.is_included(file));
         Ok(files)
     }
 
-    fn compile_file(&self, file_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-        println!("\n{} {}", colors::info("Compiling"), colors::path(&file_path.display().to_string()));
-
-        // Read file content
-        let content = fs::read_to_string(file_path)?;
-        println!("  {} {} bytes", colors::dim("Size:"), content.len());
-
-        // Create module resolver
-        let root_dir = file_path.parent()
-            .ok_or("Cannot determine root directory")?
-            .to_path_buf();
-        let mut resolver = ModuleResolver::new(root_dir);
-
-        // Resolve all dependencies (including imports)
-        let dependencies = resolver.resolve_dependencies(file_path)
-            .map_err(|e| format!("Dependency resolution failed: {}", e))?;
-
+    /// Generate TypeScript for one resolved file; on success returns the code
+    /// with its warnings. Only reads the resolver, so files run in parallel.
+    fn generate(
+        &self,
+        resolver: &ModuleResolver,
+        file_path: &Path,
+        dependencies: &[PathBuf],
+    ) -> Result<Generated, DiagnosticsError> {
         // Get merged AST with all dependencies in correct order
         // Only includes schemas that are exported or used by exported schemas
-        let ast_nodes = resolver.get_merged_ast(&dependencies, file_path)
-            .map_err(|e| format!("Import/Export analysis failed: {}", e))?;
-
-        // Validate naming conventions and best practices
-        if let Err(errors) = validation::validate_ast(&ast_nodes) {
-            eprintln!("\n{}", colors::error("Validation errors:"));
-            for error in &errors {
-                eprintln!("  {} {}", colors::error("•"), error);
-            }
-            return Err(format!("Validation failed with {} error(s)", errors.len()).into());
+        let ast_nodes = resolver.get_merged_ast(dependencies, file_path)
+            .map_err(|diagnostics| DiagnosticsError::new("Import/Export analysis failed", diagnostics))?;
+
+        // Lint the file itself; its dependencies are linted when they are compiled
+        let lints = resolver.lint(file_path, &ast_nodes);
+        if lints.iter().any(Diagnostic::is_error) {
+            return Err(DiagnosticsError::new("Validation failed", lints));
         }
 
         // Generate TypeScript
         let mut generator = TypeScriptGenerator::new();
-        let ts_code = generator.generate(&ast_nodes);
+        let code = generator.generate(&ast_nodes);
 
-        // Determine output path
-        let output_path = self.get_output_path(file_path)?;
-        
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = output_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-        }
+        Ok(Generated { code, warnings: lints })
+    }
 
-        // Write output
-        fs::write(&output_path, ts_code)?;
-        println!("  {} {}", colors::success("Generated"), colors::path(&output_path.display().to_string()));
+    /// The output directory as a directory, for files kept alongside outputs
+    fn output_root(&self) -> PathBuf {
+        let output_dir = self.output_dir();
+        match output_dir.is_file() {
+            true => output_dir.parent().map(Path::to_path_buf).unwrap_or_default(),
+            false => output_dir,
+        }
+    }
 
-        Ok(())
+    fn output_dir(&self) -> PathBuf {
+        self.options.output_dir.clone().unwrap_or_else(|| self.options.input_dir.clone())
     }
 
-    fn get_output_path(&self, input_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let file_stem = input_path.file_stem()
+    /// Where the TypeScript for `input_path` is written: its place in the
+    /// input tree mirrored under the output directory, or a flattened name
+    /// when rel.json sets "flatten"
+    pub fn get_output_path(&self, input_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let relative = self.source_relative_path(input_path);
+        let file_stem = relative.file_stem()
             .ok_or("Invalid file name")?
             .to_str()
             .ok_or("Invalid file name encoding")?;
 
-        let output_dir = self.options.output_dir
-            .as_ref()
-            .unwrap_or(&self.options.input_dir);
-
-        // Create output path with .ts extension
-        let mut output_path = output_dir.join(file_stem);
-        output_path.set_extension("ts");
+        let output_dir = self.output_root();
+        let output_path = match &self.options.config.flatten {
+            Some(pattern) => output_dir.join(format!("{}.ts", config::flatten_name(pattern, &relative))),
+            None => {
+                let subdir = relative.parent().unwrap_or(Path::new(""));
+                output_dir.join(subdir).join(format!("{}.ts", file_stem))
+            }
+        };
 
         Ok(output_path)
     }
+
+    /// `input_path` relative to the innermost input directory holding it; a
+    /// file given directly as an input is just its name
+    fn source_relative_path(&self, input_path: &Path) -> PathBuf {
+        let file = input_path.canonicalize().unwrap_or_else(|_| input_path.to_path_buf());
+        self.inputs().iter()
+            .map(|input| input.canonicalize().unwrap_or_else(|_| input.clone()))
+            .filter(|input| input.is_dir())
+            .filter_map(|input| file.strip_prefix(&input).ok().map(Path::to_path_buf))
+            .min_by_key(|relative| relative.components().count())
+            .unwrap_or_else(|| PathBuf::from(input_path.file_name().unwrap_or_default()))
+    }
+
+    /// Fail when two of `files` would write the same output file
+    pub(crate) fn check_outputs(&self, files: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writers: BTreeMap<PathBuf, &PathBuf> = BTreeMap::new();
+        let mut diagnostics = Vec::new();
+
+        for file_path in files {
+            let output_path = self.get_output_path(file_path)?;
+            let Some(first) = writers.get(&output_path) else {
+                writers.insert(output_path, file_path);
+                continue;
+            };
+
+            let help = match &self.options.config.flatten {
+                Some(pattern) => format!("the \"flatten\" pattern \"{}\" gives both the same name; add {{dir}} or use {{path}}", pattern),
+                None => "every input directory is mirrored into the same output directory; rename one of the files".to_string(),
+            };
+            diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticCode::OutputCollision,
+                    format!("'{}' and '{}' both generate '{}'", first.display(), file_path.display(), output_path.display()),
+                )
+                .with_file(file_path)
+                .with_help(help)
+            );
+        }
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(DiagnosticsError::new("Output collision", diagnostics).into())
+        }
+    }
 }
 
 // Runtime API for direct usage (like the rel class mentioned in the design)
@@ -180,12 +420,14 @@ impl rel {
 
         let lexer = Lexer::new(&content);
         let tokens = lexer.tokenize().map_err(|errors| {
-            format!("Tokenization failed: {:?}", errors)
+            let diagnostics = errors.iter().map(|e| Diagnostic::from(e).with_file(file_path)).collect();
+            DiagnosticsError::new("Tokenization failed", diagnostics)
         })?;
 
         let mut parser = Parser::new(tokens);
         let ast_nodes = parser.parse().map_err(|errors| {
-            format!("Parsing failed: {:?}", errors)
+            let diagnostics = errors.iter().map(|e| Diagnostic::from(e).with_file(file_path)).collect();
+            DiagnosticsError::new("Parsing failed", diagnostics)
         })?;
 
         let mut generator = TypeScriptGenerator::new();
@@ -0,0 +1,408 @@
+/*!
+ * Config Module
+ *
+ * Loads `rel.json` project configuration, found by searching upward from
+ * the working directory. Command-line flags override config values.
+ *
+ *   {
+ *     "schemas": ["./schemas", "./shared"],
+ *     "output": "./generated",
+ *     "targets": ["typescript"],
+ *     "include": ["schemas", "shared"],
+ *     "exclude": ["drafts", "*.draft.rel"],
+ *     "aliases": { "@shared": "./shared" },
+ *     "paths": ["../common-schemas"],
+ *     "flatten": "{dir}_{name}",
+ *     "lint": { "naming-convention": "warn", "unused-import": "deny" }
+ *   }
+ */
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::compiler::CompilerOptions;
+use crate::lint;
+use crate::diagnostics::{Diagnostic, DiagnosticCode, DiagnosticsFormat, Severity};
+
+pub const CONFIG_FILE: &str = "rel.json";
+
+/// Code generation targets; TypeScript is the only one today
+pub const TARGETS: &[&str] = &["typescript"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+// ============================================================================
+// SECTION: File Format
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// `rel.json` as written; unknown keys are rejected so typos don't go unnoticed
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    #[allow(dead_code)]
+    name: Option<String>,
+    #[allow(dead_code)]
+    version: Option<String>,
+    schemas: Option<OneOrMany>,
+    output: Option<String>,
+    targets: Option<Vec<String>>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    paths: Vec<String>,
+    flatten: Option<String>,
+    #[serde(default)]
+    lint: BTreeMap<String, LintLevel>,
+}
+
+// ============================================================================
+// SECTION: Project Config
+// ============================================================================
+
+/// Project settings with every path resolved against the config's directory
+#[derive(Debug, Clone, Default)]
+pub struct ProjectConfig {
+    /// The `rel.json` this came from, if any
+    pub path: Option<PathBuf>,
+    /// Directory containing `rel.json`; include/exclude globs are relative to it
+    pub root: PathBuf,
+    pub inputs: Vec<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub targets: Vec<String>,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    pub aliases: BTreeMap<String, PathBuf>,
+    /// Search paths for package-style imports such as `@acme/common/money`
+    pub paths: Vec<PathBuf>,
+    /// Output file name pattern; when unset, outputs mirror the input tree
+    pub flatten: Option<String>,
+    pub lint: BTreeMap<String, LintLevel>,
+}
+
+impl ProjectConfig {
+    /// Find `rel.json` in `start` or its nearest ancestor
+    pub fn discover(start: &Path) -> Result<Option<Self>, String> {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        for dir in start.ancestors() {
+            let candidate = dir.join(CONFIG_FILE);
+            if candidate.is_file() {
+                return Self::load(&candidate).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let raw: RawConfig = serde_json::from_str(&content).map_err(|e| {
+            format!("Invalid {} at line {}, column {}: {}", path.display(), e.line(), e.column(), strip_position(&e))
+        })?;
+
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::from_raw(raw, root, Some(path.to_path_buf()))
+            .map_err(|message| format!("Invalid {}: {}", path.display(), message))
+    }
+
+    fn from_raw(raw: RawConfig, root: PathBuf, path: Option<PathBuf>) -> Result<Self, String> {
+        let inputs = match raw.schemas {
+            None => Vec::new(),
+            Some(OneOrMany::One(dir)) => vec![join(&root, &dir)],
+            Some(OneOrMany::Many(dirs)) if dirs.is_empty() => return Err("\"schemas\" must list at least one path".to_string()),
+            Some(OneOrMany::Many(dirs)) => dirs.iter().map(|dir| join(&root, dir)).collect(),
+        };
+
+        let targets = raw.targets.unwrap_or_else(|| vec![TARGETS[0].to_string()]);
+        if let Some(unknown) = targets.iter().find(|t| !TARGETS.contains(&t.as_str())) {
+            return Err(format!("unknown target \"{}\" (supported: {})", unknown, TARGETS.join(", ")));
+        }
+
+        for rule in raw.lint.keys() {
+            if lint::rule_named(rule).is_none() {
+                let known: Vec<&str> = lint::RULES.iter().map(|rule| rule.name).collect();
+                return Err(format!("unknown lint rule \"{}\" (known rules: {})", rule, known.join(", ")));
+            }
+        }
+
+        for alias in raw.aliases.keys() {
+            if alias.is_empty() || alias.starts_with('.') || alias.ends_with('/') {
+                return Err(format!("alias \"{}\" must be a bare prefix such as \"@shared\"", alias));
+            }
+        }
+
+        if let Some(pattern) = &raw.flatten {
+            validate_flatten(pattern).map_err(|e| format!("\"flatten\": {}", e))?;
+        }
+
+        Ok(Self {
+            path,
+            inputs,
+            output: raw.output.map(|dir| join(&root, &dir)),
+            targets,
+            include: raw.include.iter().map(|g| glob_to_regex(g)).collect::<Result<_, _>>()?,
+            exclude: raw.exclude.iter().map(|g| glob_to_regex(g)).collect::<Result<_, _>>()?,
+            aliases: raw.aliases.into_iter().map(|(alias, dir)| (alias, join(&root, &dir))).collect(),
+            paths: raw.paths.iter().map(|dir| join(&root, dir)).collect(),
+            flatten: raw.flatten,
+            lint: raw.lint,
+            root,
+        })
+    }
+
+    /// Whether `path` passes the include/exclude globs. A pattern matches a
+    /// file or any of its parent directories, so `"drafts"` excludes a folder.
+    pub fn is_included(&self, path: &Path) -> bool {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return true;
+        }
+
+        let root = self.root.canonicalize().unwrap_or_else(|_| self.root.clone());
+        let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let relative = absolute.strip_prefix(&root).unwrap_or(&absolute);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+
+        // The path itself plus each ancestor directory, e.g. `a`, `a/b`, `a/b/c.rel`
+        let candidates: Vec<&str> = relative.match_indices('/')
+            .map(|(i, _)| &relative[..i])
+            .chain(std::iter::once(relative.as_str()))
+            .collect();
+        let matches = |patterns: &[Regex]| candidates.iter().any(|c| patterns.iter().any(|p| p.is_match(c)));
+
+        (self.include.is_empty() || matches(&self.include)) && !matches(&self.exclude)
+    }
+
+    /// Rewrite an aliased import path (`@shared/address.rel`); the longest alias wins
+    pub fn resolve_alias(&self, import_path: &str) -> Option<PathBuf> {
+        self.aliases.iter()
+            .filter(|(alias, _)| {
+                import_path == alias.as_str()
+                    || import_path.strip_prefix(alias.as_str()).is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(alias, _)| alias.len())
+            .map(|(alias, dir)| dir.join(import_path[alias.len()..].trim_start_matches('/')))
+    }
+
+    /// Configured level for the rule that produces `code`, else the rule's
+    /// default; diagnostics that aren't lints are always denied
+    pub fn lint_level(&self, code: DiagnosticCode) -> LintLevel {
+        lint::rule_for(code)
+            .map(|rule| self.lint.get(rule.name).copied().unwrap_or(rule.default))
+            .unwrap_or(LintLevel::Deny)
+    }
+
+    /// Drop allowed diagnostics and downgrade warned ones
+    pub fn apply_lint(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics.into_iter()
+            .filter_map(|mut diagnostic| match self.lint_level(diagnostic.code) {
+                LintLevel::Allow => None,
+                LintLevel::Warn => {
+                    diagnostic.severity = Severity::Warning;
+                    Some(diagnostic)
+                }
+                LintLevel::Deny => Some(diagnostic),
+            })
+            .collect()
+    }
+}
+
+/// Combine command-line flags with `rel.json` from the working directory
+/// upward; flags win, and config paths are relative to the config file
+pub fn compiler_options(
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    watch: bool,
+    diagnostics_format: DiagnosticsFormat,
+) -> Result<CompilerOptions, Box<dyn std::error::Error>> {
+    let mut config = ProjectConfig::discover(&std::env::current_dir()?)?.unwrap_or_default();
+
+    if let Some(input) = input {
+        config.inputs = vec![input];
+    }
+    if output.is_some() {
+        config.output = output;
+    }
+
+    let input_dir = config.inputs.first().cloned().ok_or_else(|| match &config.path {
+        Some(path) => format!("No input given: pass --input or set \"schemas\" in {}", path.display()),
+        None => format!("No input given: pass --input or create a {} with a \"schemas\" entry", CONFIG_FILE),
+    })?;
+
+    Ok(CompilerOptions {
+        input_dir,
+        output_dir: config.output.clone(),
+        watch,
+        diagnostics_format,
+        incremental: true,
+        jobs: crate::parallel::default_jobs(),
+        config,
+    })
+}
+
+// ============================================================================
+// SECTION: Flattened Output Names
+// ============================================================================
+
+/// Placeholders a `flatten` pattern may use, for `billing/invoice/line.rel`:
+/// `{name}` is `line`, `{dir}` is `billing_invoice`, `{path}` is `billing_invoice_line`
+pub const FLATTEN_PLACEHOLDERS: &[&str] = &["{name}", "{dir}", "{path}"];
+
+/// A pattern must name the file and stay inside the output directory
+pub fn validate_flatten(pattern: &str) -> Result<(), String> {
+    if pattern.contains('/') || pattern.contains('\\') {
+        return Err(format!("pattern \"{}\" must not contain path separators", pattern));
+    }
+
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').map(|end| start + end + 1)
+            .ok_or_else(|| format!("pattern \"{}\" has an unclosed '{{'", pattern))?;
+        let placeholder = &rest[start..end];
+        if !FLATTEN_PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!("unknown placeholder {} (use {})", placeholder, FLATTEN_PLACEHOLDERS.join(", ")));
+        }
+        rest = &rest[end..];
+    }
+
+    if !pattern.contains("{name}") && !pattern.contains("{path}") {
+        return Err(format!("pattern \"{}\" must include {{name}} or {{path}}", pattern));
+    }
+    Ok(())
+}
+
+/// The output file stem for `relative`, a source path relative to its input
+/// directory. Separators left dangling by an empty `{dir}` are trimmed.
+pub fn flatten_name(pattern: &str, relative: &Path) -> String {
+    let name = relative.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let dir: Vec<String> = relative.parent().into_iter()
+        .flat_map(|parent| parent.components())
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let path = dir.iter().cloned().chain(std::iter::once(name.clone())).collect::<Vec<_>>().join("_");
+
+    pattern
+        .replace("{name}", &name)
+        .replace("{dir}", &dir.join("_"))
+        .replace("{path}", &path)
+        .trim_matches(|c| c == '_' || c == '-' || c == '.')
+        .to_string()
+}
+
+// ============================================================================
+// SECTION: Globs
+// ============================================================================
+
+/// `**` spans directories, `*` and `?` stay within one path segment. As in
+/// .gitignore, a pattern without `/` matches a name at any depth.
+fn glob_to_regex(glob: &str) -> Result<Regex, String> {
+    let glob = glob.trim_start_matches("./");
+    let mut pattern = String::from(if glob.contains('/') { "^" } else { "^(?:.*/)?" });
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).map_err(|e| format!("invalid glob \"{}\": {}", glob, e))
+}
+
+/// `root/./schemas` reads as `root/schemas` in messages and output paths
+fn join(root: &Path, relative: &str) -> PathBuf {
+    let mut path = root.to_path_buf();
+    path.extend(Path::new(relative).components().filter(|c| *c != std::path::Component::CurDir));
+    path
+}
+
+/// serde_json appends " at line X column Y", which the caller already reports
+fn strip_position(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> Result<ProjectConfig, String> {
+        let raw: RawConfig = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        ProjectConfig::from_raw(raw, PathBuf::from("/project"), None)
+    }
+
+    #[test]
+    fn test_globs_aliases_and_lint() {
+        let config = config(r#"{
+            "schemas": "./schemas",
+            "include": ["schemas/**/*.rel"],
+            "exclude": ["drafts", "*.draft.rel"],
+            "aliases": { "@shared": "./shared" },
+            "lint": { "naming-convention": "allow" }
+        }"#).unwrap();
+
+        assert_eq!(config.inputs, vec![PathBuf::from("/project/schemas")]);
+        assert!(config.is_included(Path::new("/project/schemas/user.rel")));
+        assert!(config.is_included(Path::new("/project/schemas/api/order.rel")));
+        assert!(!config.is_included(Path::new("/project/schemas/drafts/wip.rel")));
+        assert!(!config.is_included(Path::new("/project/schemas/api/order.draft.rel")));
+        assert!(!config.is_included(Path::new("/project/other/user.rel")));
+
+        assert_eq!(config.resolve_alias("@shared/address.rel"), Some(PathBuf::from("/project/shared/address.rel")));
+        assert_eq!(config.resolve_alias("@sharedx/address.rel"), None);
+        assert_eq!(config.lint_level(DiagnosticCode::NamingConvention), LintLevel::Allow);
+        assert_eq!(config.lint_level(DiagnosticCode::UnusedImport), LintLevel::Deny);
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        assert!(config(r#"{ "outptu": "./generated" }"#).unwrap_err().contains("unknown field `outptu`"));
+        assert!(config(r#"{ "targets": ["rust"] }"#).unwrap_err().contains("unknown target \"rust\""));
+        assert!(config(r#"{ "lint": { "naming": "warn" } }"#).unwrap_err().contains("unknown lint rule \"naming\""));
+        assert!(config(r#"{ "flatten": "{file}" }"#).unwrap_err().contains("unknown placeholder {file}"));
+        assert!(config(r#"{ "flatten": "{dir}" }"#).unwrap_err().contains("must include {name} or {path}"));
+    }
+
+    #[test]
+    fn test_flatten_names() {
+        let nested = Path::new("billing/invoice/line.rel");
+        assert_eq!(flatten_name("{name}", nested), "line");
+        assert_eq!(flatten_name("{dir}_{name}", nested), "billing_invoice_line");
+        assert_eq!(flatten_name("{path}.schema", nested), "billing_invoice_line.schema");
+        assert_eq!(flatten_name("{dir}_{name}", Path::new("user.rel")), "user");
+    }
+}
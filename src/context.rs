@@ -1,4 +1,4 @@
-/**
+/*!
  * Compilation Context - Stores variables, types, and functions during compilation
  */
 
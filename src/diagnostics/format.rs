@@ -0,0 +1,229 @@
+/*!
+ * Diagnostics Output Formats - human snippets, JSON and SARIF 2.1.0
+ */
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use super::{emit, word_length, Diagnostic, DiagnosticCode, Label, Severity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DiagnosticsFormat {
+    /// Colored source snippets on stderr
+    #[default]
+    Human,
+    /// One JSON document on stdout
+    Json,
+    /// A SARIF 2.1.0 log on stdout, for CI code scanning
+    Sarif,
+}
+
+impl DiagnosticsFormat {
+    /// Machine-readable formats own stdout, so progress output must stay quiet
+    pub fn is_human(self) -> bool {
+        self == DiagnosticsFormat::Human
+    }
+}
+
+/// Print `diagnostics` in `format`; machine-readable formats always print a
+/// document, even when it is empty, so tools can parse every run
+pub fn report(diagnostics: &[Diagnostic], format: DiagnosticsFormat) {
+    match format {
+        DiagnosticsFormat::Human => emit(diagnostics),
+        DiagnosticsFormat::Json => println!("{}", to_pretty(&to_json(diagnostics))),
+        DiagnosticsFormat::Sarif => println!("{}", to_pretty(&to_sarif(diagnostics))),
+    }
+}
+
+fn to_pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+// ============================================================================
+// SECTION: JSON
+// ============================================================================
+
+pub fn to_json(diagnostics: &[Diagnostic]) -> Value {
+    let mut sources = SourceCache::default();
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+
+    json!({
+        "version": 1,
+        "diagnostics": diagnostics.iter().map(|d| {
+            let mut labels = Vec::new();
+            if let Some(primary) = &d.primary {
+                labels.push(label_json(primary, true, d.file.as_deref(), &mut sources));
+            }
+            for secondary in &d.secondary {
+                labels.push(label_json(secondary, false, d.file.as_deref(), &mut sources));
+            }
+            json!({
+                "code": d.code.as_str(),
+                "severity": d.severity.as_str(),
+                "message": d.message,
+                "file": d.file.as_ref().map(|f| f.display().to_string()),
+                "labels": labels,
+                "help": d.help,
+            })
+        }).collect::<Vec<_>>(),
+        "summary": { "errors": errors, "warnings": diagnostics.len() - errors },
+    })
+}
+
+fn label_json(label: &Label, primary: bool, file: Option<&Path>, sources: &mut SourceCache) -> Value {
+    json!({
+        "primary": primary,
+        "line": label.span.line,
+        "column": label.span.column,
+        "length": sources.length(file, label),
+        "message": label.message,
+    })
+}
+
+// ============================================================================
+// SECTION: SARIF
+// ============================================================================
+
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> Value {
+    let mut sources = SourceCache::default();
+
+    let rules: Vec<Value> = DiagnosticCode::ALL.iter()
+        .map(|code| json!({
+            "id": code.as_str(),
+            "name": format!("{:?}", code),
+            "shortDescription": { "text": code.description() },
+        }))
+        .collect();
+
+    let results: Vec<Value> = diagnostics.iter()
+        .map(|d| {
+            let text = match &d.help {
+                Some(help) => format!("{}\nhelp: {}", d.message, help),
+                None => d.message.clone(),
+            };
+            let mut result = json!({
+                "ruleId": d.code.as_str(),
+                "ruleIndex": DiagnosticCode::ALL.iter().position(|c| *c == d.code),
+                "level": match d.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": { "text": text },
+            });
+
+            if let Some(file) = &d.file {
+                let mut location = json!({ "artifactLocation": { "uri": artifact_uri(file) } });
+                if let Some(primary) = &d.primary {
+                    location["region"] = region(primary, Some(file), &mut sources);
+                }
+                result["locations"] = json!([{ "physicalLocation": location }]);
+
+                if !d.secondary.is_empty() {
+                    result["relatedLocations"] = d.secondary.iter().enumerate()
+                        .map(|(id, label)| json!({
+                            "id": id,
+                            "physicalLocation": {
+                                "artifactLocation": { "uri": artifact_uri(file) },
+                                "region": region(label, Some(file), &mut sources),
+                            },
+                            "message": { "text": label.message },
+                        }))
+                        .collect();
+                }
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "rel",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// SARIF columns are one-based and `endColumn` is exclusive
+fn region(label: &Label, file: Option<&Path>, sources: &mut SourceCache) -> Value {
+    json!({
+        "startLine": label.span.line,
+        "startColumn": label.span.column,
+        "endColumn": label.span.column + sources.length(file, label),
+    })
+}
+
+/// Paths relative to the working directory, with forward slashes, as code
+/// scanning tools expect
+fn artifact_uri(file: &Path) -> String {
+    let relative = std::env::current_dir().ok()
+        .and_then(|cwd| file.strip_prefix(&cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| file.to_path_buf());
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+// ============================================================================
+// SECTION: Span Lengths
+// ============================================================================
+
+/// Source files read once per report, to turn "the word here" spans into lengths
+#[derive(Default)]
+struct SourceCache {
+    files: HashMap<PathBuf, Option<String>>,
+}
+
+impl SourceCache {
+    fn length(&mut self, file: Option<&Path>, label: &Label) -> usize {
+        if label.span.length > 0 {
+            return label.span.length;
+        }
+
+        let line = file.and_then(|file| {
+            self.files
+                .entry(file.to_path_buf())
+                .or_insert_with(|| fs::read_to_string(file).ok())
+                .as_deref()
+                .and_then(|source| source.lines().nth(label.span.line - 1))
+        });
+
+        line.map(|line| {
+            let chars: Vec<char> = line.chars().skip(label.span.column - 1).collect();
+            word_length(&chars)
+        })
+        .unwrap_or(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Span;
+
+    #[test]
+    fn test_sarif_result_references_rule_and_region() {
+        let diagnostic = Diagnostic::error(DiagnosticCode::NamingConvention, "Schema 'user' should start with uppercase letter")
+            .with_file("schemas/user.rel")
+            .with_span(Span::new(3, 1, 11))
+            .with_help("rename it to 'User'");
+
+        let sarif = to_sarif(&[diagnostic]);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "REL0400");
+        assert_eq!(result["level"], "error");
+        assert_eq!(result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "schemas/user.rel");
+        assert_eq!(result["locations"][0]["physicalLocation"]["region"]["endColumn"], 12);
+
+        let rule_index = result["ruleIndex"].as_u64().unwrap() as usize;
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"][rule_index]["id"], "REL0400");
+    }
+}
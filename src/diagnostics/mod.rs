@@ -0,0 +1,668 @@
+/*!
+ * Diagnostics Module
+ *
+ * Shared error reporting for every compiler phase. Each diagnostic carries a
+ * stable code (`REL0001`...), a severity, optional primary/secondary spans and
+ * a `help:` suggestion, and renders as an underlined source snippet:
+ *
+ *   error[REL0302]: Undefined type 'Adress'
+ *    --> user.rel:4:12
+ *     |
+ *   4 |   address: Adress
+ *     |            ^^^^^^ not defined in this scope
+ *     |
+ *     = help: did you mean 'Address'?
+ */
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{ParseError, SourceLocation};
+use crate::colors::Colors;
+use crate::interpreter::RuntimeError;
+use crate::lexer::LexerError;
+
+mod format;
+
+pub use format::{report, to_json, to_sarif, DiagnosticsFormat};
+
+const TAB_WIDTH: usize = 4;
+
+// ============================================================================
+// SECTION: Codes
+// ============================================================================
+
+/// Stable error codes, grouped by phase: 00xx lexer, 01xx parser,
+/// 02xx modules, 03xx semantics, 04xx style, 05xx interpreter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiagnosticCode {
+    UnexpectedCharacter,
+    UnterminatedString,
+    InvalidEscape,
+    InvalidNumber,
+    SyntaxError,
+    ModuleNotFound,
+    NotExported,
+    CircularDependency,
+    UnusedImport,
+    FileReadError,
+    OutputCollision,
+    ExportConflict,
+    DuplicateField,
+    DuplicateEnumValue,
+    UndefinedType,
+    ConflictingConstraints,
+    InapplicableConstraint,
+    InvalidRegex,
+    UnsupportedConstraint,
+    InvalidRecordKey,
+    InvalidDiscriminator,
+    DuplicateDiscriminator,
+    NotAnObjectType,
+    ConflictingIntersection,
+    UnknownField,
+    NamingConvention,
+    UnusedSchema,
+    UnreachableBranch,
+    PermissiveAny,
+    RedundantConstraint,
+    UndefinedVariable,
+    DivisionByZero,
+    InvalidOperation,
+    UnknownFunction,
+}
+
+impl DiagnosticCode {
+    pub const ALL: &'static [DiagnosticCode] = &[
+        DiagnosticCode::UnexpectedCharacter,
+        DiagnosticCode::UnterminatedString,
+        DiagnosticCode::InvalidEscape,
+        DiagnosticCode::InvalidNumber,
+        DiagnosticCode::SyntaxError,
+        DiagnosticCode::ModuleNotFound,
+        DiagnosticCode::NotExported,
+        DiagnosticCode::CircularDependency,
+        DiagnosticCode::UnusedImport,
+        DiagnosticCode::FileReadError,
+        DiagnosticCode::OutputCollision,
+        DiagnosticCode::ExportConflict,
+        DiagnosticCode::DuplicateField,
+        DiagnosticCode::DuplicateEnumValue,
+        DiagnosticCode::UndefinedType,
+        DiagnosticCode::ConflictingConstraints,
+        DiagnosticCode::InapplicableConstraint,
+        DiagnosticCode::InvalidRegex,
+        DiagnosticCode::UnsupportedConstraint,
+        DiagnosticCode::InvalidRecordKey,
+        DiagnosticCode::InvalidDiscriminator,
+        DiagnosticCode::DuplicateDiscriminator,
+        DiagnosticCode::NotAnObjectType,
+        DiagnosticCode::ConflictingIntersection,
+        DiagnosticCode::UnknownField,
+        DiagnosticCode::NamingConvention,
+        DiagnosticCode::UnusedSchema,
+        DiagnosticCode::UnreachableBranch,
+        DiagnosticCode::PermissiveAny,
+        DiagnosticCode::RedundantConstraint,
+        DiagnosticCode::UndefinedVariable,
+        DiagnosticCode::DivisionByZero,
+        DiagnosticCode::InvalidOperation,
+        DiagnosticCode::UnknownFunction,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticCode::UnexpectedCharacter => "REL0001",
+            DiagnosticCode::UnterminatedString => "REL0002",
+            DiagnosticCode::InvalidEscape => "REL0003",
+            DiagnosticCode::InvalidNumber => "REL0004",
+            DiagnosticCode::SyntaxError => "REL0100",
+            DiagnosticCode::ModuleNotFound => "REL0200",
+            DiagnosticCode::NotExported => "REL0201",
+            DiagnosticCode::CircularDependency => "REL0202",
+            DiagnosticCode::UnusedImport => "REL0203",
+            DiagnosticCode::FileReadError => "REL0204",
+            DiagnosticCode::OutputCollision => "REL0205",
+            DiagnosticCode::ExportConflict => "REL0206",
+            DiagnosticCode::DuplicateField => "REL0300",
+            DiagnosticCode::DuplicateEnumValue => "REL0301",
+            DiagnosticCode::UndefinedType => "REL0302",
+            DiagnosticCode::ConflictingConstraints => "REL0303",
+            DiagnosticCode::InapplicableConstraint => "REL0304",
+            DiagnosticCode::InvalidRegex => "REL0305",
+            DiagnosticCode::UnsupportedConstraint => "REL0306",
+            DiagnosticCode::InvalidRecordKey => "REL0307",
+            DiagnosticCode::InvalidDiscriminator => "REL0308",
+            DiagnosticCode::DuplicateDiscriminator => "REL0309",
+            DiagnosticCode::NotAnObjectType => "REL0310",
+            DiagnosticCode::ConflictingIntersection => "REL0311",
+            DiagnosticCode::UnknownField => "REL0312",
+            DiagnosticCode::NamingConvention => "REL0400",
+            DiagnosticCode::UnusedSchema => "REL0401",
+            DiagnosticCode::UnreachableBranch => "REL0402",
+            DiagnosticCode::PermissiveAny => "REL0403",
+            DiagnosticCode::RedundantConstraint => "REL0404",
+            DiagnosticCode::UndefinedVariable => "REL0500",
+            DiagnosticCode::DivisionByZero => "REL0501",
+            DiagnosticCode::InvalidOperation => "REL0502",
+            DiagnosticCode::UnknownFunction => "REL0503",
+        }
+    }
+
+    /// One-line explanation, used by the docs table and machine-readable output
+    pub fn description(self) -> &'static str {
+        match self {
+            DiagnosticCode::UnexpectedCharacter => "character not valid in .rel source",
+            DiagnosticCode::UnterminatedString => "string literal is never closed",
+            DiagnosticCode::InvalidEscape => "unknown or incomplete escape sequence",
+            DiagnosticCode::InvalidNumber => "malformed number literal",
+            DiagnosticCode::SyntaxError => "source does not match the grammar",
+            DiagnosticCode::ModuleNotFound => "imported file does not exist",
+            DiagnosticCode::NotExported => "imported name is not exported by the module",
+            DiagnosticCode::CircularDependency => "modules import each other",
+            DiagnosticCode::UnusedImport => "imported name is never used",
+            DiagnosticCode::FileReadError => "file could not be read",
+            DiagnosticCode::OutputCollision => "two inputs would generate the same output file",
+            DiagnosticCode::ExportConflict => "two `export *` statements export different types under one name",
+            DiagnosticCode::DuplicateField => "field is defined twice in one schema",
+            DiagnosticCode::DuplicateEnumValue => "value is listed twice in one enum",
+            DiagnosticCode::UndefinedType => "type is not defined or imported",
+            DiagnosticCode::ConflictingConstraints => "constraints on one field can never all hold",
+            DiagnosticCode::InapplicableConstraint => "constraint does not apply to the field's type",
+            DiagnosticCode::InvalidRegex => "`matches` pattern is not a valid regular expression",
+            DiagnosticCode::UnsupportedConstraint => "constraint or type can't be expressed in the generated output",
+            DiagnosticCode::InvalidRecordKey => "record key type is not a string or number",
+            DiagnosticCode::InvalidDiscriminator => "union of schemas has no usable discriminator",
+            DiagnosticCode::DuplicateDiscriminator => "two variants of a discriminated union have the same tag",
+            DiagnosticCode::NotAnObjectType => "intersection member or utility type argument is not an object type",
+            DiagnosticCode::ConflictingIntersection => "intersected types define one field with different types",
+            DiagnosticCode::UnknownField => "`Pick` or `Omit` names a field the type doesn't have",
+            DiagnosticCode::NamingConvention => "name does not follow naming conventions",
+            DiagnosticCode::UnusedSchema => "declaration is neither exported nor used in its file",
+            DiagnosticCode::UnreachableBranch => "`when` or `else` branch can never apply",
+            DiagnosticCode::PermissiveAny => "`any` accepts every value",
+            DiagnosticCode::RedundantConstraint => "constraint is repeated or implied by another",
+            DiagnosticCode::UndefinedVariable => "variable is used before it is declared",
+            DiagnosticCode::DivisionByZero => "division by zero",
+            DiagnosticCode::InvalidOperation => "operator or function applied to the wrong values",
+            DiagnosticCode::UnknownFunction => "function is not built in",
+        }
+    }
+}
+
+impl DiagnosticCode {
+    /// Failure class that decides the process exit code
+    pub fn failure_class(self) -> FailureClass {
+        use DiagnosticCode::*;
+        match self {
+            UnexpectedCharacter | UnterminatedString | InvalidEscape | InvalidNumber | SyntaxError => FailureClass::Syntax,
+            ModuleNotFound | NotExported | CircularDependency | UnusedImport | FileReadError | OutputCollision | ExportConflict => FailureClass::Module,
+            DuplicateField | DuplicateEnumValue | UndefinedType | ConflictingConstraints | InapplicableConstraint | InvalidRegex | UnsupportedConstraint | InvalidRecordKey
+            | InvalidDiscriminator | DuplicateDiscriminator | NotAnObjectType | ConflictingIntersection | UnknownField => FailureClass::Semantic,
+            NamingConvention | UnusedSchema | UnreachableBranch | PermissiveAny | RedundantConstraint => FailureClass::Style,
+            UndefinedVariable | DivisionByZero | InvalidOperation | UnknownFunction => FailureClass::Runtime,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Process exit codes by failure class; when several classes fail, the
+/// earliest phase wins. 1 is any other error and 2 is a CLI usage error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FailureClass {
+    Syntax = 3,
+    Module = 4,
+    Semantic = 5,
+    Style = 6,
+    Runtime = 7,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+// ============================================================================
+// SECTION: Diagnostic
+// ============================================================================
+
+/// One-based source position; a `length` of 0 covers the word at that position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Self {
+        Self { line, column, length }
+    }
+
+    /// The word starting at `line:column`
+    pub fn at(line: usize, column: usize) -> Self {
+        Self::new(line, column, 0)
+    }
+
+    /// Positions of 0 mean "unknown" throughout the AST
+    pub fn is_known(&self) -> bool {
+        self.line > 0 && self.column > 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<PathBuf>,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: Severity::Error,
+            message: message.into(),
+            file: None,
+            primary: None,
+            secondary: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn warning(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, ..Self::error(code, message) }
+    }
+
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Set the file only if none was recorded by an earlier phase
+    pub fn or_file(mut self, file: &Path) -> Self {
+        if self.file.is_none() {
+            self.file = Some(file.to_path_buf());
+        }
+        self
+    }
+
+    /// Primary span without a label; unknown positions are ignored
+    pub fn with_span(self, span: Span) -> Self {
+        self.with_label(span, "")
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        if span.is_known() {
+            self.primary = Some(Label { span, message: message.into() });
+        }
+        self
+    }
+
+    /// Primary label at an AST location, taking the file from it when recorded
+    pub fn with_location(mut self, location: &SourceLocation, message: impl Into<String>) -> Self {
+        if let Some(file) = &location.file_path {
+            self.file = Some(PathBuf::from(file));
+        }
+        self.with_label(Span::at(location.line, location.column), message)
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        if span.is_known() {
+            self.secondary.push(Label { span, message: message.into() });
+        }
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Render with a snippet read from `self.file`
+    pub fn render(&self) -> String {
+        let source = self.file.as_ref().and_then(|file| fs::read_to_string(file).ok());
+        self.render_with_source(source.as_deref())
+    }
+
+    /// Render with a snippet taken from `source`; without it only the header is shown
+    pub fn render_with_source(&self, source: Option<&str>) -> String {
+        let severity_color = match self.severity {
+            Severity::Error => Colors::RED,
+            Severity::Warning => Colors::YELLOW,
+        };
+        let mut out = format!(
+            "{}{}{}[{}]{}{}: {}{}\n",
+            Colors::BOLD, severity_color, self.severity.as_str(), self.code,
+            Colors::RESET, Colors::BOLD, self.message, Colors::RESET
+        );
+
+        let lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+        let mut labels: Vec<(&Label, bool)> = self.primary.iter().map(|l| (l, true))
+            .chain(self.secondary.iter().map(|l| (l, false)))
+            .filter(|(l, _)| l.span.line <= lines.len())
+            .collect();
+        labels.sort_by_key(|(l, primary)| (l.span.line, !*primary));
+
+        let gutter_width = labels.iter()
+            .map(|(l, _)| l.span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(gutter_width);
+        let bar = format!("{}{}|{}", Colors::BRIGHT_BLUE, Colors::BOLD, Colors::RESET);
+
+        if let Some(location) = self.location() {
+            out.push_str(&format!(
+                "{}{}{}-->{} {}\n",
+                gutter, Colors::BRIGHT_BLUE, Colors::BOLD, Colors::RESET, location
+            ));
+        }
+
+        if !labels.is_empty() {
+            out.push_str(&format!("{} {}\n", gutter, bar));
+            let mut previous_line = None;
+            for (label, primary) in &labels {
+                let line_number = label.span.line;
+                let text = lines[line_number - 1];
+
+                if previous_line != Some(line_number) {
+                    if previous_line.is_some_and(|p| line_number > p + 1) {
+                        out.push_str(&format!("{}{}...{}\n", Colors::BRIGHT_BLUE, Colors::BOLD, Colors::RESET));
+                    }
+                    out.push_str(&format!(
+                        "{}{:>width$} |{} {}\n",
+                        Colors::BRIGHT_BLUE, line_number, Colors::RESET,
+                        expand_tabs(text), width = gutter_width
+                    ));
+                    previous_line = Some(line_number);
+                }
+
+                let (offset, width) = underline_extent(text, label.span);
+                let (marker, color) = if *primary { ('^', severity_color) } else { ('-', Colors::BRIGHT_BLUE) };
+                let message = if label.message.is_empty() { String::new() } else { format!(" {}", label.message) };
+                out.push_str(&format!(
+                    "{} {} {}{}{}{}{}{}\n",
+                    gutter, bar, " ".repeat(offset), Colors::BOLD, color,
+                    marker.to_string().repeat(width), message, Colors::RESET
+                ));
+            }
+        }
+
+        if let Some(help) = &self.help {
+            if !labels.is_empty() {
+                out.push_str(&format!("{} {}\n", gutter, bar));
+            }
+            out.push_str(&format!(
+                "{} {}{}={} {}help:{} {}\n",
+                gutter, Colors::BRIGHT_BLUE, Colors::BOLD, Colors::RESET,
+                Colors::BOLD, Colors::RESET, help
+            ));
+        }
+
+        out
+    }
+
+    /// `file:line:column`, or whichever parts are known
+    fn location(&self) -> Option<String> {
+        let position = self.primary.as_ref().map(|l| format!("{}:{}", l.span.line, l.span.column));
+        match (&self.file, position) {
+            (Some(file), Some(position)) => Some(format!("{}:{}", file.display(), position)),
+            (Some(file), None) => Some(file.display().to_string()),
+            (None, Some(position)) => Some(position),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Compact single-line form: `file:line:col: error[CODE]: message`
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = self.location() {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}[{}]: {}", self.severity.as_str(), self.code, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+// ============================================================================
+// SECTION: Errors
+// ============================================================================
+
+/// Error returned by commands that failed with diagnostics; the command
+/// handler reports the diagnostics and `main` maps them to an exit code
+#[derive(Debug)]
+pub struct DiagnosticsError {
+    pub context: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticsError {
+    pub fn new(context: impl Into<String>, diagnostics: Vec<Diagnostic>) -> Self {
+        Self { context: context.into(), diagnostics }
+    }
+}
+
+impl fmt::Display for DiagnosticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, summary(&self.diagnostics))
+    }
+}
+
+impl std::error::Error for DiagnosticsError {}
+
+/// Diagnostics carried by an error, if it is a `DiagnosticsError`
+pub fn diagnostics_of<'a>(error: &'a (dyn std::error::Error + 'static)) -> &'a [Diagnostic] {
+    error.downcast_ref::<DiagnosticsError>()
+        .map(|e| e.diagnostics.as_slice())
+        .unwrap_or(&[])
+}
+
+/// Exit code for a failed command: the failure class of its earliest-phase
+/// error diagnostic, or 1 for errors without diagnostics
+pub fn exit_code(error: &(dyn std::error::Error + 'static)) -> i32 {
+    diagnostics_of(error).iter()
+        .filter(|d| d.is_error())
+        .map(|d| d.code.failure_class())
+        .min()
+        .map_or(1, |class| class as i32)
+}
+
+// ============================================================================
+// SECTION: Phase Conversions
+// ============================================================================
+
+impl From<&LexerError> for Diagnostic {
+    fn from(error: &LexerError) -> Self {
+        Diagnostic::error(error.code, error.message.clone())
+            .with_span(Span::at(error.line, error.column))
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let mut diagnostic = Diagnostic::error(DiagnosticCode::SyntaxError, error.message.clone())
+            .with_label(Span::at(error.line, error.column), error.context.clone().unwrap_or_default());
+        if let Some(file) = &error.file_path {
+            diagnostic = diagnostic.with_file(file);
+        }
+        diagnostic
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        Diagnostic::error(error.code, error.message.clone())
+            .with_span(Span::at(error.line, error.column))
+    }
+}
+
+// ============================================================================
+// SECTION: Reporting Helpers
+// ============================================================================
+
+/// Print every diagnostic to stderr
+pub fn emit(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.render());
+    }
+}
+
+/// Like `emit`, for diagnostics whose source is already in memory
+pub fn emit_with_source(diagnostics: &[Diagnostic], source: &str) {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.render_with_source(Some(source)));
+    }
+}
+
+/// "2 errors, 1 warning"
+pub fn summary(diagnostics: &[Diagnostic]) -> String {
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+
+    match (errors, warnings) {
+        (_, 0) => plural(errors, "error"),
+        (0, _) => plural(warnings, "warning"),
+        _ => format!("{}, {}", plural(errors, "error"), plural(warnings, "warning")),
+    }
+}
+
+/// The closest candidate within a small edit distance, for "did you mean" hints
+pub fn did_you_mean<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let threshold = (name.chars().count() / 3).max(1);
+    candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(&name.to_lowercase(), &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// ============================================================================
+// SECTION: Snippet Layout
+// ============================================================================
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// Display offset and width of the underline for `span` within `text`
+fn underline_extent(text: &str, span: Span) -> (usize, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let start = (span.column - 1).min(chars.len());
+
+    let length = if span.length > 0 {
+        span.length
+    } else {
+        word_length(&chars[start..])
+    };
+    let end = (start + length).min(chars.len()).max(start + 1);
+
+    let display_width = |slice: &[char]| slice.iter().map(|c| if *c == '\t' { TAB_WIDTH } else { 1 }).sum::<usize>();
+    let offset = display_width(&chars[..start]);
+    let width = if end > chars.len() { 1 } else { display_width(&chars[start..end]).max(1) };
+    (offset, width)
+}
+
+/// Length of the identifier or quoted string at the start of `chars`
+fn word_length(chars: &[char]) -> usize {
+    match chars.first() {
+        Some(quote @ ('"' | '\'')) => chars[1..].iter()
+            .position(|c| c == quote)
+            .map(|end| end + 2)
+            .unwrap_or(chars.len()),
+        Some(c) if c.is_alphanumeric() || *c == '_' => chars.iter()
+            .take_while(|c| c.is_alphanumeric() || **c == '_')
+            .count(),
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip_colors(text: &str) -> String {
+        regex::Regex::new("\x1b\\[[0-9;]*m").unwrap().replace_all(text, "").to_string()
+    }
+
+    #[test]
+    fn test_render_underlines_span_with_code_and_help() {
+        let source = "define User {\n  address: Adress\n}\n";
+        let diagnostic = Diagnostic::error(DiagnosticCode::UndefinedType, "Undefined type 'Adress'")
+            .with_file("user.rel")
+            .with_label(Span::at(2, 12), "not defined in this scope")
+            .with_help("did you mean 'Address'?");
+
+        assert_eq!(
+            strip_colors(&diagnostic.render_with_source(Some(source))),
+            [
+                "error[REL0302]: Undefined type 'Adress'",
+                " --> user.rel:2:12",
+                "  |",
+                "2 |   address: Adress",
+                "  |            ^^^^^^ not defined in this scope",
+                "  |",
+                "  = help: did you mean 'Address'?",
+                "",
+            ].join("\n")
+        );
+        assert_eq!(diagnostic.to_string(), "user.rel:2:12: error[REL0302]: Undefined type 'Adress'");
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean("Adress", ["User", "Address"]), Some("Address"));
+        assert_eq!(did_you_mean("Order", ["User", "Address"]), None);
+    }
+}
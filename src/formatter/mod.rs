@@ -0,0 +1,197 @@
+/*!
+ * Formatter Module
+ *
+ * Rewrites .rel files into their canonical form (`rel fmt`), keeping
+ * `#` comments attached to the declarations and fields they describe.
+ */
+
+use std::fs;
+use std::path::Path;
+
+use crate::ast::ASTNode;
+use crate::colors;
+use crate::compiler;
+use crate::diagnostics::{self, Diagnostic, DiagnosticsFormat};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::printer::Printer;
+
+/// Format every .rel file under `input`, or only report unformatted files with `check`
+pub fn format_files(input: &Path, check: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut rel_files = if input.is_file() {
+        vec![input.to_path_buf()]
+    } else {
+        let compiler = compiler::relCompiler::new(compiler::CompilerOptions {
+            input_dir: input.to_path_buf(),
+            output_dir: None,
+            watch: false,
+            diagnostics_format: DiagnosticsFormat::Human,
+            incremental: false,
+            jobs: 1,
+            config: Default::default(),
+        });
+        compiler.find_rel_files(input)?
+    };
+    rel_files.sort();
+
+    let mut changed = Vec::new();
+    let mut failed = 0;
+
+    for file_path in &rel_files {
+        let content = fs::read_to_string(file_path)?;
+        let formatted = match format_source(&content) {
+            Ok(formatted) => formatted,
+            Err(diagnostics) => {
+                let diagnostics: Vec<Diagnostic> = diagnostics.into_iter().map(|d| d.with_file(file_path)).collect();
+                diagnostics::emit_with_source(&diagnostics, &content);
+                failed += 1;
+                continue;
+            }
+        };
+
+        if formatted == content {
+            continue;
+        }
+
+        if check {
+            println!("{} {}", colors::warning("Would reformat"), colors::path(&file_path.display().to_string()));
+        } else {
+            fs::write(file_path, &formatted)?;
+            println!("{} {}", colors::success("Formatted"), colors::path(&file_path.display().to_string()));
+        }
+        changed.push(file_path);
+    }
+
+    if failed > 0 {
+        return Err(format!("{} file(s) could not be parsed", failed).into());
+    }
+    if check && !changed.is_empty() {
+        return Err(format!("{} of {} file(s) need formatting", changed.len(), rel_files.len()).into());
+    }
+
+    let verb = if check { "already formatted" } else { "unchanged" };
+    println!("{}", colors::success(&format!(
+        "{} file(s) checked, {} {}",
+        rel_files.len(),
+        rel_files.len() - changed.len(),
+        verb
+    )));
+    Ok(())
+}
+
+/// Parse `source` with comments retained and print it back in canonical form
+pub fn format_source(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let tokens = Lexer::new(source).with_comments().tokenize()
+        .map_err(|errors| errors.iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+
+    let nodes = Parser::new(tokens).parse()
+        .map_err(|errors| errors.iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+
+    Ok(Printer::new().print(&canonical_order(nodes)))
+}
+
+/// Imports first and exports last; everything else keeps its order since
+/// statements like `declare var` and `print` are order-sensitive.
+/// Comments travel with the declaration that follows them.
+fn canonical_order(nodes: Vec<ASTNode>) -> Vec<ASTNode> {
+    let mut imports = Vec::new();
+    let mut body = Vec::new();
+    let mut exports = Vec::new();
+    let mut pending_comments = Vec::new();
+
+    for node in nodes {
+        let section = match node {
+            ASTNode::Comment(_) => {
+                pending_comments.push(node);
+                continue;
+            }
+            ASTNode::Import(_) => &mut imports,
+            ASTNode::Export(_) => &mut exports,
+            _ => &mut body,
+        };
+        section.append(&mut pending_comments);
+        section.push(node);
+    }
+
+    imports.into_iter()
+        .chain(body)
+        .chain(exports)
+        .chain(pending_comments)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_keeps_comments_and_is_idempotent() {
+        let source = r#"export User
+# User schema
+
+# The user record
+define User {
+
+  id:   number   # primary key
+  email: email
+
+
+  # Access
+  role: admin | user
+  
+  when role = admin {
+    token: string
+  }
+  # trailing notes
+}
+import { Address } from "./address.rel"
+"#;
+
+        let formatted = format_source(source).unwrap();
+        assert_eq!(
+            formatted,
+            r#"import { Address } from "./address.rel"
+
+# User schema
+
+# The user record
+define User {
+  id: number # primary key
+  email: email
+
+  # Access
+  role: admin | user
+
+  when role = admin {
+    token: string
+  }
+  # trailing notes
+}
+
+export User
+"#
+        );
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_comments_above_when_stay_above_it() {
+        let source = include_str!("../../__tests__/test-conditionals-advanced.rel");
+        let formatted = format_source(source).unwrap();
+
+        // Each comment still sits directly above the line it described
+        let described = |text: &str| -> Vec<(String, String)> {
+            let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+            lines.windows(2)
+                .filter(|pair| pair[0].starts_with('#'))
+                .map(|pair| (pair[0].to_string(), pair[1].to_string()))
+                .collect()
+        };
+        assert_eq!(described(&formatted), described(source));
+        assert!(formatted.contains("  # Conditional fields based on role\n  when role = admin {\n    adminPanel: boolean\n"), "{}", formatted);
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+
+        // Blank lines between groups are kept, so the fixture is already formatted
+        assert_eq!(formatted, source);
+    }
+}
@@ -8,63 +8,166 @@ pub struct ConstraintGeneration;
 impl ConstraintGeneration {
     /// Generate constrained type inline
     pub fn generate_constrained_type_inline(base_type: &TypeNode, constraints: &[ConstraintNode], context: &CompilationContext) -> String {
-        let base = match base_type {
-            TypeNode::Number => "number",
-            TypeNode::String => "string",
-            TypeNode::Array(_) => return super::type_generation::TypeGeneration::expand_type_inline(base_type, &HashMap::new(), &mut context.clone(), &mut 0, &|_, _, _, _| String::new()),
-            _ => return super::type_generation::TypeGeneration::expand_type_inline(base_type, &HashMap::new(), &mut context.clone(), &mut 0, &|_, _, _, _| String::new()),
-        };
+        // Named types that expand to objects can't take constraints in a type string
+        if !Self::is_type_string(base_type, context) {
+            return super::type_generation::TypeGeneration::expand_type_inline(base_type, &HashMap::new(), &mut context.clone(), &mut 0, &|_, _, _, _| String::new());
+        }
+
+        let base = super::type_generation::TypeGeneration::generate_type_name(base_type);
+        let value = |expr: &ExpressionNode| super::expression_generation::ExpressionGeneration::expression_to_string(expr, context);
+        Self::quote(&Self::constraint_schema(base_type, &base, constraints, &value).schema)
+    }
+
+    /// Whether a type fits in one ReliantType type string, items of arrays included
+    fn is_type_string(type_node: &TypeNode, context: &CompilationContext) -> bool {
+        match type_node {
+            TypeNode::Identifier(name) => context.get_type_alias(name).is_none(),
+            TypeNode::Array(inner) => match inner.as_ref() {
+                TypeNode::String | TypeNode::Number | TypeNode::Boolean | TypeNode::Identifier(_) | TypeNode::Constrained { .. } => {
+                    Self::is_type_string(inner, context)
+                }
+                _ => false,
+            },
+            TypeNode::Constrained { base_type, .. } => Self::is_type_string(base_type, context),
+            _ => true,
+        }
+    }
+
+    /// A type string as a JS string literal; regexes in it keep their backslashes
+    pub fn quote(schema: &str) -> String {
+        format!("\"{}\"", schema.replace('\\', "\\\\").replace('"', "\\\""))
+    }
 
-        // Check for special number types
-        if base == "number" {
+    /// A constraint chain as one ReliantType type string, with the constraints
+    /// that string can't express. `base` is the base type's spelling and
+    /// `value` spells an argument without quotes.
+    pub fn constraint_schema<'a>(
+        base_type: &TypeNode,
+        base: &str,
+        constraints: &'a [ConstraintNode],
+        value: &dyn Fn(&ExpressionNode) -> String,
+    ) -> ConstraintSchema<'a> {
+        let mut unsupported = Vec::new();
+        let mut drop = |constraint: &'a ConstraintNode, reason: &str| unsupported.push(Unsupported { constraint, reason: reason.to_string() });
+
+        // Constraints that replace the whole type
+        let mut suffix = String::new();
+        let mut replacement = None;
+        for constraint in constraints {
+            match (&constraint.constraint_type, &constraint.value) {
+                (ConstraintType::Literal, Some(literal)) => replacement = Some(format!("={}", value(literal))),
+                (ConstraintType::In, Some(list)) => {
+                    let members: Vec<String> = list_items(list).iter()
+                        .map(|item| match item {
+                            ExpressionNode::String(text) => text.clone(),
+                            other => format!("={}", value(other)),
+                        })
+                        .collect();
+                    replacement = Some(members.join("|"));
+                }
+                (ConstraintType::Null, _) => suffix = "|null".to_string(),
+                _ => {}
+            }
+        }
+        if let Some(replacement) = replacement {
             for constraint in constraints {
                 match constraint.constraint_type {
-                    ConstraintType::Positive => return "\"positive\"".to_string(),
-                    ConstraintType::Negative => return "\"negative\"".to_string(),
-                    ConstraintType::Integer => return "\"int\"".to_string(),
-                    ConstraintType::Float => return "\"float\"".to_string(),
-                    _ => {}
+                    ConstraintType::Literal | ConstraintType::In | ConstraintType::Null => {}
+                    ConstraintType::Exists => drop(constraint, EXISTS_ON_A_TYPE),
+                    _ => drop(constraint, "ReliantType can't combine it with `in` or `literal`"),
                 }
             }
+            return ConstraintSchema { schema: format!("{}{}", replacement, suffix), unsupported };
         }
 
-        // Handle min/max constraints
-        let mut min_val = None;
-        let mut max_val = None;
-        let mut pattern = None;
+        let mut bounds = Bounds::default();
+        let mut patterns: Vec<Pattern> = Vec::new();
+        let mut name = base.to_string();
+
+        let numeric = matches!(base_type, TypeNode::Number)
+            || matches!(base_type, TypeNode::Identifier(id) if NUMBER_TYPES.contains(&id.as_str()));
+        let text = matches!(base_type, TypeNode::String);
+        let list = matches!(base_type, TypeNode::Array(_));
+
+        if numeric {
+            let integer = matches!(base_type, TypeNode::Identifier(id) if id == "int" || id == "integer")
+                || constraints.iter().any(|c| c.constraint_type == ConstraintType::Integer);
+            let positive = constraints.iter().any(|c| c.constraint_type == ConstraintType::Positive);
+            let negative = constraints.iter().any(|c| c.constraint_type == ConstraintType::Negative);
+            let float = constraints.iter().any(|c| c.constraint_type == ConstraintType::Float);
+            if integer {
+                // There is no "positive int" type, so the sign becomes a bound
+                if positive {
+                    bounds.raise("1".to_string(), Some(1.0));
+                }
+                if negative {
+                    bounds.lower("-1".to_string(), Some(-1.0));
+                }
+                name = "int".to_string();
+            } else if positive {
+                name = "positive".to_string();
+            } else if negative {
+                name = "negative".to_string();
+            } else if float {
+                name = "float".to_string();
+            }
+        }
 
         for constraint in constraints {
+            use ConstraintType::*;
+            let argument = constraint.value.as_ref();
+            let number = argument.and_then(number_value);
             match &constraint.constraint_type {
-                ConstraintType::Min => min_val = constraint.value.clone(),
-                ConstraintType::Max => max_val = constraint.value.clone(),
-                ConstraintType::MinLength => min_val = constraint.value.clone(),
-                ConstraintType::MaxLength => max_val = constraint.value.clone(),
-                ConstraintType::Matches => pattern = constraint.value.clone(),
-                _ => {}
+                // Handled above
+                Literal | In | Null => {}
+                Exists => drop(constraint, EXISTS_ON_A_TYPE),
+                Integer | Positive | Negative | Float if numeric => {}
+                Min | Max | MinLength | MaxLength | Between | Empty if !(numeric || text || list) => {
+                    drop(constraint, "ReliantType only bounds numbers, strings and arrays")
+                }
+                MinLength | MaxLength | Empty if numeric => drop(constraint, "numbers have no length"),
+                MinItems | MaxItems if !list => drop(constraint, "only arrays have items"),
+                Min | MinLength | MinItems => if let Some(arg) = argument { bounds.raise(value(arg), number) },
+                Max | MaxLength | MaxItems => if let Some(arg) = argument { bounds.lower(value(arg), number) },
+                Empty => bounds.lower("0".to_string(), Some(0.0)),
+                Between => match argument.map(list_items).as_deref() {
+                    Some([low, high]) => {
+                        bounds.raise(value(low), number_value(low));
+                        bounds.lower(value(high), number_value(high));
+                    }
+                    _ => drop(constraint, "`between` takes two arguments, as in between(1, 10)"),
+                },
+                Matches | Contains | StartsWith | EndsWith | HasUppercase | HasLowercase | HasNumber | HasSpecialChar | NotIn if text => {
+                    match Pattern::of(&constraint.constraint_type, argument.unwrap_or(&ExpressionNode::Null), value) {
+                        Some(pattern) => patterns.push(pattern),
+                        None => drop(constraint, "it needs a string argument"),
+                    }
+                }
+                NotIn => drop(constraint, "ReliantType can only exclude values from strings"),
+                Future | Past | Before | After => drop(constraint, "ReliantType has no date constraints"),
+                Unique => drop(constraint, "ReliantType can't require array items to be distinct"),
+                _ => drop(constraint, "ReliantType has no equivalent for this type"),
             }
         }
 
-        if let Some(pat) = pattern {
-            let pat_str = super::expression_generation::ExpressionGeneration::expression_to_string(&pat, context);
-            return format!("\"{}({})\"", base, pat_str);
-        }
+        let schema = match (patterns.as_slice(), bounds.is_empty()) {
+            ([], true) => name,
+            ([], false) => format!("{}({},{})", name, bounds.low_text(), bounds.high_text()),
+            ([pattern], true) => format!("{}(/{}/)", name, pattern.alone),
+            // Several requirements become lookaheads anchored at the start
+            (patterns, _) => {
+                let mut regex = String::from("^");
+                if !bounds.is_empty() {
+                    regex.push_str(&format!("(?=[\\s\\S]{{{},{}}}$)", bounds.low.as_ref().map_or("0".to_string(), |b| b.0.clone()), bounds.high_text()));
+                }
+                for pattern in patterns {
+                    regex.push_str(&pattern.lookahead);
+                }
+                format!("{}(/{}/)", name, regex)
+            }
+        };
 
-        match (min_val, max_val) {
-            (Some(min), Some(max)) => {
-                let min_str = super::expression_generation::ExpressionGeneration::expression_to_string(&min, context);
-                let max_str = super::expression_generation::ExpressionGeneration::expression_to_string(&max, context);
-                format!("\"{}({},{})\"", base, min_str, max_str)
-            },
-            (Some(min), None) => {
-                let min_str = super::expression_generation::ExpressionGeneration::expression_to_string(&min, context);
-                format!("\"{}({},)\"", base, min_str)
-            },
-            (None, Some(max)) => {
-                let max_str = super::expression_generation::ExpressionGeneration::expression_to_string(&max, context);
-                format!("\"{}(,{})\"", base, max_str)
-            },
-            (None, None) => format!("\"{}\"", base),
-        }
+        ConstraintSchema { schema: format!("{}{}", schema, suffix), unsupported }
     }
 
     /// Generate constraint string representation
@@ -76,6 +179,9 @@ impl ConstraintGeneration {
             ConstraintType::Max => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
             ConstraintType::MinLength => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
             ConstraintType::MaxLength => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
+            ConstraintType::MinItems => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
+            ConstraintType::MaxItems => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
+            ConstraintType::Unique => String::new(),
             ConstraintType::Matches => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
             ConstraintType::Contains => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
             ConstraintType::StartsWith => super::type_generation::TypeGeneration::generate_expression_value(&constraint.value),
@@ -109,6 +215,9 @@ impl ConstraintGeneration {
             ConstraintType::Max => format!(".max({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
             ConstraintType::MinLength => format!(".minLength({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
             ConstraintType::MaxLength => format!(".maxLength({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
+            ConstraintType::MinItems => format!(".minItems({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
+            ConstraintType::MaxItems => format!(".maxItems({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
+            ConstraintType::Unique => ".unique()".to_string(),
             ConstraintType::Matches => format!(".matches({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
             ConstraintType::Contains => format!(".contains({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
             ConstraintType::StartsWith => format!(".startsWith({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
@@ -134,4 +243,240 @@ impl ConstraintGeneration {
             ConstraintType::Literal => format!(".literal({})", super::type_generation::TypeGeneration::generate_expression_value(&constraint.value)),
         }
     }
-}
\ No newline at end of file
+}
+/// A ReliantType type string for a constraint chain
+pub struct ConstraintSchema<'a> {
+    pub schema: String,
+    /// Constraints left out of `schema`, with why
+    pub unsupported: Vec<Unsupported<'a>>,
+}
+
+pub struct Unsupported<'a> {
+    pub constraint: &'a ConstraintNode,
+    pub reason: String,
+}
+
+/// Why `& exists` on a field's type is dropped: the field is required unless
+/// it is marked `?`, and presence is only a question inside a `when` condition
+const EXISTS_ON_A_TYPE: &str = "a field is already required unless marked `?`; `exists` only checks presence in a `when` condition";
+
+/// Built-in type names that are numbers
+const NUMBER_TYPES: &[&str] = &["positive", "negative", "integer", "float", "int", "double"];
+
+/// The tightest lower and upper bound seen, as spelled and, when literal, as numbers
+#[derive(Default)]
+struct Bounds {
+    low: Option<(String, Option<f64>)>,
+    high: Option<(String, Option<f64>)>,
+}
+
+impl Bounds {
+    fn raise(&mut self, text: String, number: Option<f64>) {
+        let keep = matches!((&self.low, number), (Some((_, Some(current))), Some(new)) if *current >= new);
+        if !keep {
+            self.low = Some((text, number));
+        }
+    }
+
+    fn lower(&mut self, text: String, number: Option<f64>) {
+        let keep = matches!((&self.high, number), (Some((_, Some(current))), Some(new)) if *current <= new);
+        if !keep {
+            self.high = Some((text, number));
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+
+    fn low_text(&self) -> String {
+        self.low.as_ref().map(|b| b.0.clone()).unwrap_or_default()
+    }
+
+    fn high_text(&self) -> String {
+        self.high.as_ref().map(|b| b.0.clone()).unwrap_or_default()
+    }
+}
+
+/// A string requirement as a regex: on its own, and as a lookahead that
+/// can be combined with others
+struct Pattern {
+    alone: String,
+    lookahead: String,
+}
+
+impl Pattern {
+    fn of(constraint_type: &ConstraintType, argument: &ExpressionNode, value: &dyn Fn(&ExpressionNode) -> String) -> Option<Pattern> {
+        let literal = || match argument {
+            ExpressionNode::String(_) | ExpressionNode::RawString(_) | ExpressionNode::Number(_) => Some(escape_regex(&value(argument))),
+            _ => None,
+        };
+        let (alone, lookahead) = match constraint_type {
+            ConstraintType::Matches => {
+                let regex = escape_slashes(&value(argument));
+                (regex.clone(), format!("(?=[\\s\\S]*(?:{}))", regex))
+            }
+            ConstraintType::Contains => {
+                let text = literal()?;
+                (text.clone(), format!("(?=[\\s\\S]*{})", text))
+            }
+            ConstraintType::StartsWith => {
+                let text = literal()?;
+                (format!("^{}", text), format!("(?={})", text))
+            }
+            ConstraintType::EndsWith => {
+                let text = literal()?;
+                (format!("{}$", text), format!("(?=[\\s\\S]*{}$)", text))
+            }
+            ConstraintType::NotIn => {
+                let items: Vec<String> = list_items(argument).iter().map(|item| escape_regex(&value(item))).collect();
+                let excluded = format!("(?!(?:{})$)", items.join("|"));
+                (format!("^{}", excluded), excluded)
+            }
+            _ => {
+                let class = match constraint_type {
+                    ConstraintType::HasUppercase => "[A-Z]",
+                    ConstraintType::HasLowercase => "[a-z]",
+                    ConstraintType::HasNumber => "\\d",
+                    _ => "[^A-Za-z0-9\\s]",
+                };
+                (class.to_string(), format!("(?=[\\s\\S]*{})", class))
+            }
+        };
+        Some(Pattern { alone, lookahead })
+    }
+}
+
+/// The items of a list argument; a single value is a list of one
+fn list_items(argument: &ExpressionNode) -> Vec<ExpressionNode> {
+    match argument {
+        ExpressionNode::Array(items) => items.clone(),
+        other => vec![other.clone()],
+    }
+}
+
+fn number_value(expression: &ExpressionNode) -> Option<f64> {
+    match expression {
+        ExpressionNode::Number(value) => Some(*value),
+        ExpressionNode::UnaryOp { operator: UnaryOperator::Negate, operand } => number_value(operand).map(|value| -value),
+        _ => None,
+    }
+}
+
+/// Escape text for use inside a `/.../` regex literal
+/// A user's pattern with every unescaped `/` escaped, so it can sit between
+/// the slashes of a regex literal
+fn escape_slashes(pattern: &str) -> String {
+    let mut escaped = String::new();
+    let mut after_backslash = false;
+    for c in pattern.chars() {
+        if c == '/' && !after_backslash {
+            escaped.push('\\');
+        }
+        after_backslash = c == '\\' && !after_backslash;
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}/".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// Each field's type string, with the names of the constraints left out
+    fn schemas(fields: &str) -> Vec<(String, Vec<&'static str>)> {
+        let source = format!("define T {{\n{}\n}}", fields);
+        let ast = Parser::new(Lexer::new(&source).tokenize().unwrap()).parse().unwrap();
+        let ASTNode::Schema(schema) = &ast[0] else { panic!("expected a schema") };
+        let context = CompilationContext::new();
+        let value = |expr: &ExpressionNode| super::super::expression_generation::ExpressionGeneration::expression_to_string(expr, &context);
+
+        schema.fields.iter()
+            .map(|field| {
+                let TypeNode::Constrained { base_type, constraints } = &field.field_type else { panic!("expected constraints") };
+                let base = super::super::type_generation::TypeGeneration::generate_type_name(base_type);
+                let rendered = ConstraintGeneration::constraint_schema(base_type, &base, constraints, &value);
+                let dropped = rendered.unsupported.iter().map(|u| crate::printer::constraint_name(&u.constraint.constraint_type)).collect();
+                (rendered.schema, dropped)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_every_constraint_is_rendered_or_reported() {
+        let rendered = schemas(r#"
+  a: number & min(1) & min(5) & max(10)
+  b: number & integer & positive & max(99)
+  c: string & matches(r"^a") & minLength(2)
+  d: string & contains("@") & startsWith("a.b") & endsWith("z")
+  e: string & hasUppercase & hasNumber
+  f: string & notIn("root", "admin")
+  g: string & in("x", "y") & null
+  h: number & between(1, 10)
+  i: string[] & min(1) & max(5)
+  j: date & future & before("2030-01-01")
+  k: number & empty
+  l: string[] & minItems(1) & maxItems(10) & unique
+  m: (string & minLength(2))[] & maxItems(3)
+  n: string & exists
+  o: string & in("x", "y") & exists
+  p: string & matches(r"^a/b\/c$")
+"#);
+        let expected: Vec<(&str, Vec<&str>)> = vec![
+            ("number(5,10)", vec![]),
+            ("int(1,99)", vec![]),
+            (r"string(/^(?=[\s\S]{2,}$)(?=[\s\S]*(?:^a))/)", vec![]),
+            (r"string(/^(?=[\s\S]*@)(?=a\.b)(?=[\s\S]*z$)/)", vec![]),
+            (r"string(/^(?=[\s\S]*[A-Z])(?=[\s\S]*\d)/)", vec![]),
+            ("string(/^(?!(?:root|admin)$)/)", vec![]),
+            ("x|y|null", vec![]),
+            ("number(1,10)", vec![]),
+            ("string[](1,5)", vec![]),
+            ("date", vec!["future", "before"]),
+            ("number", vec!["empty"]),
+            ("string[](1,10)", vec!["unique"]),
+            ("string(2,)[](,3)", vec![]),
+            ("string", vec!["exists"]),
+            ("x|y", vec!["exists"]),
+            (r"string(/^a\/b\/c$/)", vec![]),
+        ];
+        let rendered: Vec<(&str, Vec<&str>)> = rendered.iter().map(|(schema, dropped)| (schema.as_str(), dropped.clone())).collect();
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_array_and_item_constraints_parse_and_generate() {
+        let source = "define Post {\n  tags: string[] & minItems(1) & maxItems(10)\n  labels: (string & minLength(2))[]\n}\nexport Post\n";
+        let ast = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+        let ASTNode::Schema(schema) = &ast[0] else { panic!("expected a schema") };
+        let constraint_types = |constraints: &[ConstraintNode]| constraints.iter().map(|c| c.constraint_type.clone()).collect::<Vec<_>>();
+
+        // Constraints after `[]` apply to the array
+        let TypeNode::Constrained { base_type, constraints } = &schema.fields[0].field_type else { panic!("expected constraints") };
+        assert_eq!(**base_type, TypeNode::Array(Box::new(TypeNode::String)));
+        assert_eq!(constraint_types(constraints), vec![ConstraintType::MinItems, ConstraintType::MaxItems]);
+
+        // Constraints inside the parentheses apply to each item
+        let TypeNode::Array(item) = &schema.fields[1].field_type else { panic!("expected an array") };
+        let TypeNode::Constrained { base_type, constraints } = item.as_ref() else { panic!("expected item constraints") };
+        assert_eq!(**base_type, TypeNode::String);
+        assert_eq!(constraint_types(constraints), vec![ConstraintType::MinLength]);
+
+        let output = super::super::TypeScriptGenerator::new().generate(&ast);
+        assert!(output.contains("tags: \"string[](1,10)\","), "{}", output);
+        assert!(output.contains("labels: \"string(2,)[]\","), "{}", output);
+    }
+}
@@ -68,10 +68,37 @@ impl TypeScriptGenerator {
             }
         }
 
+        // Aliases of intersections and utility types become schemas of the
+        // fields they resolve to, so they export and expand like any other schema
+        let lookup = crate::validation::intersections::lookup_in(ast);
+        for node in ast {
+            if let crate::ast::ASTNode::TypeAlias(alias) = node {
+                let builds_object = crate::validation::intersections::builds_object(&alias.type_definition);
+                if let (true, crate::validation::intersections::Member::Object(fields)) = (builds_object, lookup(&alias.name)) {
+                    self.schema_definitions.insert(alias.name.clone(), crate::ast::SchemaNode {
+                        name: alias.name.clone(),
+                        fields,
+                        extends: None,
+                        mixins: Vec::new(),
+                        generics: Vec::new(),
+                        validations: Vec::new(),
+                        location: alias.location.clone(),
+                    });
+                }
+            }
+        }
+
         let mut output = String::from("import { Interface } from 'reliant-type';\n\n");
 
-        // Second pass: only generate exported schemas with inline expansion
-        let exported_list: Vec<String> = self.exported_schemas.iter().cloned().collect();
+        // Second pass: only generate exported schemas with inline expansion,
+        // in declaration order so the output is stable
+        let exported_list: Vec<String> = ast.iter()
+            .filter_map(|node| match node {
+                crate::ast::ASTNode::Schema(schema) if self.exported_schemas.contains(&schema.name) => Some(schema.name.clone()),
+                crate::ast::ASTNode::TypeAlias(alias) if self.exported_schemas.contains(&alias.name) => Some(alias.name.clone()),
+                _ => None,
+            })
+            .collect();
         for schema_name in exported_list {
             if let Some(schema) = self.schema_definitions.get(&schema_name).cloned() {
                 output.push_str(&SchemaGeneration::generate_schema_inline(
@@ -1,6 +1,7 @@
 use crate::ast::*;
 use std::collections::HashMap;
 use crate::context::CompilationContext;
+use crate::generator::constraint_generation::ConstraintGeneration;
 
 /// Schema and field generation methods for the TypeScript generator
 pub struct SchemaGeneration;
@@ -40,6 +41,15 @@ impl SchemaGeneration {
         let indent = "  ".repeat(*indent_level);
         let mut output = String::new();
 
+        // A discriminated union of schemas becomes one object keyed by its tag
+        if let Some(type_str) = field.discriminator.as_deref()
+            .and_then(|tag| Self::expand_discriminated_union(field, tag, indent_level, schema_definitions, context))
+        {
+            let optional = if field.optional { "?" } else { "" };
+            output.push_str(&format!("{}{}{}: {},\n", indent, field.name, optional, type_str));
+            return output;
+        }
+
         // Check if this field has conditionals - handle them specially
         if !field.conditionals.is_empty() {
             // This is a regular field, generate it normally
@@ -73,6 +83,115 @@ impl SchemaGeneration {
         output
     }
 
+    /// Expand `A | B` under `@discriminator(tag)` into a single object: the tag
+    /// accepts every variant's literal, and fields that differ between variants
+    /// are chosen with `when tag === ...`. `None` when a member isn't an object
+    /// with a literal tag, which fails the lint pass before generation.
+    fn expand_discriminated_union(
+        field: &FieldNode,
+        tag: &str,
+        indent_level: &mut usize,
+        schema_definitions: &HashMap<String, SchemaNode>,
+        context: &mut CompilationContext,
+    ) -> Option<String> {
+        use super::type_generation::TypeGeneration;
+
+        let TypeNode::Union(members) = &field.field_type else { return None };
+        let mut variants = Vec::new();
+        for member in members {
+            let fields = TypeGeneration::object_fields(member, schema_definitions, context)?;
+            let value = fields.iter()
+                .find(|f| f.name == tag)
+                .and_then(|f| match &f.field_type {
+                    TypeNode::Literal(value) => Some(value.clone()),
+                    _ => None,
+                })?;
+            variants.push((value, fields));
+        }
+
+        // Field name -> (tag value, expanded type, optional) for each variant that has it
+        let mut inner_level = *indent_level + 1;
+        let mut order: Vec<String> = Vec::new();
+        let mut by_name: HashMap<String, Vec<(ExpressionNode, String, bool)>> = HashMap::new();
+        for (value, fields) in &variants {
+            for variant_field in fields.iter().filter(|f| f.name != tag) {
+                let type_str = TypeGeneration::expand_type_inline(
+                    &variant_field.field_type,
+                    schema_definitions,
+                    context,
+                    &mut inner_level,
+                    &|f, il, sd, ctx| Self::generate_field_inline(f, il, sd, ctx)
+                );
+                if !by_name.contains_key(&variant_field.name) {
+                    order.push(variant_field.name.clone());
+                }
+                by_name.entry(variant_field.name.clone()).or_default()
+                    .push((value.clone(), type_str, variant_field.optional));
+            }
+        }
+
+        let inner_indent = "  ".repeat(inner_level);
+        let condition = |value: &ExpressionNode| {
+            super::expression_generation::ExpressionGeneration::generate_expression(&ExpressionNode::BinaryOp {
+                left: Box::new(ExpressionNode::Identifier(tag.to_string())),
+                operator: BinaryOperator::Equals,
+                right: Box::new(value.clone()),
+            })
+        };
+        let part = |type_str: &str| TypeGeneration::type_string_part(type_str.to_string());
+
+        let tags: Vec<String> = variants.iter()
+            .map(|(value, _)| part(&TypeGeneration::generate_type_schema(&TypeNode::Literal(value.clone()))))
+            .collect();
+        let mut output = String::from("{\n");
+        output.push_str(&format!("{}{}: {},\n", inner_indent, tag, ConstraintGeneration::quote(&tags.join("|"))));
+
+        for name in &order {
+            let entries = &by_name[name];
+            let everywhere = entries.len() == variants.len();
+            let optional = if !everywhere || entries.iter().any(|(_, _, optional)| *optional) { "?" } else { "" };
+
+            // Distinct types, each with the tags of the variants that use it
+            let mut groups: Vec<(&str, Vec<&ExpressionNode>)> = Vec::new();
+            for (value, type_str, _) in entries {
+                match groups.iter_mut().find(|(t, _)| t == type_str) {
+                    Some((_, values)) => values.push(value),
+                    None => groups.push((type_str, vec![value])),
+                }
+            }
+
+            let type_str = match groups.as_slice() {
+                // Only one variant has it; absent everywhere else
+                [(type_str, values)] if values.len() == 1 && !everywhere => {
+                    let variant_optional = if entries[0].2 { "?" } else { "" };
+                    let when = format!("when {} *? {} : any?", condition(values[0]), part(type_str));
+                    output.push_str(&format!("{}{}{}: {},\n", inner_indent, name, variant_optional, ConstraintGeneration::quote(&when)));
+                    continue;
+                }
+                // Same type wherever it appears
+                [(type_str, _)] => type_str.to_string(),
+                [(first, first_values), (second, second_values)] if everywhere
+                    && (first_values.len() == 1 || second_values.len() == 1) =>
+                {
+                    let (single, value, other) = if first_values.len() == 1 {
+                        (first, first_values[0], second)
+                    } else {
+                        (second, second_values[0], first)
+                    };
+                    ConstraintGeneration::quote(&format!("when {} *? {} : {}", condition(value), part(single), part(other)))
+                }
+                _ => {
+                    let members: Vec<String> = groups.iter().map(|(type_str, _)| part(type_str)).collect();
+                    ConstraintGeneration::quote(&members.join("|"))
+                }
+            };
+            output.push_str(&format!("{}{}{}: {},\n", inner_indent, name, optional, type_str));
+        }
+
+        output.push_str(&format!("{}}}", "  ".repeat(*indent_level)));
+        Some(output)
+    }
+
     /// Generate conditional fields using ReliantType inline syntax
     pub fn generate_conditional_fields_inline(
         conditional: &ConditionalNode,
@@ -282,4 +401,42 @@ impl SchemaGeneration {
 
         output
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::generator::TypeScriptGenerator;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn generate(source: &str) -> String {
+        let ast = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+        TypeScriptGenerator::new().generate(&ast)
+    }
+
+    #[test]
+    fn test_variant_fields_are_escaped_type_strings() {
+        let output = generate(r#"
+define Address {
+  city: string
+}
+define Card {
+  kind: =card
+  number: string & matches(r"^\d{16}$")
+  shipping: Address
+}
+define Bank {
+  kind: ="bank \"eu\""
+  shipping: Address
+}
+define Order {
+  @discriminator(kind)
+  payment: Card | Bank
+}
+export Order
+"#);
+        assert!(output.contains(r#"kind: "=card|=bank \"eu\"","#), "{}", output);
+        assert!(output.contains(r#"number: "when kind === card *? string(/^\\d{16}$/) : any?","#), "{}", output);
+        // A schema every variant shares is expanded
+        assert!(output.contains("shipping: {\n      city: \"string\",\n    },"), "{}", output);
+    }
+}
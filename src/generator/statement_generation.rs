@@ -35,15 +35,24 @@ impl StatementGeneration {
 
     /// Generate import statement
     pub fn generate_import(import: &ImportNode) -> String {
-        let items_str = import.items.join(", ");
-        format!("import {{ {} }} from \"{}\";", items_str, import.path)
+        match &import.alias {
+            Some(namespace) => format!("import * as {} from \"{}\";", namespace, import.path),
+            None => {
+                let items: Vec<String> = import.items.iter().map(ToString::to_string).collect();
+                format!("import {{ {} }} from \"{}\";", items.join(", "), import.path)
+            }
+        }
     }
 
     /// Generate export statement
     pub fn generate_export(export: &ExportNode) -> String {
         // This method is no longer used - exports are handled inline
         let items_str = export.items.join(", ");
-        format!("export {{ {} }};", items_str)
+        match &export.from {
+            Some(path) if export.all => format!("export * from \"{}\";", path),
+            Some(path) => format!("export {{ {} }} from \"{}\";", items_str, path),
+            None => format!("export {{ {} }};", items_str),
+        }
     }
 
     /// Generate variable statement
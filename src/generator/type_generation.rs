@@ -3,6 +3,7 @@ use crate::context::CompilationContext;
 use std::collections::HashMap;
 use crate::generator::constraint_generation::ConstraintGeneration;
 use crate::generator::expression_generation::ExpressionGeneration;
+use crate::validation::{intersections, unions, utility_types};
 
 /// Type generation methods for the TypeScript generator
 pub struct TypeGeneration;
@@ -84,22 +85,65 @@ impl TypeGeneration {
             TypeNode::Boolean => "\"boolean\"".to_string(),
             TypeNode::Array(inner) => {
                 let inner_type = Self::expand_type_inline(inner, schema_definitions, context, indent_level, generate_field_inline);
-                format!("{}[]", inner_type)
+                // A type string gets `[]` inside its quotes: "string(2,)[]"
+                match Self::unquote(&inner_type) {
+                    Some(name) if matches!(inner.as_ref(), TypeNode::Union(_)) => ConstraintGeneration::quote(&format!("({})[]", name)),
+                    Some(name) => ConstraintGeneration::quote(&format!("{}[]", name)),
+                    None => format!("{}[]", inner_type),
+                }
+            }
+            TypeNode::Record { key, value } => {
+                // Keys and values are expanded first, so aliases resolve to what they name
+                let key = Self::type_string_part(Self::expand_type_inline(key, schema_definitions, context, indent_level, generate_field_inline));
+                let value = Self::type_string_part(Self::expand_type_inline(value, schema_definitions, context, indent_level, generate_field_inline));
+                ConstraintGeneration::quote(&format!("record<{},{}>", key, value))
+            }
+            TypeNode::Tuple(elements) => {
+                let items = elements.iter()
+                    .map(|element| {
+                        let item_type = match (&element.element_type, element.rest) {
+                            (TypeNode::Array(inner), true) => inner.as_ref(),
+                            (element_type, _) => element_type,
+                        };
+                        Self::type_string_part(Self::expand_type_inline(item_type, schema_definitions, context, indent_level, generate_field_inline))
+                    })
+                    .collect();
+                ConstraintGeneration::quote(&Self::tuple_type_name(elements, items))
             }
             TypeNode::Union(types) => {
+                // A schema that may be null or undefined is generated as the
+                // schema; the lint pass reports what that leaves out
+                let present: Vec<&TypeNode> = types.iter().filter(|t| !unions::is_empty_member(t)).collect();
+                if let [only] = present.as_slice() {
+                    if present.len() < types.len() && Self::object_fields(only, schema_definitions, context).is_some() {
+                        return Self::expand_type_inline(only, schema_definitions, context, indent_level, generate_field_inline);
+                    }
+                }
+
                 let type_strs: Vec<String> = types.iter()
                     .map(|t| {
                         match t {
                             TypeNode::Identifier(name) => name.clone(),
-                            _ => Self::expand_type_inline(t, schema_definitions, context, indent_level, generate_field_inline).trim_matches('"').to_string()
+                            _ => Self::type_string_part(Self::expand_type_inline(t, schema_definitions, context, indent_level, generate_field_inline)),
                         }
                     })
                     .collect();
-                format!("\"{}\"", type_strs.join("|"))
+                ConstraintGeneration::quote(&type_strs.join("|"))
             }
             TypeNode::Constrained { base_type, constraints } => {
                 ConstraintGeneration::generate_constrained_type_inline(base_type, constraints, context)
             }
+            TypeNode::Literal(_) => Self::generate_type_schema(type_node),
+            // An object built from others, `User & Timestamps` or `Pick<User, id>`;
+            // problems building it were reported by the lint pass
+            TypeNode::Intersection(_) | TypeNode::Generic(..) if intersections::builds_object(type_node) => {
+                match Self::object_fields(type_node, schema_definitions, context) {
+                    Some(fields) => {
+                        Self::expand_type_inline(&TypeNode::InlineObject(fields), schema_definitions, context, indent_level, generate_field_inline)
+                    }
+                    None => Self::generate_type(type_node),
+                }
+            }
             TypeNode::InlineObject(fields) => {
                 let mut output = String::from("{\n");
                 *indent_level += 1;
@@ -117,6 +161,38 @@ impl TypeGeneration {
         }
     }
 
+    /// The fields of an object type: a schema, an alias of one, an inline
+    /// object, an intersection or a utility type, resolved against the merged
+    /// schemas the way the lint pass resolves them
+    pub(crate) fn object_fields(
+        type_node: &TypeNode,
+        schema_definitions: &HashMap<String, SchemaNode>,
+        context: &CompilationContext,
+    ) -> Option<Vec<FieldNode>> {
+        match Self::resolve_object(type_node, schema_definitions, context, 0) {
+            intersections::Member::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    fn resolve_object(
+        type_node: &TypeNode,
+        schema_definitions: &HashMap<String, SchemaNode>,
+        context: &CompilationContext,
+        depth: usize,
+    ) -> intersections::Member {
+        // Aliases that refer to themselves stop here rather than recursing forever
+        if depth > 32 {
+            return intersections::Member::Unknown;
+        }
+        let lookup = |name: &str| match (schema_definitions.get(name), context.get_type_alias(name)) {
+            (Some(schema), _) => intersections::Member::Object(schema.fields.clone()),
+            (None, Some(alias)) => Self::resolve_object(alias, schema_definitions, context, depth + 1),
+            (None, None) => intersections::Member::Unknown,
+        };
+        intersections::resolve(type_node, &SourceLocation::new(0, 0), &lookup).0
+    }
+
     /// Generate a TypeScript type from AST
     pub fn generate_type(type_node: &TypeNode) -> String {
         match type_node {
@@ -138,17 +214,36 @@ impl TypeGeneration {
                 let type_strings: Vec<String> = types.iter().map(|t| Self::generate_type(t)).collect();
                 type_strings.join(" | ")
             }
+            TypeNode::Intersection(types) => {
+                let type_strings: Vec<String> = types.iter().map(Self::generate_type).collect();
+                type_strings.join(" & ")
+            }
+            // TypeScript names the fields as string literals: Pick<User, "id" | "email">
+            TypeNode::Generic(name, type_args) if matches!(name.as_str(), "Pick" | "Omit") && type_args.len() == 2 => {
+                let keys: Vec<String> = utility_types::key_names(&type_args[1]).iter().map(|key| format!("\"{}\"", key)).collect();
+                format!("{}<{}, {}>", name, Self::generate_type(&type_args[0]), keys.join(" | "))
+            }
             TypeNode::Generic(name, type_args) => {
                 let arg_strings: Vec<String> = type_args.iter().map(|t| Self::generate_type(t)).collect();
                 format!("{}<{}>", name, arg_strings.join(", "))
             }
+            TypeNode::Record { key, value } => format!("Record<{}, {}>", Self::generate_type(key), Self::generate_type(value)),
+            TypeNode::Tuple(elements) => {
+                let parts: Vec<String> = elements.iter()
+                    .map(|element| {
+                        let element_type = Self::generate_type(&element.element_type);
+                        match (element.rest, element.optional) {
+                            (true, _) => format!("...{}", element_type),
+                            (false, true) => format!("{}?", element_type),
+                            (false, false) => element_type,
+                        }
+                    })
+                    .collect();
+                format!("[{}]", parts.join(", "))
+            }
             TypeNode::Constrained { base_type, .. } => Self::generate_type(base_type),
             TypeNode::Conditional(conditional) => Self::generate_conditional_type(conditional),
-            TypeNode::Literal(expr) => {
-                // Generate literal value for types
-                let value_str = Self::generate_expression_value(&Some(expr.clone()));
-                format!("={}", value_str)
-            }
+            TypeNode::Literal(expr) => ExpressionGeneration::generate_expression(expr),
             TypeNode::InlineObject(fields) => {
                 // Generate nested object type as TypeScript interface
                 let mut obj_str = String::from("{\n");
@@ -181,7 +276,7 @@ impl TypeGeneration {
                 // Return the function name as a type reference
                 format!("\"{}\"" , name)
             }
-            TypeNode::Array(inner) => format!("\"{}[]\"", Self::generate_type_name(inner)),
+            TypeNode::Array(inner) => ConstraintGeneration::quote(&format!("{}[]", Self::item_type_name(inner))),
             TypeNode::Union(types) => {
                 let union_parts: Vec<String> = types.iter().map(|t| {
                     match t {
@@ -189,21 +284,20 @@ impl TypeGeneration {
                         _ => Self::generate_type_name(t),
                     }
                 }).collect();
-                format!("\"{}\"", union_parts.join("|"))
+                ConstraintGeneration::quote(&union_parts.join("|"))
             }
             TypeNode::Generic(name, type_args) => {
                 let arg_strings: Vec<String> = type_args.iter().map(|t| Self::generate_type_name(t)).collect();
-                format!("\"{}<{}>\"", name, arg_strings.join(","))
+                ConstraintGeneration::quote(&format!("{}<{}>", name, arg_strings.join(",")))
+            }
+            TypeNode::Record { .. } | TypeNode::Tuple(_) | TypeNode::Intersection(_) => {
+                ConstraintGeneration::quote(&Self::generate_type_name(type_node))
             }
             TypeNode::Constrained { base_type, constraints } => {
                 Self::generate_constrained_type_schema(base_type, constraints)
             }
             TypeNode::Conditional(conditional) => Self::generate_conditional_schema(conditional),
-            TypeNode::Literal(expr) => {
-                // Generate literal value: =value
-                let value_str = Self::generate_expression_value(&Some(expr.clone()));
-                format!("\"={}\"", value_str)
-            }
+            TypeNode::Literal(expr) => ConstraintGeneration::quote(&Self::literal_name(expr)),
             TypeNode::InlineObject(fields) => {
                 // Generate inline object schema - simplified version
                 // For proper schema generation, use expand_type_inline instead
@@ -217,6 +311,62 @@ impl TypeGeneration {
         }
     }
 
+    /// An inline expansion as it reads inside another type string; an object
+    /// can't sit inside one, so it becomes `object`
+    pub(crate) fn type_string_part(expanded: String) -> String {
+        match Self::unquote(&expanded) {
+            Some(name) => name,
+            None if expanded.starts_with('{') => "object".to_string(),
+            None => expanded,
+        }
+    }
+
+    /// The type string inside a quoted one, with its escapes undone; `None`
+    /// for an expanded object
+    fn unquote(expanded: &str) -> Option<String> {
+        let inner = expanded.strip_prefix('"')?.strip_suffix('"')?;
+        Some(inner.replace("\\\"", "\"").replace("\\\\", "\\"))
+    }
+
+    /// A tuple as an array of its element types, bounded by how many elements
+    /// it can have. Type strings have no tuples, so positions aren't checked.
+    fn tuple_type_name(elements: &[TupleElement], items: Vec<String>) -> String {
+        let mut members: Vec<String> = Vec::new();
+        for item in items {
+            if !members.contains(&item) {
+                members.push(item);
+            }
+        }
+        let item = match members.as_slice() {
+            [] => "any".to_string(),
+            [only] if !only.contains('|') => only.clone(),
+            _ => format!("({})", members.join("|")),
+        };
+        let required = elements.iter().filter(|element| !element.optional && !element.rest).count();
+        let most = match elements.iter().any(|element| element.rest) {
+            true => String::new(),
+            false => elements.len().to_string(),
+        };
+        format!("{}[]({},{})", item, required, most)
+    }
+
+    /// A literal as it reads inside a type string: `=admin`, `=super-admin`,
+    /// `=42`. Strings go in bare; the whole type string is quoted once.
+    fn literal_name(expr: &ExpressionNode) -> String {
+        match expr {
+            ExpressionNode::String(text) | ExpressionNode::RawString(text) => format!("={}", text),
+            other => format!("={}", ExpressionGeneration::generate_expression(other)),
+        }
+    }
+
+    /// An array's item type name, in parentheses when it is a union
+    fn item_type_name(inner: &TypeNode) -> String {
+        match inner {
+            TypeNode::Union(_) => format!("({})", Self::generate_type_name(inner)),
+            _ => Self::generate_type_name(inner),
+        }
+    }
+
     /// Generate type name for internal use
     pub fn generate_type_name(type_node: &TypeNode) -> String {
         match type_node {
@@ -233,7 +383,7 @@ impl TypeGeneration {
                 // Use generate_type_schema and strip quotes
                 Self::generate_type_schema(type_node).trim_matches('"').to_string()
             }
-            TypeNode::Array(inner) => format!("{}[]", Self::generate_type_name(inner)),
+            TypeNode::Array(inner) => format!("{}[]", Self::item_type_name(inner)),
             TypeNode::Union(types) => {
                 let union_parts: Vec<String> = types.iter().map(|t| Self::generate_type_name(t)).collect();
                 union_parts.join("|")
@@ -242,27 +392,20 @@ impl TypeGeneration {
                 let arg_strings: Vec<String> = type_args.iter().map(|t| Self::generate_type_name(t)).collect();
                 format!("{}<{}>", name, arg_strings.join(", "))
             }
-            TypeNode::Constrained { base_type, constraints } => {
-                let base_str = Self::generate_type_name(base_type);
-                let mut constraint_strs = Vec::new();
-
-                for constraint in constraints {
-                    constraint_strs.push(ConstraintGeneration::generate_constraint_string(constraint));
-                }
-
-                if constraint_strs.is_empty() {
-                    base_str
-                } else {
-                    format!("{}({})", base_str, constraint_strs.join(","))
-                }
+            TypeNode::Record { key, value } => format!("record<{},{}>", Self::generate_type_name(key), Self::generate_type_name(value)),
+            TypeNode::Tuple(elements) => {
+                let items = elements.iter()
+                    .map(|element| match (&element.element_type, element.rest) {
+                        (TypeNode::Array(inner), true) => Self::generate_type_name(inner),
+                        (element_type, _) => Self::generate_type_name(element_type),
+                    })
+                    .collect();
+                Self::tuple_type_name(elements, items)
             }
+            TypeNode::Constrained { base_type, constraints } => Self::constrained_type_name(base_type, constraints),
             TypeNode::Conditional(_) => "any".to_string(), // Fallback for conditional types
-            TypeNode::Literal(expr) => {
-                // Generate literal value without quotes for type names
-                let value_str = Self::generate_expression_value(&Some(expr.clone()));
-                format!("={}", value_str)
-            }
-            TypeNode::InlineObject(_) => "object".to_string(),
+            TypeNode::Literal(expr) => Self::literal_name(expr),
+            TypeNode::Intersection(_) | TypeNode::InlineObject(_) => "object".to_string(),
         }
     }
 
@@ -300,87 +443,14 @@ impl TypeGeneration {
 
     /// Generate constrained type schema
     pub fn generate_constrained_type_schema(base_type: &TypeNode, constraints: &[ConstraintNode]) -> String {
-        let base_str = Self::generate_type_name(base_type);
-
-        if constraints.is_empty() {
-            return format!("\"{}\"", base_str);
-        }
-
-        // Build constraint string based on ReliantType syntax
-        let mut min_val = None;
-        let mut max_val = None;
-        let mut has_special_type = false;
-        let mut special_type = String::new();
-        let mut matches_pattern = None;
-
-        for constraint in constraints {
-            match constraint.constraint_type {
-                ConstraintType::Min => min_val = Some(ConstraintGeneration::generate_constraint_string(constraint)),
-                ConstraintType::Max => max_val = Some(ConstraintGeneration::generate_constraint_string(constraint)),
-                ConstraintType::Matches => {
-                    // Extract regex pattern from matches constraint
-                    if let Some(expr) = &constraint.value {
-                        // For RawString, wrap with / delimiters for ReliantType regex syntax
-                        let pattern_str = match expr {
-                            ExpressionNode::RawString(s) => format!("/{}/", s),
-                            _ => Self::generate_expression_value(&Some(expr.clone()))
-                        };
-                        matches_pattern = Some(pattern_str);
-                    }
-                }
-                // These are standalone types in ReliantType, not constraints
-                ConstraintType::Positive => {
-                    has_special_type = true;
-                    special_type = "positive".to_string();
-                }
-                ConstraintType::Negative => {
-                    has_special_type = true;
-                    special_type = "negative".to_string();
-                }
-                ConstraintType::Integer => {
-                    has_special_type = true;
-                    special_type = "int".to_string();
-                }
-                ConstraintType::Float => {
-                    has_special_type = true;
-                    special_type = "double".to_string();
-                }
-                ConstraintType::Literal => {
-                    // Handle &literal(value) - convert to =value
-                    if let Some(expr) = &constraint.value {
-                        let value_str = Self::generate_expression_value(&Some(expr.clone()));
-                        // Return early with literal type
-                        return format!("\"={}\"", value_str);
-                    }
-                }
-                _ => {
-                    // Other constraints not yet handled
-                }
-            }
-        }
+        ConstraintGeneration::quote(&Self::constrained_type_name(base_type, constraints))
+    }
 
-        // Generate proper ReliantType constraint syntax
-        if let Some(pattern) = matches_pattern {
-            // Regex pattern: "string(/pattern/)"
-            format!("\"{}({})\"", base_str, pattern)
-        } else if has_special_type {
-            // Special types like "positive", "negative", "int", "double" are standalone
-            // If there are also min/max constraints, combine them
-            if min_val.is_some() || max_val.is_some() {
-                let min = min_val.unwrap_or_else(|| String::new());
-                let max = max_val.unwrap_or_else(|| String::new());
-                format!("\"{}({},{})\"", special_type, min, max)
-            } else {
-                format!("\"{}\"", special_type)
-            }
-        } else if min_val.is_some() || max_val.is_some() {
-            // Min/max constraints: "number(min,max)"
-            let min = min_val.unwrap_or_else(|| String::new());
-            let max = max_val.unwrap_or_else(|| String::new());
-            format!("\"{}({},{})\"", base_str, min, max)
-        } else {
-            format!("\"{}\"", base_str)
-        }
+    /// A constraint chain as a ReliantType type string, without quotes
+    fn constrained_type_name(base_type: &TypeNode, constraints: &[ConstraintNode]) -> String {
+        let context = CompilationContext::new();
+        let value = |expr: &ExpressionNode| ExpressionGeneration::expression_to_string(expr, &context);
+        ConstraintGeneration::constraint_schema(base_type, &Self::generate_type_name(base_type), constraints, &value).schema
     }
 
     /// Evaluate an expression in the current context and return the resolved expression
@@ -1,10 +1,13 @@
 use std::collections::{HashMap, HashSet};
 use crate::ast::{ASTNode, SchemaNode, FieldNode, TypeNode, ImportNode, ExportNode};
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
 
 /// Tracks which imported types are actually used in schemas
 pub struct ImportTracker {
-    /// Map of imported type name -> source file
+    /// Map of imported local name or namespace -> source file
     imports: HashMap<String, String>,
+    /// Namespaces of `import * as ns`, used through qualified names `ns.Type`
+    namespaces: HashSet<String>,
     /// Set of types that are actually used
     used_types: HashSet<String>,
     /// Set of types that should be exported
@@ -15,6 +18,7 @@ impl ImportTracker {
     pub fn new() -> Self {
         Self {
             imports: HashMap::new(),
+            namespaces: HashSet::new(),
             used_types: HashSet::new(),
             exports: HashSet::new(),
         }
@@ -22,8 +26,11 @@ impl ImportTracker {
 
     /// Register an import statement
     pub fn add_import(&mut self, import: &ImportNode) {
-        for name in &import.items {
-            self.imports.insert(name.clone(), import.path.clone());
+        for name in import.local_names() {
+            self.imports.insert(name.to_string(), import.path.clone());
+        }
+        if let Some(namespace) = &import.alias {
+            self.namespaces.insert(namespace.clone());
         }
     }
 
@@ -38,15 +45,19 @@ impl ImportTracker {
     pub fn track_type_usage(&mut self, type_node: &TypeNode) {
         match type_node {
             TypeNode::Identifier(name) => {
-                // Check if this is an imported type
+                // Check if this is an imported type, or a name from an imported namespace
                 if self.imports.contains_key(name) {
                     self.used_types.insert(name.clone());
+                } else if let Some((namespace, _)) = name.split_once('.') {
+                    if self.namespaces.contains(namespace) {
+                        self.used_types.insert(namespace.to_string());
+                    }
                 }
             }
             TypeNode::Array(inner) => {
                 self.track_type_usage(inner);
             }
-            TypeNode::Union(types) => {
+            TypeNode::Union(types) | TypeNode::Intersection(types) => {
                 for t in types {
                     self.track_type_usage(t);
                 }
@@ -56,6 +67,15 @@ impl ImportTracker {
                     self.track_type_usage(arg);
                 }
             }
+            TypeNode::Record { key, value } => {
+                self.track_type_usage(key);
+                self.track_type_usage(value);
+            }
+            TypeNode::Tuple(elements) => {
+                for element in elements {
+                    self.track_type_usage(&element.element_type);
+                }
+            }
             TypeNode::Constrained { base_type, .. } => {
                 self.track_type_usage(base_type);
             }
@@ -121,7 +141,7 @@ impl ImportTracker {
 }
 
 /// Analyze AST and build import/export tracking
-pub fn analyze_imports_exports(ast: &[ASTNode]) -> Result<ImportTracker, String> {
+pub fn analyze_imports_exports(ast: &[ASTNode]) -> Result<ImportTracker, Vec<Diagnostic>> {
     let mut tracker = ImportTracker::new();
 
     // First pass: collect imports and exports
@@ -137,23 +157,42 @@ pub fn analyze_imports_exports(ast: &[ASTNode]) -> Result<ImportTracker, String>
         }
     }
 
-    // Second pass: track type usage
+    // Second pass: track type usage; exporting an imported name re-exports it
     for node in ast {
         match node {
             ASTNode::Schema(schema) => {
                 tracker.track_schema_usage(schema);
             }
+            ASTNode::TypeAlias(alias) => {
+                tracker.track_type_usage(&alias.type_definition);
+            }
+            ASTNode::Export(export) if export.from.is_none() => {
+                for name in &export.items {
+                    if tracker.is_imported(name) {
+                        tracker.used_types.insert(name.clone());
+                    }
+                }
+            }
             _ => {}
         }
     }
 
-    // Check for unused imports
-    let unused = tracker.get_unused_imports();
-    if !unused.is_empty() {
-        return Err(format!(
-            "Unused imports detected: {}. Remove them or use them in your schemas.",
-            unused.join(", ")
-        ));
+    // Check for unused imports, in source order
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        if let ASTNode::Import(import) = node {
+            for name in import.local_names().into_iter().filter(|name| !tracker.used_types.contains(*name)) {
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticCode::UnusedImport, format!("Unused import '{}'", name))
+                        .with_location(&import.location, format!("'{}' is imported here but never used", name))
+                        .with_help("remove it from the import or use it in a schema")
+                );
+            }
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
     }
 
     Ok(tracker)
@@ -0,0 +1,586 @@
+/*!
+ * Importer Module
+ *
+ * Converts schemas written in other formats (currently JSON Schema) into
+ * .rel declarations.
+ */
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+
+use crate::ast::*;
+use crate::colors;
+use crate::diagnostics::Severity;
+use crate::generator::TypeScriptGenerator;
+use crate::lexer::{Lexer, TokenType};
+use crate::lint;
+use crate::parser::Parser;
+use crate::printer::Printer;
+
+/// Keywords that only carry documentation and are dropped without a warning
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema", "$id", "$comment", "title", "description", "examples", "default",
+    "readOnly", "writeOnly", "deprecated",
+];
+
+/// Import a JSON Schema file and write the equivalent .rel source
+pub fn import_json_schema(input: &PathBuf, output: Option<&PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(input)?;
+    let schema: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid JSON in {:?}: {}", input, e))?;
+
+    let fallback_name = input.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Schema");
+    // Strip a trailing ".schema" from names like "user.schema.json"
+    let fallback_name = fallback_name.trim_end_matches(".schema");
+
+    let mut importer = JsonSchemaImporter::new();
+    let nodes = importer.convert(&schema, fallback_name);
+    let source = Printer::new().print(&nodes);
+
+    check_generated(&source)?;
+
+    match output {
+        Some(output_path) => {
+            if let Some(parent) = output_path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            fs::write(output_path, &source)?;
+            println!("{} {}", colors::success("Generated"), colors::path(&output_path.display().to_string()));
+        }
+        None => print!("{}", source),
+    }
+
+    if !importer.warnings.is_empty() {
+        eprintln!("\n{}", colors::warning(&format!("{} unsupported construct(s) were skipped:", importer.warnings.len())));
+        for warning in &importer.warnings {
+            eprintln!("  {} {}", colors::dim("•"), warning);
+        }
+    }
+
+    Ok(())
+}
+
+/// Compile `source` the way `rel build` would and return the TypeScript, so
+/// gaps between the printer, parser and generator are caught before a broken
+/// file is written
+pub(crate) fn check_generated(source: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let tokens = Lexer::new(source).tokenize()
+        .map_err(|errors| format!("Generated .rel failed to tokenize: {}", errors[0].message))?;
+    let ast = Parser::new(tokens).parse()
+        .map_err(|errors| format!("Generated .rel failed to parse: {}", errors[0].message))?;
+    if let Some(error) = lint::check(&ast).iter().find(|d| d.severity == Severity::Error) {
+        return Err(format!("Generated .rel does not compile: {}", error.message).into());
+    }
+    Ok(TypeScriptGenerator::new().generate(&ast))
+}
+
+/// Converts a JSON Schema document into .rel AST nodes
+pub struct JsonSchemaImporter {
+    /// Warnings for keywords that have no .rel equivalent
+    pub warnings: Vec<String>,
+    /// Map of JSON Schema definition name -> .rel declaration name
+    definition_names: HashMap<String, String>,
+}
+
+impl JsonSchemaImporter {
+    pub fn new() -> Self {
+        Self {
+            warnings: Vec::new(),
+            definition_names: HashMap::new(),
+        }
+    }
+
+    /// Convert a whole document: `$defs`/`definitions` first, then the root schema
+    pub fn convert(&mut self, schema: &Value, fallback_name: &str) -> Vec<ASTNode> {
+        let mut nodes = Vec::new();
+        let mut exports = Vec::new();
+
+        let definitions: Vec<(String, &Value)> = ["$defs", "definitions"].iter()
+            .filter_map(|key| schema.get(*key).and_then(|v| v.as_object()).map(|defs| (*key, defs)))
+            .flat_map(|(key, defs)| {
+                defs.iter().map(move |(name, def)| (format!("#/{}/{}", key, name), def))
+            })
+            .collect();
+
+        // Register names up front so references can point forward
+        for (pointer, _) in &definitions {
+            let raw_name = pointer.rsplit('/').next().unwrap_or_default();
+            self.definition_names.insert(pointer.clone(), to_type_name(raw_name));
+        }
+
+        for (pointer, definition) in &definitions {
+            let name = self.definition_names[pointer].clone();
+            if let Some(node) = self.convert_declaration(&name, definition, pointer) {
+                exports.push(name);
+                nodes.push(node);
+            }
+        }
+
+        if is_object_schema(schema) {
+            let title = schema.get("title").and_then(|t| t.as_str()).unwrap_or(fallback_name);
+            let name = to_type_name(title);
+            if let Some(node) = self.convert_declaration(&name, schema, "#") {
+                exports.push(name);
+                nodes.push(node);
+            }
+        }
+
+        if !exports.is_empty() {
+            nodes.push(ASTNode::Export(ExportNode::new(exports)));
+        }
+
+        nodes
+    }
+
+    /// Convert a named schema into a `define`, `enum` or `type` declaration
+    fn convert_declaration(&mut self, name: &str, schema: &Value, path: &str) -> Option<ASTNode> {
+        if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+            let names: Option<Vec<String>> = values.iter()
+                .map(|v| v.as_str().filter(|s| is_plain_identifier(s)).map(str::to_string))
+                .collect();
+            if let Some(names) = names {
+                self.check_keywords(schema, path, &["enum", "type"]);
+                return Some(ASTNode::Enum(EnumNode {
+                    name: name.to_string(),
+                    values: names,
+                    location: SourceLocation::unknown(),
+                }));
+            }
+        }
+
+        if schema.get("properties").is_some() {
+            let fields = self.convert_object_fields(schema, path);
+            return Some(ASTNode::Schema(SchemaNode {
+                name: name.to_string(),
+                fields,
+                extends: None,
+                mixins: Vec::new(),
+                generics: Vec::new(),
+                validations: Vec::new(),
+                location: SourceLocation::unknown(),
+            }));
+        }
+
+        let type_definition = self.convert_type(schema, path);
+        Some(ASTNode::TypeAlias(TypeAliasNode {
+            name: name.to_string(),
+            type_definition,
+            location: SourceLocation::unknown(),
+        }))
+    }
+
+    /// Convert `properties`/`required` into field nodes
+    fn convert_object_fields(&mut self, schema: &Value, path: &str) -> Vec<FieldNode> {
+        self.check_keywords(schema, path, &["type", "properties", "required", "additionalProperties"]);
+
+        if let Some(additional) = schema.get("additionalProperties") {
+            if additional != &Value::Bool(false) {
+                self.warn(path, "'additionalProperties' on an object with 'properties' is not supported; extra keys will be rejected");
+            }
+        }
+
+        let required: Vec<&str> = schema.get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        let empty = Map::new();
+        let properties = schema.get("properties").and_then(|p| p.as_object()).unwrap_or(&empty);
+
+        let mut fields = Vec::new();
+        for (property, property_schema) in properties {
+            let property_path = format!("{}/properties/{}", path, property);
+            let name = if is_field_name(property) {
+                property.clone()
+            } else {
+                let renamed = to_field_name(property);
+                self.warn(&property_path, &format!("property '{}' is not a valid field name; renamed to '{}'", property, renamed));
+                renamed
+            };
+
+            let default_value = match property_schema.get("default") {
+                Some(value) => {
+                    let expr = json_to_expression(value);
+                    if expr.is_none() {
+                        self.warn(&property_path, "only scalar 'default' values are supported");
+                    }
+                    expr
+                }
+                None => None,
+            };
+
+            fields.push(FieldNode {
+                name,
+                field_type: self.convert_type(property_schema, &property_path),
+                optional: !required.contains(&property.as_str()),
+                default_value,
+                computed_value: None,
+                validations: Vec::new(),
+                conditionals: Vec::new(),
+                discriminator: None,
+                location: SourceLocation::unknown(),
+                comments: Box::default(),
+            });
+        }
+
+        fields
+    }
+
+    /// Convert an arbitrary JSON Schema into a type expression
+    pub fn convert_type(&mut self, schema: &Value, path: &str) -> TypeNode {
+        let object = match schema {
+            Value::Bool(true) => return TypeNode::Any,
+            Value::Object(object) => object,
+            _ => {
+                self.warn(path, "schema must be an object or 'true'; using 'any'");
+                return TypeNode::Any;
+            }
+        };
+
+        if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+            self.check_keywords(schema, path, &["$ref"]);
+            return match self.definition_names.get(reference) {
+                Some(name) => TypeNode::Identifier(name.clone()),
+                None => {
+                    self.warn(path, &format!("cannot resolve '$ref' to '{}'; only local '$defs'/'definitions' are supported", reference));
+                    TypeNode::Any
+                }
+            };
+        }
+
+        if let Some(value) = object.get("const") {
+            self.check_keywords(schema, path, &["const", "type"]);
+            return self.literal_type(value, path);
+        }
+
+        if let Some(values) = object.get("enum").and_then(|e| e.as_array()) {
+            self.check_keywords(schema, path, &["enum", "type"]);
+            let members: Vec<TypeNode> = values.iter().map(|v| self.literal_type(v, path)).collect();
+            return Self::union_of(members);
+        }
+
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(variants) = object.get(keyword).and_then(|v| v.as_array()) {
+                self.check_keywords(schema, path, &[keyword]);
+                let members: Vec<TypeNode> = variants.iter()
+                    .enumerate()
+                    .map(|(i, variant)| self.convert_type(variant, &format!("{}/{}/{}", path, keyword, i)))
+                    .collect();
+                return Self::union_of(members);
+            }
+        }
+
+        if let Some(parts) = object.get("allOf").and_then(|v| v.as_array()) {
+            if parts.len() == 1 {
+                return self.convert_type(&parts[0], &format!("{}/allOf/0", path));
+            }
+            self.warn(path, "'allOf' with more than one schema is not supported; using 'any'");
+            return TypeNode::Any;
+        }
+
+        match object.get("type") {
+            Some(Value::String(type_name)) => self.convert_typed(type_name, schema, path),
+            Some(Value::Array(type_names)) => {
+                let members: Vec<TypeNode> = type_names.iter()
+                    .filter_map(|t| t.as_str())
+                    .map(|t| self.convert_typed(t, schema, path))
+                    .collect();
+                Self::union_of(members)
+            }
+            _ if object.contains_key("properties") => TypeNode::InlineObject(self.convert_object_fields(schema, path)),
+            _ => {
+                self.check_keywords(schema, path, &[]);
+                TypeNode::Any
+            }
+        }
+    }
+
+    /// Convert a schema whose `type` keyword names a single JSON type
+    fn convert_typed(&mut self, type_name: &str, schema: &Value, path: &str) -> TypeNode {
+        match type_name {
+            "string" => {
+                self.check_keywords(schema, path, &["type", "format", "minLength", "maxLength", "pattern"]);
+                let base = match schema.get("format").and_then(|f| f.as_str()) {
+                    Some(format) => self.convert_format(format, path),
+                    None => TypeNode::String,
+                };
+                let mut constraints = Vec::new();
+                Self::push_number_constraint(&mut constraints, schema, "minLength", ConstraintType::MinLength);
+                Self::push_number_constraint(&mut constraints, schema, "maxLength", ConstraintType::MaxLength);
+                if let Some(pattern) = schema.get("pattern").and_then(|p| p.as_str()) {
+                    constraints.push(ConstraintNode {
+                        constraint_type: ConstraintType::Matches,
+                        value: Some(ExpressionNode::RawString(pattern.to_string())),
+                        location: SourceLocation::unknown(),
+                    });
+                }
+                Self::constrained(base, constraints)
+            }
+            "number" | "integer" => {
+                self.check_keywords(schema, path, &["type", "minimum", "maximum"]);
+                let base = if type_name == "integer" {
+                    TypeNode::Identifier("int".to_string())
+                } else {
+                    TypeNode::Number
+                };
+                let mut constraints = Vec::new();
+                Self::push_number_constraint(&mut constraints, schema, "minimum", ConstraintType::Min);
+                Self::push_number_constraint(&mut constraints, schema, "maximum", ConstraintType::Max);
+                Self::constrained(base, constraints)
+            }
+            "boolean" => {
+                self.check_keywords(schema, path, &["type"]);
+                TypeNode::Boolean
+            }
+            "null" => TypeNode::Null,
+            "array" => {
+                self.check_keywords(schema, path, &["type", "items", "prefixItems", "additionalItems", "minItems", "maxItems", "uniqueItems"]);
+                // Tuples: `prefixItems` (2020-12) or an `items` array (draft 7)
+                let (positional, rest_keyword) = match (schema.get("prefixItems"), schema.get("items")) {
+                    (Some(Value::Array(items)), _) => (Some((items, "prefixItems")), "items"),
+                    (None, Some(Value::Array(items))) => (Some((items, "items")), "additionalItems"),
+                    _ => (None, ""),
+                };
+                if let Some((items, keyword)) = positional {
+                    return self.convert_tuple(schema, items, keyword, rest_keyword, path);
+                }
+                let inner = match schema.get("items") {
+                    Some(items) => self.convert_type(items, &format!("{}/items", path)),
+                    None => TypeNode::Any,
+                };
+                let mut constraints = Vec::new();
+                Self::push_number_constraint(&mut constraints, schema, "minItems", ConstraintType::MinItems);
+                Self::push_number_constraint(&mut constraints, schema, "maxItems", ConstraintType::MaxItems);
+                if schema.get("uniqueItems").and_then(|u| u.as_bool()) == Some(true) {
+                    constraints.push(ConstraintNode {
+                        constraint_type: ConstraintType::Unique,
+                        value: None,
+                        location: SourceLocation::unknown(),
+                    });
+                }
+                Self::constrained(TypeNode::Array(Box::new(inner)), constraints)
+            }
+            "object" => {
+                if schema.get("properties").is_some() {
+                    return TypeNode::InlineObject(self.convert_object_fields(schema, path));
+                }
+                self.check_keywords(schema, path, &["type", "additionalProperties"]);
+                match schema.get("additionalProperties") {
+                    Some(Value::Object(_)) => {
+                        let value_schema = &schema["additionalProperties"];
+                        let value_type = self.convert_type(value_schema, &format!("{}/additionalProperties", path));
+                        TypeNode::Record { key: Box::new(TypeNode::String), value: Box::new(value_type) }
+                    }
+                    _ => TypeNode::Object,
+                }
+            }
+            other => {
+                self.warn(path, &format!("unknown type '{}'; using 'any'", other));
+                TypeNode::Any
+            }
+        }
+    }
+
+    /// A tuple from positional item schemas; items past `minItems` are optional
+    /// and a schema under `rest_keyword` types any items after them
+    fn convert_tuple(&mut self, schema: &Value, items: &[Value], keyword: &str, rest_keyword: &str, path: &str) -> TypeNode {
+        let required = schema.get("minItems").and_then(|m| m.as_u64()).unwrap_or(0) as usize;
+        let mut elements: Vec<TupleElement> = items.iter().enumerate()
+            .map(|(index, item)| TupleElement {
+                element_type: self.convert_type(item, &format!("{}/{}/{}", path, keyword, index)),
+                optional: index >= required,
+                rest: false,
+            })
+            .collect();
+        if let Some(rest @ Value::Object(_)) = schema.get(rest_keyword) {
+            let item_type = self.convert_type(rest, &format!("{}/{}", path, rest_keyword));
+            elements.push(TupleElement { element_type: TypeNode::Array(Box::new(item_type)), optional: false, rest: true });
+        }
+        TypeNode::Tuple(elements)
+    }
+
+    /// Map a JSON Schema `format` onto a rel format type
+    fn convert_format(&mut self, format: &str, path: &str) -> TypeNode {
+        let type_name = match format {
+            "email" | "idn-email" => "email",
+            "uri" | "url" | "iri" => "url",
+            "uuid" => "uuid",
+            "date" | "date-time" => "date",
+            "ipv4" | "ipv6" => "ip",
+            "json-pointer" | "regex" | "time" | "duration" | "hostname" | "idn-hostname" | "uri-reference" | "uri-template" => {
+                self.warn(path, &format!("format '{}' has no rel equivalent; using 'string'", format));
+                return TypeNode::String;
+            }
+            other if Lexer::is_builtin_type(other) => other,
+            other => {
+                self.warn(path, &format!("unknown format '{}'; using 'string'", other));
+                return TypeNode::String;
+            }
+        };
+        TypeNode::Identifier(type_name.to_string())
+    }
+
+    fn literal_type(&mut self, value: &Value, path: &str) -> TypeNode {
+        match value {
+            // Bare identifiers read best in unions (e.g. `admin | user`)
+            Value::String(s) if is_plain_identifier(s) => TypeNode::Identifier(s.clone()),
+            Value::Null => TypeNode::Null,
+            _ => match json_to_expression(value) {
+                Some(expr) => TypeNode::Literal(expr),
+                None => {
+                    self.warn(path, "only scalar 'enum'/'const' values are supported; using 'any'");
+                    TypeNode::Any
+                }
+            },
+        }
+    }
+
+    fn push_number_constraint(constraints: &mut Vec<ConstraintNode>, schema: &Value, keyword: &str, constraint_type: ConstraintType) {
+        if let Some(value) = schema.get(keyword).and_then(|v| v.as_f64()) {
+            constraints.push(ConstraintNode {
+                constraint_type,
+                value: Some(ExpressionNode::Number(value)),
+                location: SourceLocation::unknown(),
+            });
+        }
+    }
+
+    fn constrained(base: TypeNode, constraints: Vec<ConstraintNode>) -> TypeNode {
+        if constraints.is_empty() {
+            base
+        } else {
+            TypeNode::Constrained {
+                base_type: Box::new(base),
+                constraints,
+            }
+        }
+    }
+
+    fn union_of(mut members: Vec<TypeNode>) -> TypeNode {
+        if members.len() == 1 {
+            members.remove(0)
+        } else {
+            TypeNode::Union(members)
+        }
+    }
+
+    /// Warn about every keyword that is neither handled nor an annotation
+    fn check_keywords(&mut self, schema: &Value, path: &str, handled: &[&str]) {
+        if let Some(object) = schema.as_object() {
+            for keyword in object.keys() {
+                let is_definitions = path == "#" && (keyword == "$defs" || keyword == "definitions");
+                if !handled.contains(&keyword.as_str()) && !ANNOTATION_KEYWORDS.contains(&keyword.as_str()) && !is_definitions {
+                    self.warn(path, &format!("unsupported keyword '{}'", keyword));
+                }
+            }
+        }
+    }
+
+    fn warn(&mut self, path: &str, message: &str) {
+        self.warnings.push(format!("{}: {}", path, message));
+    }
+}
+
+impl Default for JsonSchemaImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_object_schema(schema: &Value) -> bool {
+    schema.get("properties").is_some() || schema.get("type").and_then(|t| t.as_str()) == Some("object")
+}
+
+/// Convert a scalar JSON value into a .rel expression
+fn json_to_expression(value: &Value) -> Option<ExpressionNode> {
+    match value {
+        Value::String(s) => Some(ExpressionNode::String(s.clone())),
+        Value::Number(n) => n.as_f64().map(ExpressionNode::Number),
+        Value::Bool(b) => Some(ExpressionNode::Boolean(*b)),
+        Value::Null => Some(ExpressionNode::Null),
+        _ => None,
+    }
+}
+
+/// True when `name` lexes as a single plain identifier (not a keyword, type or constraint)
+pub(crate) fn is_plain_identifier(name: &str) -> bool {
+    matches!(
+        Lexer::new(name).tokenize().as_deref(),
+        Ok([token, _eof]) if token.token_type == TokenType::Identifier && token.value == name
+    )
+}
+
+/// Field names may also reuse built-in type names (e.g. `email: email`)
+pub(crate) fn is_field_name(name: &str) -> bool {
+    matches!(
+        Lexer::new(name).tokenize().as_deref(),
+        Ok([token, _eof]) if matches!(token.token_type, TokenType::Identifier | TokenType::TypeName) && token.value == name
+    )
+}
+
+pub(crate) fn to_field_name(name: &str) -> String {
+    let mut field: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if field.chars().next().is_none_or(|c| c.is_ascii_digit()) {
+        field.insert(0, '_');
+    }
+    if !is_field_name(&field) {
+        field.push('_');
+    }
+    field
+}
+
+/// Convert a definition name or title into a PascalCase type name
+pub(crate) fn to_type_name(name: &str) -> String {
+    let mut result = String::new();
+    for part in name.split(|c: char| !c.is_alphanumeric()).filter(|p| !p.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.push_str(chars.as_str());
+        }
+    }
+    if result.chars().next().is_none_or(|c| c.is_ascii_digit()) {
+        result.insert(0, 'T');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_values_that_are_not_identifiers_import_and_generate() {
+        let dir = std::env::temp_dir().join(format!("rel-importer-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("account.schema.json");
+        fs::write(&input, r#"{
+            "type": "object",
+            "properties": {
+                "role": { "enum": ["admin", "user", "super-admin"] },
+                "quote": { "const": "say \"hi\"" }
+            },
+            "required": ["role"]
+        }"#).unwrap();
+        let output = dir.join("nested/account.rel");
+
+        import_json_schema(&input, Some(&output)).expect("import");
+        let source = fs::read_to_string(&output).unwrap();
+        assert!(source.contains(r#"role: admin | user | ="super-admin""#), "{}", source);
+
+        let generated = check_generated(&source).expect("generate");
+        assert!(generated.contains(r#"role: "admin|user|=super-admin","#), "{}", generated);
+        assert!(generated.contains(r#"quote?: "=say \"hi\"","#), "{}", generated);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,371 @@
+/*!
+ * Infer Module
+ *
+ * Bootstraps a .rel schema from example JSON payloads.
+ */
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::ast::*;
+use crate::colors;
+use crate::importer::{is_field_name, is_plain_identifier, to_field_name, to_type_name};
+use crate::lexer::Lexer;
+use crate::printer::Printer;
+
+/// Infer a schema from sample JSON files and write it as .rel source
+pub fn infer_files(
+    inputs: &[PathBuf],
+    name: &str,
+    output: Option<&PathBuf>,
+    max_literals: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut inference = SchemaInference::new(max_literals);
+
+    for input in inputs {
+        let content = fs::read_to_string(input)?;
+        let sample: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid JSON in {:?}: {}", input, e))?;
+
+        // A top-level array is treated as a list of samples
+        match sample {
+            Value::Array(items) => items.iter().for_each(|item| inference.add_sample(item)),
+            other => inference.add_sample(&other),
+        }
+    }
+
+    if inference.sample_count() == 0 {
+        return Err("No samples found".into());
+    }
+
+    let (nodes, warnings) = inference.to_ast(&to_type_name(name))?;
+    let source = Printer::new().print(&nodes);
+
+    match output {
+        Some(output_path) => {
+            if let Some(parent) = output_path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            fs::write(output_path, &source)?;
+            println!(
+                "{} {} from {} sample(s)",
+                colors::success("Inferred"),
+                colors::path(&output_path.display().to_string()),
+                inference.sample_count()
+            );
+        }
+        None => print!("{}", source),
+    }
+
+    if !warnings.is_empty() {
+        eprintln!("\n{}", colors::warning(&format!("{} key(s) were renamed:", warnings.len())));
+        for warning in &warnings {
+            eprintln!("  {} {}", colors::dim("•"), warning);
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything observed at one position across all samples
+#[derive(Debug, Default)]
+struct Observation {
+    /// Number of times this position held a value (including null)
+    occurrences: usize,
+    nulls: usize,
+    booleans: usize,
+    integers: usize,
+    floats: usize,
+    strings: usize,
+    /// Distinct string values, used for format detection and literal unions
+    distinct_strings: Vec<String>,
+    arrays: usize,
+    elements: Option<Box<Observation>>,
+    objects: usize,
+    fields: Vec<String>,
+    field_observations: HashMap<String, Observation>,
+}
+
+/// Accumulates samples and turns them into a schema
+pub struct SchemaInference {
+    root: Observation,
+    samples: usize,
+    max_literals: usize,
+}
+
+impl SchemaInference {
+    pub fn new(max_literals: usize) -> Self {
+        Self {
+            root: Observation::default(),
+            samples: 0,
+            max_literals,
+        }
+    }
+
+    pub fn add_sample(&mut self, sample: &Value) {
+        self.samples += 1;
+        Self::observe(&mut self.root, sample);
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.samples
+    }
+
+    fn observe(observation: &mut Observation, value: &Value) {
+        observation.occurrences += 1;
+
+        match value {
+            Value::Null => observation.nulls += 1,
+            Value::Bool(_) => observation.booleans += 1,
+            Value::Number(n) => {
+                if n.is_i64() || n.is_u64() {
+                    observation.integers += 1;
+                } else {
+                    observation.floats += 1;
+                }
+            }
+            Value::String(s) => {
+                observation.strings += 1;
+                if !observation.distinct_strings.contains(s) {
+                    observation.distinct_strings.push(s.clone());
+                }
+            }
+            Value::Array(items) => {
+                observation.arrays += 1;
+                let elements = observation.elements.get_or_insert_with(Default::default);
+                for item in items {
+                    Self::observe(elements, item);
+                }
+            }
+            Value::Object(map) => {
+                observation.objects += 1;
+                for (key, field_value) in map {
+                    if !observation.field_observations.contains_key(key) {
+                        observation.fields.push(key.clone());
+                    }
+                    let field = observation.field_observations.entry(key.clone()).or_default();
+                    Self::observe(field, field_value);
+                }
+            }
+        }
+    }
+
+    /// Build `define <name> { ... }` plus its export, with a warning for every
+    /// key that had to be renamed into a field name. Fails when the samples
+    /// aren't objects, or when two keys would become the same field.
+    pub fn to_ast(&self, name: &str) -> Result<(Vec<ASTNode>, Vec<String>), String> {
+        if self.root.objects == 0 {
+            return Err("Samples must be JSON objects to infer a schema".to_string());
+        }
+
+        let mut warnings = Vec::new();
+        let schema = SchemaNode {
+            name: name.to_string(),
+            fields: self.to_fields(&self.root, "$", &mut warnings)?,
+            extends: None,
+            mixins: Vec::new(),
+            generics: Vec::new(),
+            validations: Vec::new(),
+            location: SourceLocation::unknown(),
+        };
+
+        let nodes = vec![
+            ASTNode::Schema(schema),
+            ASTNode::Export(ExportNode::new(vec![name.to_string()])),
+        ];
+        Ok((nodes, warnings))
+    }
+
+    fn to_fields(&self, observation: &Observation, path: &str, warnings: &mut Vec<String>) -> Result<Vec<FieldNode>, String> {
+        // Field name -> the key it came from
+        let mut keys: HashMap<String, &str> = HashMap::new();
+        observation.fields.iter()
+            .map(|key| {
+                let field = &observation.field_observations[key];
+                let key_path = format!("{}.{}", path, key);
+                let name = if is_field_name(key) {
+                    key.clone()
+                } else {
+                    let renamed = to_field_name(key);
+                    warnings.push(format!("{}: key '{}' is not a valid field name; renamed to '{}'", key_path, key, renamed));
+                    renamed
+                };
+                if let Some(other) = keys.insert(name.clone(), key) {
+                    return Err(format!("{}: keys '{}' and '{}' would both become field '{}'; rename one in the samples", path, other, key, name));
+                }
+                Ok(FieldNode {
+                    name,
+                    field_type: self.to_type(field, &key_path, warnings)?,
+                    // Missing from some objects means optional
+                    optional: field.occurrences < observation.objects,
+                    default_value: None,
+                    computed_value: None,
+                    validations: Vec::new(),
+                    conditionals: Vec::new(),
+                    discriminator: None,
+                    location: SourceLocation::unknown(),
+                    comments: Box::default(),
+                })
+            })
+            .collect()
+    }
+
+    fn to_type(&self, observation: &Observation, path: &str, warnings: &mut Vec<String>) -> Result<TypeNode, String> {
+        let mut members = Vec::new();
+
+        if observation.booleans > 0 {
+            members.push(TypeNode::Boolean);
+        }
+        if observation.floats > 0 {
+            members.push(TypeNode::Identifier("float".to_string()));
+        } else if observation.integers > 0 {
+            members.push(TypeNode::Identifier("int".to_string()));
+        }
+        if observation.strings > 0 {
+            members.extend(self.string_types(observation));
+        }
+        if observation.arrays > 0 {
+            let element_type = match &observation.elements {
+                Some(elements) if elements.occurrences > 0 => self.to_type(elements, &format!("{}[]", path), warnings)?,
+                _ => TypeNode::Any,
+            };
+            members.push(TypeNode::Array(Box::new(element_type)));
+        }
+        if observation.objects > 0 {
+            members.push(TypeNode::InlineObject(self.to_fields(observation, path, warnings)?));
+        }
+        if observation.nulls > 0 {
+            members.push(TypeNode::Null);
+        }
+
+        Ok(match members.len() {
+            0 => TypeNode::Any,
+            1 => members.remove(0),
+            _ => TypeNode::Union(members),
+        })
+    }
+
+    /// A detected format, a literal union for small repeated value sets, or plain `string`
+    fn string_types(&self, observation: &Observation) -> Vec<TypeNode> {
+        if let Some(format) = detect_format(&observation.distinct_strings) {
+            return vec![TypeNode::Identifier(format.to_string())];
+        }
+
+        // Only repeated values suggest a closed set; unique ones are free text
+        let distinct = observation.distinct_strings.len();
+        let repeats = distinct < observation.strings;
+        if repeats && distinct <= self.max_literals {
+            return observation.distinct_strings.iter()
+                .map(|value| {
+                    if is_plain_identifier(value) {
+                        TypeNode::Identifier(value.clone())
+                    } else {
+                        TypeNode::Literal(ExpressionNode::String(value.clone()))
+                    }
+                })
+                .collect();
+        }
+
+        vec![TypeNode::String]
+    }
+}
+
+/// Return the format type shared by every value, using the names `Lexer::is_builtin_type` accepts
+fn detect_format(values: &[String]) -> Option<&'static str> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let formats: [(&str, &str); 4] = [
+        ("uuid", r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"),
+        ("email", r"^[^\s@]+@[^\s@]+\.[^\s@]+$"),
+        ("url", r"^https?://[^\s/$.?#].[^\s]*$"),
+        ("date", r"^\d{4}-\d{2}-\d{2}([Tt ]\d{2}:\d{2}(:\d{2}(\.\d+)?)?([Zz]|[+-]\d{2}:?\d{2})?)?$"),
+    ];
+
+    formats.iter()
+        .filter(|(name, _)| Lexer::is_builtin_type(name))
+        .find(|(_, pattern)| {
+            let regex = Regex::new(pattern).expect("format pattern is valid");
+            values.iter().all(|v| regex.is_match(v))
+        })
+        .map(|(name, _)| *name)
+}
+
+impl Default for SchemaInference {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_infers_optional_fields_formats_and_literals() {
+        let mut inference = SchemaInference::new(5);
+        inference.add_sample(&json!({
+            "id": "1b4e28ba-2fa1-11d2-883f-0016d3cca427",
+            "status": "paid",
+            "total": 10,
+            "note": "first"
+        }));
+        inference.add_sample(&json!({
+            "id": "6fa459ea-ee8a-3ca4-894e-db77e160355e",
+            "status": "paid",
+            "total": 12.5
+        }));
+        inference.add_sample(&json!({
+            "id": "16fd2706-8baf-433b-82eb-8c7fada847da",
+            "status": "pending",
+            "total": 3
+        }));
+
+        let (nodes, warnings) = inference.to_ast("Order").unwrap();
+        assert!(warnings.is_empty());
+        let source = Printer::new().print(&nodes);
+
+        assert!(source.contains("id: uuid\n"));
+        assert!(source.contains("status: paid | pending\n"));
+        assert!(source.contains("total: float\n"));
+        assert!(source.contains("note: string?\n"));
+    }
+
+    #[test]
+    fn test_renamed_keys_warn_and_colliding_keys_fail() {
+        let dir = std::env::temp_dir().join(format!("rel-infer-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let renamed = dir.join("renamed.json");
+        fs::write(&renamed, r#"{ "first-name": "Ada", "address": { "zip-code": "12345" } }"#).unwrap();
+        let colliding = dir.join("colliding.json");
+        fs::write(&colliding, r#"{ "first-name": "Ada", "first_name": "Ada" }"#).unwrap();
+
+        // The output's directories are created like `rel import` does
+        let output = dir.join("schemas/people/person.rel");
+        infer_files(std::slice::from_ref(&renamed), "Person", Some(&output), 5).expect("infer");
+        let source = fs::read_to_string(&output).unwrap();
+        assert!(source.contains("first_name: string\n"), "{}", source);
+        assert!(source.contains("zip_code: string\n"), "{}", source);
+
+        let mut inference = SchemaInference::new(5);
+        inference.add_sample(&serde_json::from_str(&fs::read_to_string(&renamed).unwrap()).unwrap());
+        let (_, warnings) = inference.to_ast("Person").unwrap();
+        assert_eq!(warnings, vec![
+            "$.first-name: key 'first-name' is not a valid field name; renamed to 'first_name'".to_string(),
+            "$.address.zip-code: key 'zip-code' is not a valid field name; renamed to 'zip_code'".to_string(),
+        ]);
+
+        let error = infer_files(&[colliding], "Person", None, 5).unwrap_err();
+        assert_eq!(error.to_string(), "$: keys 'first-name' and 'first_name' would both become field 'first_name'; rename one in the samples");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*!
  * rel Interpreter - Executes rel AST nodes
  * 
  * Supports:
@@ -9,6 +9,7 @@
  */
 
 use crate::ast::*;
+use crate::diagnostics::DiagnosticCode;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -58,14 +59,16 @@ impl std::fmt::Display for RuntimeValue {
 
 #[derive(Debug)]
 pub struct RuntimeError {
+    pub code: DiagnosticCode,
     pub message: String,
     pub line: usize,
     pub column: usize,
 }
 
 impl RuntimeError {
-    fn new(message: String) -> Self {
+    fn new(code: DiagnosticCode, message: String) -> Self {
         Self {
+            code,
             message,
             line: 0,
             column: 0,
@@ -151,13 +154,13 @@ impl Interpreter {
             ExpressionNode::Identifier(name) => {
                 self.variables.get(name)
                     .cloned()
-                    .ok_or_else(|| RuntimeError::new(format!("Undefined variable: {}", name)))
+                    .ok_or_else(|| RuntimeError::new(DiagnosticCode::UndefinedVariable, format!("Undefined variable: {}", name)))
             }
             
             ExpressionNode::VariableRef(name) => {
                 self.variables.get(name)
                     .cloned()
-                    .ok_or_else(|| RuntimeError::new(format!("Undefined variable: {}", name)))
+                    .ok_or_else(|| RuntimeError::new(DiagnosticCode::UndefinedVariable, format!("Undefined variable: {}", name)))
             }
             
             ExpressionNode::Array(elements) => {
@@ -269,7 +272,7 @@ impl Interpreter {
             }
             (RuntimeValue::Number(l), BinaryOperator::Divide, RuntimeValue::Number(r)) => {
                 if *r == 0.0 {
-                    Err(RuntimeError::new("Division by zero".to_string()))
+                    Err(RuntimeError::new(DiagnosticCode::DivisionByZero, "Division by zero".to_string()))
                 } else {
                     Ok(RuntimeValue::Number(l / r))
                 }
@@ -322,7 +325,7 @@ impl Interpreter {
                 Ok(RuntimeValue::Boolean(*l || *r))
             }
             
-            _ => Err(RuntimeError::new(format!(
+            _ => Err(RuntimeError::new(DiagnosticCode::InvalidOperation, format!(
                 "Invalid binary operation: {:?} {:?} {:?}",
                 left, operator, right
             ))),
@@ -341,7 +344,7 @@ impl Interpreter {
             (UnaryOperator::Negate, RuntimeValue::Number(n)) => {
                 Ok(RuntimeValue::Number(-n))
             }
-            _ => Err(RuntimeError::new(format!(
+            _ => Err(RuntimeError::new(DiagnosticCode::InvalidOperation, format!(
                 "Invalid unary operation: {:?} {:?}",
                 operator, operand
             ))),
@@ -356,7 +359,7 @@ impl Interpreter {
         match name {
             "len" | "length" => {
                 if arguments.len() != 1 {
-                    return Err(RuntimeError::new(format!(
+                    return Err(RuntimeError::new(DiagnosticCode::InvalidOperation, format!(
                         "Function '{}' expects 1 argument, got {}",
                         name,
                         arguments.len()
@@ -366,13 +369,13 @@ impl Interpreter {
                 match arg {
                     RuntimeValue::String(s) => Ok(RuntimeValue::Number(s.len() as f64)),
                     RuntimeValue::Array(arr) => Ok(RuntimeValue::Number(arr.len() as f64)),
-                    _ => Err(RuntimeError::new(format!(
+                    _ => Err(RuntimeError::new(DiagnosticCode::InvalidOperation, format!(
                         "Function '{}' expects a string or array",
                         name
                     ))),
                 }
             }
-            _ => Err(RuntimeError::new(format!("Unknown function: {}", name))),
+            _ => Err(RuntimeError::new(DiagnosticCode::UnknownFunction, format!("Unknown function: {}", name))),
         }
     }
 
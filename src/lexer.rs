@@ -1,9 +1,31 @@
-/**
+/*!
  * rel Lexer - Tokenizes .rel schema language
  *
  * Based on the existing ConditionalLexer but adapted for full .rel syntax
  */
 
+use crate::diagnostics::DiagnosticCode;
+
+/// Built-in type names, lexed as `TokenType::TypeName`
+pub const BUILTIN_TYPES: &[&str] = &[
+    // Basic types
+    "string", "number", "boolean", "object", "array", "date", "any", "unknown",
+    // Format types
+    "email", "url", "uuid", "phone", "ip", "json", "hexcolor", "base64", "jwt", "semver", "slug", "text", "password", "username",
+    // Number types
+    "positive", "negative", "integer", "float", "int", "double",
+    // Special types
+    "record",
+];
+
+/// Constraint function names, lexed as `TokenType::Constraint`
+pub const CONSTRAINT_FUNCTIONS: &[&str] = &[
+    "min", "max", "minLength", "maxLength", "minItems", "maxItems", "unique", "matches", "contains",
+    "startsWith", "endsWith", "hasUppercase", "hasLowercase", "hasNumber",
+    "hasSpecialChar", "between", "in", "notIn", "exists", "empty",
+    "null", "future", "past", "before", "after", "integer", "positive", "negative", "float",
+    "literal",
+];
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
@@ -59,6 +81,7 @@ pub enum TokenType {
     Pipe, // | for unions
     DoubleColon,    // ::
     DotDot,         // ..
+    Ellipsis,       // ... before a tuple's rest element
     Question, // ? for optional
     Colon,
     Dot,
@@ -106,6 +129,7 @@ pub struct Token {
 
 #[derive(Debug)]
 pub struct LexerError {
+    pub code: DiagnosticCode,
     pub message: String,
     pub position: usize,
     pub line: usize,
@@ -122,6 +146,7 @@ pub struct Lexer {
     current_token_column: usize,  // Track column where token started
     tokens: Vec<Token>,
     errors: Vec<LexerError>,
+    keep_comments: bool,          // Emit Comment tokens instead of discarding them
 }
 
 impl Lexer {
@@ -136,9 +161,16 @@ impl Lexer {
             current_token_column: 1,
             tokens: Vec::new(),
             errors: Vec::new(),
+            keep_comments: false,
         }
     }
 
+    /// Keep `#` comments as Comment tokens (used by the formatter)
+    pub fn with_comments(mut self) -> Self {
+        self.keep_comments = true;
+        self
+    }
+
     pub fn tokenize(mut self) -> Result<Vec<Token>, Vec<LexerError>> {
         while !self.is_at_end() {
             self.current_token_start = self.position;
@@ -262,7 +294,11 @@ impl Lexer {
                 }
             }
             '.' => {
-                if self.peek() == Some('.') {
+                if self.peek() == Some('.') && self.peek_next() == Some('.') {
+                    self.advance();
+                    self.advance();
+                    self.add_token(TokenType::Ellipsis, "...");
+                } else if self.peek() == Some('.') {
                     self.advance();
                     self.add_token(TokenType::Range, "..");
                 } else {
@@ -290,19 +326,30 @@ impl Lexer {
             ']' => self.add_token(TokenType::RBracket, "]"),
             '(' => self.add_token(TokenType::LParen, "("),
             ')' => self.add_token(TokenType::RParen, ")"),
-            c if c.is_whitespace() => self.skip_whitespace(),
+            c if c.is_whitespace() => {
+                // The first whitespace character was consumed above, so count it here
+                if c == '\n' {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                self.skip_whitespace();
+            }
             c if c.is_alphabetic() || c == '_' => self.scan_identifier(),
             _ => {
-                self.add_error(format!("Unexpected character: '{}'", char));
+                self.add_error(DiagnosticCode::UnexpectedCharacter, format!("Unexpected character: '{}'", char));
             }
         }
     }
 
     fn scan_comment(&mut self) {
+        let mut text = String::new();
         while !self.is_at_end() && self.peek() != Some('\n') {
-            self.advance();
+            text.push(self.advance());
+        }
+        // Comments only reach the token stream when explicitly requested
+        if self.keep_comments {
+            self.add_token(TokenType::Comment, text.trim_end());
         }
-        // Don't add comment tokens to the stream
     }
 
     fn scan_string(&mut self, quote: char) {
@@ -321,7 +368,7 @@ impl Lexer {
                 self.advance(); // Skip backslash
 
                 if self.is_at_end() {
-                    self.add_error("Unterminated escape sequence in string".to_string());
+                    self.add_error(DiagnosticCode::InvalidEscape, "Unterminated escape sequence in string".to_string());
                     return;
                 }
 
@@ -334,7 +381,7 @@ impl Lexer {
                     '\'' => value.push('\''),
                     c => {
                         value.push(c);
-                        self.add_error(format!("Unknown escape sequence: \\{}", c));
+                        self.add_error(DiagnosticCode::InvalidEscape, format!("Unknown escape sequence: \\{}", c));
                     }
                 }
             } else {
@@ -343,7 +390,7 @@ impl Lexer {
         }
 
         if self.is_at_end() {
-            self.add_error(format!(
+            self.add_error(DiagnosticCode::UnterminatedString, format!(
                 "Unterminated string starting at line {}, column {}",
                 start_line, start_column
             ));
@@ -370,7 +417,7 @@ impl Lexer {
         }
 
         if self.is_at_end() {
-            self.add_error(format!(
+            self.add_error(DiagnosticCode::UnterminatedString, format!(
                 "Unterminated raw string starting at line {}, column {}",
                 start_line, start_column
             ));
@@ -442,7 +489,7 @@ impl Lexer {
 
         // Validate number format
         if value == "." || value.ends_with('.') || value == "-" {
-            self.add_error("Invalid number format".to_string());
+            self.add_error(DiagnosticCode::InvalidNumber, "Invalid number format".to_string());
             return;
         }
 
@@ -498,29 +545,12 @@ impl Lexer {
         self.add_token(token_type, &value);
     }
 
-    fn is_builtin_type(name: &str) -> bool {
-        matches!(
-            name,
-            // Basic types
-            "string" | "number" | "boolean" | "object" | "array" | "date" | "any" | "unknown" |
-            // Format types
-            "email" | "url" | "uuid" | "phone" | "ip" | "json" | "hexcolor" | "base64" | "jwt" | "semver" | "slug" | "text" | "password" | "username" |
-            // Number types
-            "positive" | "negative" | "integer" | "float" | "int" | "double" |
-            // Special types
-            "record"
-        )
+    pub fn is_builtin_type(name: &str) -> bool {
+        BUILTIN_TYPES.contains(&name)
     }
 
     fn is_constraint_function(name: &str) -> bool {
-        matches!(
-            name,
-            "min" | "max" | "minLength" | "maxLength" | "matches" | "contains" |
-            "startsWith" | "endsWith" | "hasUppercase" | "hasLowercase" | "hasNumber" |
-            "hasSpecialChar" | "between" | "in" | "notIn" | "exists" | "empty" |
-            "null" | "future" | "past" | "before" | "after" | "integer" | "positive" | "negative" | "float" |
-            "literal"
-        )
+        CONSTRAINT_FUNCTIONS.contains(&name)
     }
 
     fn skip_whitespace(&mut self) {
@@ -576,8 +606,9 @@ impl Lexer {
         });
     }
 
-    fn add_error(&mut self, message: String) {
+    fn add_error(&mut self, code: DiagnosticCode, message: String) {
         self.errors.push(LexerError {
+            code,
             message,
             position: self.current_token_start,
             line: self.current_token_line,      // Use line where token/error started
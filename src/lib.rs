@@ -1,4 +1,4 @@
-/**
+/*!
  * rel - ReliantType Compiler Library
  *
  * This library provides the core functionality for compiling .rel files
@@ -13,15 +13,27 @@ pub mod compiler;
 pub mod resolver;
 pub mod import_tracker;
 pub mod validation;
+pub mod lint;
 pub mod colors;
 pub mod context;
 pub mod interpreter;
+pub mod printer;
+pub mod diagnostics;
+pub mod config;
+pub mod cache;
+pub mod manifest;
+pub mod parallel;
+pub mod session;
 
 // New modular structure
 pub mod commands;
 pub mod project;
 pub mod run;
 pub mod watch;
+pub mod importer;
+pub mod infer;
+pub mod formatter;
+pub mod lsp;
 
 // Re-export main types for easy usage
 pub use compiler::{rel, relCompiler, CompilerOptions};
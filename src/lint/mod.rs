@@ -0,0 +1,640 @@
+/*!
+ * Lint Engine - named rules with configurable levels
+ *
+ * Each rule has a name, used in the "lint" section of rel.json and in
+ * `# rel-ignore: rule-name` comments, the diagnostic code it reports and a
+ * default level. Rules run on one file's own AST, before imports are merged,
+ * so every finding points at the file that has to change.
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+use crate::ast::{
+    ASTNode, BinaryOperator, ConditionalNode, ExpressionNode, FieldNode,
+    SourceLocation, TypeNode, UnaryOperator,
+};
+use crate::config::{LintLevel, ProjectConfig};
+use crate::diagnostics::{Diagnostic, DiagnosticCode, Span};
+use crate::import_tracker::analyze_imports_exports;
+use crate::validation::{constraints, intersections, records, tuples, unions};
+
+// ============================================================================
+// SECTION: Rules
+// ============================================================================
+
+pub struct LintRule {
+    pub name: &'static str,
+    pub code: DiagnosticCode,
+    pub default: LintLevel,
+    pub description: &'static str,
+}
+
+pub const RULES: &[LintRule] = &[
+    LintRule {
+        name: "naming-convention",
+        code: DiagnosticCode::NamingConvention,
+        default: LintLevel::Deny,
+        description: "schemas, enums and type aliases are PascalCase; fields start lowercase",
+    },
+    LintRule {
+        name: "unused-import",
+        code: DiagnosticCode::UnusedImport,
+        default: LintLevel::Deny,
+        description: "an imported name is never used",
+    },
+    LintRule {
+        name: "unused-schema",
+        code: DiagnosticCode::UnusedSchema,
+        default: LintLevel::Warn,
+        description: "a schema, enum or type alias is neither exported nor used in its file",
+    },
+    LintRule {
+        name: "unreachable-branch",
+        code: DiagnosticCode::UnreachableBranch,
+        default: LintLevel::Warn,
+        description: "a `when` or `else` branch can never apply",
+    },
+    LintRule {
+        name: "permissive-any",
+        code: DiagnosticCode::PermissiveAny,
+        default: LintLevel::Warn,
+        description: "a field or type alias accepts `any` value",
+    },
+    LintRule {
+        name: "conflicting-constraints",
+        code: DiagnosticCode::ConflictingConstraints,
+        default: LintLevel::Deny,
+        description: "constraints on one field can never all hold",
+    },
+    LintRule {
+        name: "unsupported-constraint",
+        code: DiagnosticCode::UnsupportedConstraint,
+        default: LintLevel::Warn,
+        description: "the TypeScript output can't express a constraint or type, so it is left out",
+    },
+    LintRule {
+        name: "redundant-constraint",
+        code: DiagnosticCode::RedundantConstraint,
+        default: LintLevel::Warn,
+        description: "a constraint is repeated or implied by another",
+    },
+];
+
+pub fn rule_named(name: &str) -> Option<&'static LintRule> {
+    RULES.iter().find(|rule| rule.name == name)
+}
+
+/// The rule that reports `code`, if `code` is a lint at all
+pub fn rule_for(code: DiagnosticCode) -> Option<&'static LintRule> {
+    RULES.iter().find(|rule| rule.code == code)
+}
+
+/// Lint one file: run every rule, drop findings silenced by `# rel-ignore:`
+/// comments in `source`, then apply the levels set in rel.json
+pub fn lint_file(ast: &[ASTNode], source: &str, config: &ProjectConfig) -> Vec<Diagnostic> {
+    config.apply_lint(suppress(check(ast), source))
+}
+
+/// Every rule's findings, as errors in source order, before levels and
+/// suppressions are applied. Constraints that can't apply or won't compile,
+/// unions of schemas without a usable tag, intersections or utility types
+/// that can't be built, and invalid record keys are reported here too, as
+/// errors no rule can silence.
+pub fn check(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let mut diagnostics = naming_conventions(ast);
+    diagnostics.extend(analyze_imports_exports(ast).err().unwrap_or_default());
+    diagnostics.extend(unused_schemas(ast));
+    diagnostics.extend(unreachable_branches(ast));
+    diagnostics.extend(permissive_any(ast));
+    diagnostics.extend(constraints::check_constraints(ast));
+    diagnostics.extend(unions::check_discriminated_unions(ast));
+    diagnostics.extend(intersections::check_object_types(ast));
+    diagnostics.extend(records::check_records(ast));
+    diagnostics.extend(tuples::check_tuples(ast));
+
+    diagnostics.sort_by_key(|d| d.primary.as_ref().map(|label| (label.span.line, label.span.column)));
+    diagnostics
+}
+
+// ============================================================================
+// SECTION: Suppression
+// ============================================================================
+
+/// Drop diagnostics whose line carries a `# rel-ignore:` for their rule
+pub fn suppress(diagnostics: Vec<Diagnostic>, source: &str) -> Vec<Diagnostic> {
+    let ignored = suppressions(source);
+    diagnostics.into_iter()
+        .filter(|diagnostic| {
+            let Some(rule) = rule_for(diagnostic.code) else { return true };
+            let Some(label) = &diagnostic.primary else { return true };
+            !ignored.get(&label.span.line).is_some_and(|rules| rules.iter().any(|name| name == rule.name))
+        })
+        .collect()
+}
+
+/// Line -> rules silenced on it. A trailing `# rel-ignore: a, b` covers its
+/// own line; one on a line of its own covers the next line of code.
+fn suppressions(source: &str) -> HashMap<usize, Vec<String>> {
+    let pattern = Regex::new(r"#\s*rel-ignore:\s*([A-Za-z0-9_\-, ]+)$").expect("valid pattern");
+    let lines: Vec<&str> = source.lines().collect();
+    let mut ignored: HashMap<usize, Vec<String>> = HashMap::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let Some(captures) = pattern.captures(line.trim_end()) else { continue };
+        let comment_start = captures.get(0).map_or(0, |m| m.start());
+        let rules = captures[1].split(',').map(str::trim).filter(|rule| !rule.is_empty()).map(String::from);
+
+        let target = if line[..comment_start].trim().is_empty() {
+            lines.iter().enumerate().skip(index + 1)
+                .find(|(_, next)| !next.trim().is_empty() && !next.trim_start().starts_with('#'))
+                .map(|(next_index, _)| next_index)
+        } else {
+            Some(index)
+        };
+
+        if let Some(target) = target {
+            ignored.entry(target + 1).or_default().extend(rules);
+        }
+    }
+
+    ignored
+}
+
+// ============================================================================
+// SECTION: naming-convention
+// ============================================================================
+
+fn naming_conventions(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) => {
+                // Check schema naming conventions
+                if !schema.name.chars().next().unwrap_or(' ').is_uppercase() {
+                    errors.push(naming_error(
+                        format!("Schema '{}' should start with uppercase letter", schema.name),
+                        declaration_span(&schema.location, "define", &schema.name),
+                        &schema.location,
+                        to_upper_camel(&schema.name),
+                    ));
+                }
+
+                // Check field naming conventions
+                for field in &schema.fields {
+                    if field.name.chars().next().unwrap_or(' ').is_uppercase() {
+                        errors.push(naming_error(
+                            format!("Field '{}' in schema '{}' should start with lowercase letter", field.name, schema.name),
+                            field_span(field),
+                            &field.location,
+                            to_lower_camel(&field.name),
+                        ));
+                    }
+                }
+            }
+            // Check enum naming conventions
+            ASTNode::Enum(enum_node) if !enum_node.name.chars().next().unwrap_or(' ').is_uppercase() => {
+                errors.push(naming_error(
+                    format!("Enum '{}' should start with uppercase letter", enum_node.name),
+                    declaration_span(&enum_node.location, "enum", &enum_node.name),
+                    &enum_node.location,
+                    to_upper_camel(&enum_node.name),
+                ));
+            }
+            // Check type alias naming conventions
+            ASTNode::TypeAlias(type_alias) if !type_alias.name.chars().next().unwrap_or(' ').is_uppercase() => {
+                errors.push(naming_error(
+                    format!("Type alias '{}' should start with uppercase letter", type_alias.name),
+                    declaration_span(&type_alias.location, "type", &type_alias.name),
+                    &type_alias.location,
+                    to_upper_camel(&type_alias.name),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    errors
+}
+
+fn naming_error(message: String, span: Span, location: &SourceLocation, suggestion: String) -> Diagnostic {
+    let mut diagnostic = Diagnostic::error(DiagnosticCode::NamingConvention, message)
+        .with_span(span)
+        .with_help(format!("rename it to '{}'", suggestion));
+    if let Some(file) = &location.file_path {
+        diagnostic = diagnostic.with_file(file);
+    }
+    diagnostic
+}
+
+fn to_upper_camel(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect()
+}
+
+fn to_lower_camel(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map(|c| c.to_lowercase().chain(chars).collect()).unwrap_or_default()
+}
+
+// ============================================================================
+// SECTION: unused-schema
+// ============================================================================
+
+fn unused_schemas(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let exported: HashSet<&str> = ast.iter()
+        .filter_map(|node| match node {
+            ASTNode::Export(export) if export.from.is_none() => Some(export.items.iter().map(String::as_str)),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+
+    // Declaration -> the names it refers to, so self-references don't count as uses
+    let mut used: HashSet<String> = HashSet::new();
+    for node in ast {
+        let (name, mut references) = match node {
+            ASTNode::Schema(schema) => {
+                let mut references: Vec<String> = schema.extends.iter().chain(&schema.mixins).cloned().collect();
+                fields_type_names(&schema.fields, &mut references);
+                (schema.name.as_str(), references)
+            }
+            ASTNode::Mixin(mixin) => {
+                let mut references = Vec::new();
+                fields_type_names(&mixin.fields, &mut references);
+                (mixin.name.as_str(), references)
+            }
+            ASTNode::TypeAlias(alias) => {
+                let mut references = Vec::new();
+                type_names(&alias.type_definition, &mut references);
+                (alias.name.as_str(), references)
+            }
+            _ => continue,
+        };
+        references.retain(|reference| reference != name);
+        used.extend(references);
+    }
+
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        let (kind, keyword, name, location) = match node {
+            ASTNode::Schema(schema) => ("Schema", "define", &schema.name, &schema.location),
+            ASTNode::Enum(enum_node) => ("Enum", "enum", &enum_node.name, &enum_node.location),
+            ASTNode::TypeAlias(alias) => ("Type alias", "type", &alias.name, &alias.location),
+            _ => continue,
+        };
+        if exported.contains(name.as_str()) || used.contains(name) {
+            continue;
+        }
+        diagnostics.push(
+            Diagnostic::error(DiagnosticCode::UnusedSchema, format!("{} '{}' is never used or exported", kind, name))
+                .with_label(declaration_span(location, keyword, name), "declared here")
+                .with_help(format!("export it with `export {}`, use it in another schema, or remove it", name))
+        );
+    }
+    diagnostics
+}
+
+// ============================================================================
+// SECTION: unreachable-branch
+// ============================================================================
+
+fn unreachable_branches(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    // Enum name -> its values
+    let enums: HashMap<&str, Vec<String>> = ast.iter()
+        .filter_map(|node| match node {
+            ASTNode::Enum(enum_node) => Some((enum_node.name.as_str(), enum_node.values.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        let ASTNode::Schema(schema) = node else { continue };
+
+        // Field -> the only values it can hold, for literal unions and enums
+        let values: HashMap<&str, Vec<String>> = schema.fields.iter()
+            .filter_map(|field| {
+                let values = match &field.field_type {
+                    TypeNode::Union(members) => members.iter()
+                        .map(|member| match member {
+                            TypeNode::Identifier(value) if value.starts_with(|c: char| c.is_lowercase()) => Some(value.clone()),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<String>>>()?,
+                    TypeNode::Identifier(name) => enums.get(name.as_str())?.clone(),
+                    _ => return None,
+                };
+                Some((field.name.as_str(), values))
+            })
+            .collect();
+
+        for conditional in field_conditionals(&schema.fields) {
+            check_chain(conditional, &values, &mut diagnostics);
+        }
+    }
+    diagnostics
+}
+
+/// Every `when` among `fields`, including those nested inside other branches;
+/// `else when` continuations are left to `check_chain`
+fn field_conditionals(fields: &[FieldNode]) -> Vec<&ConditionalNode> {
+    let mut conditionals = Vec::new();
+    for field in fields {
+        let own = field.conditionals.iter().chain(match &field.field_type {
+            TypeNode::Conditional(conditional) => Some(conditional.as_ref()),
+            _ => None,
+        });
+        for conditional in own {
+            conditionals.push(conditional);
+            let mut link = Some(conditional);
+            while let Some(current) = link {
+                conditionals.extend(field_conditionals(&current.then_fields));
+                conditionals.extend(field_conditionals(&current.else_fields));
+                link = match &current.else_value {
+                    Some(TypeNode::Conditional(next)) => Some(next.as_ref()),
+                    _ => None,
+                };
+            }
+        }
+    }
+    conditionals
+}
+
+/// Report branches of one `when ... else when ... else` chain that never apply
+fn check_chain(first: &ConditionalNode, values: &HashMap<&str, Vec<String>>, diagnostics: &mut Vec<Diagnostic>) {
+    let unreachable = |location: &SourceLocation, message: String, label: &str, help: String| {
+        Diagnostic::error(DiagnosticCode::UnreachableBranch, message)
+            .with_location(location, label)
+            .with_help(help)
+    };
+
+    let mut earlier: Vec<&ExpressionNode> = Vec::new();
+    let mut link = Some(first);
+    while let Some(conditional) = link {
+        let next = match &conditional.else_value {
+            Some(TypeNode::Conditional(next)) => Some(next.as_ref()),
+            _ => None,
+        };
+
+        if earlier.contains(&&conditional.condition) {
+            diagnostics.push(unreachable(
+                &conditional.location,
+                "This `when` branch can never apply".to_string(),
+                "repeats an earlier condition of the same chain",
+                "an earlier branch already handles this condition; remove this branch".to_string(),
+            ));
+        } else {
+            match evaluate(&conditional.condition, values) {
+                Some(false) => diagnostics.push(unreachable(
+                    &conditional.location,
+                    "This `when` branch can never apply".to_string(),
+                    "this condition is never true",
+                    never_true_help(&conditional.condition, values),
+                )),
+                Some(true) if conditional.else_value.is_some() => {
+                    diagnostics.push(unreachable(
+                        &conditional.location,
+                        "The `else` branch of this `when` can never apply".to_string(),
+                        "this condition is always true",
+                        "remove the `else` branch, or the condition".to_string(),
+                    ));
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        earlier.push(&conditional.condition);
+        link = next;
+    }
+}
+
+/// The value a condition always has, when it can be known without data
+fn evaluate(condition: &ExpressionNode, values: &HashMap<&str, Vec<String>>) -> Option<bool> {
+    match condition {
+        ExpressionNode::Boolean(value) => Some(*value),
+        ExpressionNode::Group(inner) => evaluate(inner, values),
+        ExpressionNode::UnaryOp { operator: UnaryOperator::Not, operand } => evaluate(operand, values).map(|value| !value),
+        ExpressionNode::BinaryOp { left, operator, right } => match operator {
+            BinaryOperator::And => match (evaluate(left, values), evaluate(right, values)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            BinaryOperator::Or => match (evaluate(left, values), evaluate(right, values)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            // Comparing a field to a value it can never hold
+            BinaryOperator::Equals | BinaryOperator::NotEquals => {
+                impossible_value(left, right, values)?;
+                Some(*operator == BinaryOperator::NotEquals)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// For `field = value` where `field` can never hold `value`: its values and `value`
+fn impossible_value<'a>(left: &ExpressionNode, right: &'a ExpressionNode, values: &'a HashMap<&str, Vec<String>>) -> Option<(&'a [String], &'a str)> {
+    let field = match left {
+        ExpressionNode::Identifier(name) => name.as_str(),
+        ExpressionNode::FieldAccess(path) if path.len() == 1 => path[0].as_str(),
+        _ => return None,
+    };
+    let value = match right {
+        ExpressionNode::Identifier(value) | ExpressionNode::String(value) => value.as_str(),
+        _ => return None,
+    };
+    let possible = values.get(field)?;
+    (!possible.iter().any(|v| v == value)).then_some((possible.as_slice(), value))
+}
+
+fn never_true_help(condition: &ExpressionNode, values: &HashMap<&str, Vec<String>>) -> String {
+    if let ExpressionNode::BinaryOp { left, right, .. } = condition {
+        if let Some((possible, value)) = impossible_value(left, right, values) {
+            return format!("'{}' is not one of {}", value, possible.join(", "));
+        }
+    }
+    "remove the branch, or fix its condition".to_string()
+}
+
+// ============================================================================
+// SECTION: permissive-any
+// ============================================================================
+
+fn permissive_any(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    fn check_fields(fields: &[FieldNode], diagnostics: &mut Vec<Diagnostic>) {
+        for field in fields {
+            if accepts_any(&field.field_type) {
+                diagnostics.push(any_diagnostic(format!("Field '{}' accepts any value", field.name), field_span(field)));
+            }
+            match &field.field_type {
+                TypeNode::InlineObject(nested) => check_fields(nested, diagnostics),
+                TypeNode::Array(inner) | TypeNode::Record { value: inner, .. } => {
+                    if let TypeNode::InlineObject(nested) = inner.as_ref() {
+                        check_fields(nested, diagnostics);
+                    }
+                }
+                TypeNode::Tuple(elements) => {
+                    for element in elements {
+                        if let TypeNode::InlineObject(nested) = &element.element_type {
+                            check_fields(nested, diagnostics);
+                        }
+                    }
+                }
+                TypeNode::Intersection(members) => {
+                    for member in members {
+                        if let TypeNode::InlineObject(nested) = member {
+                            check_fields(nested, diagnostics);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            for conditional in &field.conditionals {
+                check_fields(&conditional.then_fields, diagnostics);
+                check_fields(&conditional.else_fields, diagnostics);
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) => check_fields(&schema.fields, &mut diagnostics),
+            ASTNode::Mixin(mixin) => check_fields(&mixin.fields, &mut diagnostics),
+            ASTNode::TypeAlias(alias) if accepts_any(&alias.type_definition) => diagnostics.push(any_diagnostic(
+                format!("Type alias '{}' accepts any value", alias.name),
+                declaration_span(&alias.location, "type", &alias.name),
+            )),
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+/// Whether `any` appears in the type itself rather than in a nested object's fields
+fn accepts_any(type_node: &TypeNode) -> bool {
+    match type_node {
+        TypeNode::Any => true,
+        TypeNode::Array(inner) => accepts_any(inner),
+        TypeNode::Union(types) => types.iter().any(accepts_any),
+        TypeNode::Generic(_, args) => args.iter().any(accepts_any),
+        TypeNode::Record { value, .. } => accepts_any(value),
+        TypeNode::Tuple(elements) => elements.iter().any(|element| accepts_any(&element.element_type)),
+        TypeNode::Constrained { base_type, .. } => accepts_any(base_type),
+        _ => false,
+    }
+}
+
+fn any_diagnostic(message: String, span: Span) -> Diagnostic {
+    Diagnostic::error(DiagnosticCode::PermissiveAny, message)
+        .with_label(span, "`any` turns off validation here")
+        .with_help("use a specific type, a union, or an inline object `{ ... }`")
+}
+
+// ============================================================================
+// SECTION: Helpers
+// ============================================================================
+
+/// Span from a declaration keyword through its name (`define user`)
+fn declaration_span(location: &SourceLocation, keyword: &str, name: &str) -> Span {
+    Span::new(location.line, location.column, keyword.len() + 1 + name.chars().count())
+}
+
+fn field_span(field: &FieldNode) -> Span {
+    Span::new(field.location.line, field.location.column, field.name.chars().count())
+}
+
+fn fields_type_names(fields: &[FieldNode], names: &mut Vec<String>) {
+    for field in fields {
+        type_names(&field.field_type, names);
+        for conditional in &field.conditionals {
+            fields_type_names(&conditional.then_fields, names);
+            fields_type_names(&conditional.else_fields, names);
+        }
+    }
+}
+
+/// Every type name a type refers to, nested fields and branches included
+fn type_names(type_node: &TypeNode, names: &mut Vec<String>) {
+    match type_node {
+        TypeNode::Identifier(name) => names.push(name.clone()),
+        TypeNode::Generic(name, args) => {
+            names.push(name.clone());
+            args.iter().for_each(|arg| type_names(arg, names));
+        }
+        TypeNode::Array(inner) => type_names(inner, names),
+        TypeNode::Record { key, value } => {
+            type_names(key, names);
+            type_names(value, names);
+        }
+        TypeNode::Tuple(elements) => elements.iter().for_each(|element| type_names(&element.element_type, names)),
+        TypeNode::Union(types) | TypeNode::Intersection(types) => types.iter().for_each(|t| type_names(t, names)),
+        TypeNode::Constrained { base_type, .. } => type_names(base_type, names),
+        TypeNode::Conditional(conditional) => {
+            fields_type_names(&conditional.then_fields, names);
+            fields_type_names(&conditional.else_fields, names);
+            if let Some(else_value) = &conditional.else_value {
+                type_names(else_value, names);
+            }
+        }
+        TypeNode::InlineObject(fields) => fields_type_names(fields, names),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn lint(source: &str) -> Vec<(&'static str, usize)> {
+        let tokens = Lexer::new(source).tokenize().expect("tokenize");
+        let ast = Parser::new(tokens).parse().expect("parse");
+        suppress(check(&ast), source).iter()
+            .map(|d| (rule_for(d.code).unwrap().name, d.primary.as_ref().unwrap().span.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_rules_report_and_rel_ignore_suppresses() {
+        let source = r#"
+define Helper {
+  x: string
+}
+
+define Account {
+  kind: admin | member
+  age: number & min(200) & max(13)
+  when kind = guest {
+    token: string
+  }
+  meta: any  # rel-ignore: permissive-any
+  # rel-ignore: permissive-any, naming-convention
+  Extra: any
+  data: any
+}
+
+export Account
+"#;
+        assert_eq!(lint(source), vec![
+            ("unused-schema", 2),
+            ("conflicting-constraints", 8),
+            ("unreachable-branch", 9),
+            ("permissive-any", 15),
+        ]);
+    }
+}
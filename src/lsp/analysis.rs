@@ -0,0 +1,657 @@
+/*!
+ * LSP Analysis - Per-document facts used by the language server
+ *
+ * Symbols, imports and references come from the token stream, which is
+ * available even while the file has parse errors and carries positions
+ * for every name. Hover text is built from the parsed AST.
+ */
+
+use serde_json::{json, Value};
+
+use crate::ast::*;
+use crate::context::CompilationContext;
+use crate::diagnostics::{self, DiagnosticCode};
+use crate::generator::TypeGeneration;
+use crate::lint;
+use crate::lexer::{Lexer, Token, TokenType, BUILTIN_TYPES, CONSTRAINT_FUNCTIONS};
+use crate::parser::Parser;
+use crate::printer::Printer;
+use crate::validation;
+
+const KEYWORDS: &[&str] = &[
+    "define", "enum", "type", "import", "export", "from", "as", "mixin", "extends",
+    "with", "when", "else", "let", "declare", "validate", "print",
+];
+
+// ============================================================================
+// SECTION: Positions
+// ============================================================================
+
+/// Zero-based line and character, as used by LSP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+impl Position {
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            line: value.get("line")?.as_u64()? as usize,
+            character: value.get("character")?.as_u64()? as usize,
+        })
+    }
+
+    pub fn to_json(self) -> Value {
+        json!({ "line": self.line, "character": self.character })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    /// Span covering a token (lexer lines and columns are one-based)
+    fn of_token(token: &Token) -> Self {
+        let width = match token.token_type {
+            TokenType::String => token.value.chars().count() + 2,
+            TokenType::RawString => token.value.chars().count() + 3,
+            _ => token.value.chars().count().max(1),
+        };
+        let start = Position {
+            line: token.line.saturating_sub(1),
+            character: token.column.saturating_sub(1),
+        };
+        Self { start, end: Position { line: start.line, character: start.character + width } }
+    }
+
+    /// A single character at a one-based line and column
+    fn at(line: usize, column: usize) -> Self {
+        let start = Position { line: line.saturating_sub(1), character: column.saturating_sub(1) };
+        Self { start, end: Position { line: start.line, character: start.character + 1 } }
+    }
+
+    /// The end is inclusive so a cursor right after a name still hits it
+    pub fn contains(&self, position: Position) -> bool {
+        self.start <= position && position <= self.end
+    }
+
+    pub fn to_json(self) -> Value {
+        json!({ "start": self.start.to_json(), "end": self.end.to_json() })
+    }
+}
+
+// ============================================================================
+// SECTION: Symbols & Diagnostics
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Schema,
+    Enum,
+    TypeAlias,
+    Mixin,
+    Function,
+    Variable,
+}
+
+impl SymbolKind {
+    /// LSP `CompletionItemKind`
+    fn completion_kind(self) -> u8 {
+        match self {
+            SymbolKind::Schema => 22,    // Struct
+            SymbolKind::Enum => 13,      // Enum
+            SymbolKind::TypeAlias => 25, // TypeParameter
+            SymbolKind::Mixin => 8,      // Interface
+            SymbolKind::Function => 3,   // Function
+            SymbolKind::Variable => 6,   // Variable
+        }
+    }
+}
+
+/// A top-level declaration and the span of its name
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error = 1,
+    Warning = 2,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(span: Span, code: DiagnosticCode, message: String) -> Self {
+        Self { span, severity: Severity::Error, code, message }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "range": self.span.to_json(),
+            "severity": self.severity as u8,
+            "code": self.code.as_str(),
+            "source": "rel",
+            "message": self.message,
+        })
+    }
+}
+
+/// An `import { ... } from "path"` statement read from tokens
+#[derive(Debug, Clone)]
+pub struct ImportSite {
+    pub path: String,
+    pub path_span: Span,
+    /// Names as the module exports them
+    pub items: Vec<(String, Span)>,
+    /// The name each item goes by in this file, `B` for `A as B`
+    pub locals: Vec<String>,
+}
+
+impl ImportSite {
+    /// The exported name behind `local`, if this import brings it in
+    pub fn exported_name(&self, local: &str) -> Option<&str> {
+        let index = self.locals.iter().position(|name| name == local)?;
+        Some(self.items[index].0.as_str())
+    }
+
+    /// The local name for `exported`, if this import brings it in
+    pub fn local_name(&self, exported: &str) -> Option<&str> {
+        let index = self.items.iter().position(|(name, _)| name == exported)?;
+        Some(self.locals[index].as_str())
+    }
+}
+
+// ============================================================================
+// SECTION: Document Analysis
+// ============================================================================
+
+pub struct Analysis {
+    /// Empty when lexing failed
+    pub tokens: Vec<Token>,
+    /// Present only when the document parsed
+    pub ast: Option<Vec<ASTNode>>,
+    pub symbols: Vec<Symbol>,
+    pub imports: Vec<ImportSite>,
+    /// Lexer, parser, semantic and lint diagnostics (imports are checked by the server)
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Analysis {
+    pub fn new(text: &str) -> Self {
+        let mut analysis = Self {
+            tokens: Vec::new(),
+            ast: None,
+            symbols: Vec::new(),
+            imports: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+
+        match Lexer::new(text).tokenize() {
+            Ok(tokens) => analysis.tokens = tokens,
+            Err(errors) => {
+                for error in errors {
+                    analysis.diagnostics.push(Diagnostic::error(Span::at(error.line, error.column), error.code, error.message));
+                }
+                return analysis;
+            }
+        }
+
+        analysis.symbols = collect_symbols(&analysis.tokens);
+        analysis.imports = collect_imports(&analysis.tokens);
+
+        match Parser::new(analysis.tokens.clone()).parse() {
+            Ok(ast) => {
+                analysis.check_semantics(&ast, text);
+                analysis.ast = Some(ast);
+            }
+            Err(errors) => {
+                for error in errors {
+                    let span = analysis.span_at(error.line, error.column);
+                    analysis.diagnostics.push(Diagnostic::error(span, DiagnosticCode::SyntaxError, error.message));
+                }
+            }
+        }
+
+        analysis
+    }
+
+    fn check_semantics(&mut self, ast: &[ASTNode], text: &str) {
+        for error in validation::perform_semantic_validation(ast).err().unwrap_or_default() {
+            let diagnostic = self.convert(error, Severity::Error);
+            self.diagnostics.push(diagnostic);
+        }
+
+        // Lints may fail a build but are only style hints while editing;
+        // constraint errors no rule covers stay errors
+        for error in lint::suppress(lint::check(ast), text) {
+            let severity = if lint::rule_for(error.code).is_some() { Severity::Warning } else { Severity::Error };
+            let diagnostic = self.convert(error, severity);
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Place a compiler diagnostic on this document's tokens
+    fn convert(&self, diagnostic: diagnostics::Diagnostic, severity: Severity) -> Diagnostic {
+        let span = match &diagnostic.primary {
+            Some(label) if label.span.length > 0 => {
+                let start = Position { line: label.span.line - 1, character: label.span.column - 1 };
+                Span { start, end: Position { line: start.line, character: start.character + label.span.length } }
+            }
+            Some(label) => self.span_at(label.span.line, label.span.column),
+            None => self.span_of_quoted(&diagnostic.message),
+        };
+        Diagnostic { span, severity, code: diagnostic.code, message: diagnostic.message }
+    }
+
+    /// Span of the token starting at a one-based line and column
+    fn span_at(&self, line: usize, column: usize) -> Span {
+        self.tokens.iter()
+            .find(|t| t.line == line && t.column == column)
+            .map(Span::of_token)
+            .unwrap_or_else(|| Span::at(line, column))
+    }
+
+    /// Errors without a position name their subject in quotes (`Undefined type 'Foo'`);
+    /// point at the first occurrence of that name
+    fn span_of_quoted(&self, message: &str) -> Span {
+        message.split('\'')
+            .nth(1)
+            .and_then(|name| self.tokens.iter().find(|t| is_name_token(t) && t.value == name))
+            .map(Span::of_token)
+            .unwrap_or_else(|| Span::at(1, 1))
+    }
+
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+
+    /// Names listed in `export` statements, including `export { A } from`
+    pub fn exports(&self) -> Vec<String> {
+        let mut exports = Vec::new();
+        let mut in_export = false;
+        for token in &self.tokens {
+            match token.token_type {
+                TokenType::Export => in_export = true,
+                TokenType::Comma | TokenType::LBrace | TokenType::RBrace if in_export => {}
+                TokenType::Identifier | TokenType::TypeName if in_export => exports.push(token.value.clone()),
+                _ => in_export = false,
+            }
+        }
+        exports
+    }
+
+    /// Whether the file has an `export * from`, whose names only its target knows
+    pub fn exports_all(&self) -> bool {
+        self.tokens.windows(2).any(|pair| pair[0].token_type == TokenType::Export && pair[1].token_type == TokenType::Multiply)
+    }
+
+    /// Name token under the cursor
+    pub fn name_at(&self, position: Position) -> Option<&Token> {
+        self.tokens.iter()
+            .filter(|t| is_name_token(t) || t.token_type == TokenType::Constraint)
+            .find(|t| Span::of_token(t).contains(position))
+    }
+
+    /// The token following `token`, used to tell calls from references
+    pub fn next_token(&self, token: &Token) -> Option<&Token> {
+        let index = self.tokens.iter().position(|t| t.position == token.position)?;
+        self.tokens.get(index + 1)
+    }
+
+    /// Every place `name` appears as an identifier
+    pub fn occurrences(&self, name: &str) -> Vec<Span> {
+        self.tokens.iter()
+            .filter(|t| t.token_type == TokenType::Identifier && t.value == name)
+            .map(Span::of_token)
+            .collect()
+    }
+
+    // ========================================================================
+    // SECTION: Hover
+    // ========================================================================
+
+    /// Markdown for the declaration of `name` in this document. For `@fn`
+    /// declarations, `call_arguments` expands the body for one call site.
+    pub fn hover(&self, name: &str, call_arguments: Option<&[ExpressionNode]>) -> Option<String> {
+        let ast = self.ast.as_ref()?;
+        let mut printer = Printer::new();
+
+        for node in ast {
+            let text = match node {
+                ASTNode::TypeAlias(alias) if alias.name == name => {
+                    self.describe_alias("type", name, &alias.type_definition, &mut printer)
+                }
+                ASTNode::DeclareType(decl) if decl.name == name => {
+                    self.describe_alias("declare type", name, &decl.type_def, &mut printer)
+                }
+                ASTNode::Function(func) if func.name == name => {
+                    let mut text = code_block(&printer.print_node(node));
+                    if let Some(expanded) = call_arguments.and_then(|args| expand_call(func, args)) {
+                        let expanded = self.expand_type(&expanded, &mut Vec::new());
+                        text.push_str(&format!("\n\nExpands to:\n{}", code_block(&printer.print_type(&expanded))));
+                    }
+                    text
+                }
+                ASTNode::Schema(SchemaNode { name: n, .. })
+                | ASTNode::Enum(EnumNode { name: n, .. })
+                | ASTNode::Mixin(MixinNode { name: n, .. })
+                | ASTNode::Variable(VariableNode { name: n, .. })
+                | ASTNode::DeclareVar(DeclareVarNode { name: n, .. }) if n == name => {
+                    code_block(&printer.print_node(node))
+                }
+                _ => continue,
+            };
+            return Some(text);
+        }
+
+        None
+    }
+
+    fn describe_alias(&self, keyword: &str, name: &str, definition: &TypeNode, printer: &mut Printer) -> String {
+        let mut text = code_block(&format!("{} {} = {}", keyword, name, printer.print_type(definition)));
+        let expanded = self.expand_type(definition, &mut vec![name.to_string()]);
+        if &expanded != definition {
+            text.push_str(&format!("\n\nExpands to:\n{}", code_block(&printer.print_type(&expanded))));
+        }
+        text
+    }
+
+    /// Replace alias references and `@fn` calls with what they stand for
+    fn expand_type(&self, type_node: &TypeNode, expanding: &mut Vec<String>) -> TypeNode {
+        match type_node {
+            TypeNode::Identifier(name) if !expanding.contains(name) => {
+                match self.alias_definition(name) {
+                    Some(definition) => {
+                        expanding.push(name.clone());
+                        let expanded = self.expand_type(&definition, expanding);
+                        expanding.pop();
+                        expanded
+                    }
+                    None => type_node.clone(),
+                }
+            }
+            TypeNode::FunctionCall { name, arguments } if !expanding.contains(name) => {
+                match self.function(name).and_then(|func| expand_call(func, arguments)) {
+                    Some(body) => {
+                        expanding.push(name.clone());
+                        let expanded = self.expand_type(&body, expanding);
+                        expanding.pop();
+                        expanded
+                    }
+                    None => type_node.clone(),
+                }
+            }
+            TypeNode::Array(inner) => TypeNode::Array(Box::new(self.expand_type(inner, expanding))),
+            TypeNode::Union(members) => {
+                TypeNode::Union(members.iter().map(|m| self.expand_type(m, expanding)).collect())
+            }
+            TypeNode::Intersection(members) => {
+                TypeNode::Intersection(members.iter().map(|m| self.expand_type(m, expanding)).collect())
+            }
+            TypeNode::Generic(name, args) => {
+                TypeNode::Generic(name.clone(), args.iter().map(|a| self.expand_type(a, expanding)).collect())
+            }
+            TypeNode::Record { key, value } => TypeNode::Record {
+                key: Box::new(self.expand_type(key, expanding)),
+                value: Box::new(self.expand_type(value, expanding)),
+            },
+            TypeNode::Tuple(elements) => TypeNode::Tuple(
+                elements.iter()
+                    .map(|element| TupleElement { element_type: self.expand_type(&element.element_type, expanding), ..element.clone() })
+                    .collect(),
+            ),
+            TypeNode::Constrained { base_type, constraints } => {
+                // `Id & maxLength(5)` where `Id = string & minLength(1)` flattens into one chain
+                match self.expand_type(base_type, expanding) {
+                    TypeNode::Constrained { base_type, constraints: inner } => TypeNode::Constrained {
+                        base_type,
+                        constraints: inner.into_iter().chain(constraints.iter().cloned()).collect(),
+                    },
+                    base => TypeNode::Constrained { base_type: Box::new(base), constraints: constraints.clone() },
+                }
+            }
+            _ => type_node.clone(),
+        }
+    }
+
+    fn alias_definition(&self, name: &str) -> Option<TypeNode> {
+        self.ast.as_ref()?.iter().find_map(|node| match node {
+            ASTNode::TypeAlias(alias) if alias.name == name => Some(alias.type_definition.clone()),
+            ASTNode::DeclareType(decl) if decl.name == name => Some(decl.type_def.clone()),
+            _ => None,
+        })
+    }
+
+    fn function(&self, name: &str) -> Option<&FunctionNode> {
+        self.ast.as_ref()?.iter().find_map(|node| match node {
+            ASTNode::Function(func) if func.name == name => Some(func),
+            _ => None,
+        })
+    }
+
+    /// Arguments of the `name(...)` call in the field declared on a one-based line
+    pub fn call_arguments(&self, name: &str, line: usize) -> Option<Vec<ExpressionNode>> {
+        let ast = self.ast.as_ref()?;
+        ast.iter().find_map(|node| match node {
+            ASTNode::Schema(schema) => find_call_in_fields(&schema.fields, name, line),
+            ASTNode::Mixin(mixin) => find_call_in_fields(&mixin.fields, name, line),
+            _ => None,
+        })
+    }
+
+    // ========================================================================
+    // SECTION: Completion
+    // ========================================================================
+
+    /// Completion items for a cursor whose line reads `line_prefix` so far
+    pub fn completions(&self, line_prefix: &str, imported: &[String]) -> Vec<Value> {
+        let before_word = line_prefix
+            .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+            .trim_end();
+
+        // Only constraints can follow `&`
+        if before_word.ends_with('&') {
+            return CONSTRAINT_FUNCTIONS.iter()
+                .map(|name| json!({ "label": name, "kind": 3, "detail": "constraint" }))
+                .collect();
+        }
+
+        let mut items: Vec<Value> = BUILTIN_TYPES.iter()
+            .map(|name| json!({ "label": name, "kind": 25, "detail": "built-in type" }))
+            .collect();
+
+        for symbol in &self.symbols {
+            items.push(json!({
+                "label": symbol.name,
+                "kind": symbol.kind.completion_kind(),
+                "detail": format!("{:?}", symbol.kind).to_lowercase(),
+            }));
+        }
+        for name in imported {
+            items.push(json!({ "label": name, "kind": 7, "detail": "imported" }));
+        }
+        for keyword in KEYWORDS {
+            items.push(json!({ "label": keyword, "kind": 14 }));
+        }
+
+        items
+    }
+}
+
+// ============================================================================
+// SECTION: Helpers
+// ============================================================================
+
+fn is_name_token(token: &Token) -> bool {
+    matches!(token.token_type, TokenType::Identifier | TokenType::TypeName)
+}
+
+fn code_block(source: &str) -> String {
+    format!("```rel\n{}\n```", source.trim_end())
+}
+
+/// Declarations are a keyword followed by the declared name
+fn collect_symbols(tokens: &[Token]) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        let kind = match token.token_type {
+            TokenType::Define => SymbolKind::Schema,
+            TokenType::Enum => SymbolKind::Enum,
+            // Also covers `declare type`; `-> type {` is followed by a brace, not a name
+            TokenType::Type => SymbolKind::TypeAlias,
+            TokenType::Mixin => SymbolKind::Mixin,
+            TokenType::Fn => SymbolKind::Function,
+            TokenType::Let | TokenType::Var => SymbolKind::Variable,
+            _ => continue,
+        };
+
+        if let Some(name) = tokens.get(index + 1).filter(|t| is_name_token(t)) {
+            symbols.push(Symbol { name: name.value.clone(), kind, span: Span::of_token(name) });
+        }
+    }
+
+    symbols
+}
+
+/// `import { A, B as C } from "path"`, tolerating errors elsewhere in the file
+fn collect_imports(tokens: &[Token]) -> Vec<ImportSite> {
+    let mut imports = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.token_type != TokenType::Import {
+            continue;
+        }
+
+        let mut items = Vec::new();
+        let mut locals: Vec<String> = Vec::new();
+        let mut cursor = index + 1;
+        while let Some(t) = tokens.get(cursor) {
+            match t.token_type {
+                TokenType::LBrace | TokenType::Comma => {}
+                // `A as B`: the module exports A; B is only a local name
+                TokenType::As => {
+                    let local = tokens.get(cursor + 1).filter(|t| is_name_token(t));
+                    if let (Some(local), Some(last)) = (local, locals.last_mut()) {
+                        *last = local.value.clone();
+                    }
+                    cursor += 1;
+                }
+                TokenType::Identifier | TokenType::TypeName => {
+                    items.push((t.value.clone(), Span::of_token(t)));
+                    locals.push(t.value.clone());
+                }
+                _ => break,
+            }
+            cursor += 1;
+        }
+
+        let is_from = |t: &Token| t.token_type == TokenType::From;
+        if tokens.get(cursor).is_some_and(|t| t.token_type == TokenType::RBrace)
+            && tokens.get(cursor + 1).is_some_and(is_from)
+        {
+            if let Some(path) = tokens.get(cursor + 2).filter(|t| t.token_type == TokenType::String) {
+                imports.push(ImportSite {
+                    path: path.value.clone(),
+                    path_span: Span::of_token(path),
+                    items,
+                    locals,
+                });
+            }
+        }
+    }
+
+    imports
+}
+
+fn find_call_in_fields(fields: &[FieldNode], name: &str, line: usize) -> Option<Vec<ExpressionNode>> {
+    fields.iter().find_map(|field| {
+        if field.location.line == line {
+            if let Some(arguments) = find_call_in_type(&field.field_type, name) {
+                return Some(arguments.clone());
+            }
+        }
+        match &field.field_type {
+            TypeNode::InlineObject(inner) => find_call_in_fields(inner, name, line),
+            TypeNode::Conditional(conditional) => find_call_in_fields(&conditional.then_fields, name, line)
+                .or_else(|| find_call_in_fields(&conditional.else_fields, name, line)),
+            _ => None,
+        }
+    })
+}
+
+fn find_call_in_type<'a>(type_node: &'a TypeNode, name: &str) -> Option<&'a Vec<ExpressionNode>> {
+    match type_node {
+        TypeNode::FunctionCall { name: called, arguments } if called == name => Some(arguments),
+        TypeNode::Array(inner) => find_call_in_type(inner, name),
+        TypeNode::Union(members) | TypeNode::Intersection(members) | TypeNode::Generic(_, members) => {
+            members.iter().find_map(|member| find_call_in_type(member, name))
+        }
+        TypeNode::Record { key, value } => find_call_in_type(key, name).or_else(|| find_call_in_type(value, name)),
+        TypeNode::Tuple(elements) => elements.iter().find_map(|element| find_call_in_type(&element.element_type, name)),
+        TypeNode::Constrained { base_type, .. } => find_call_in_type(base_type, name),
+        _ => None,
+    }
+}
+
+/// The body type of `@fn` with parameters bound to the call's arguments,
+/// mirroring how the generator expands calls
+fn expand_call(func: &FunctionNode, arguments: &[ExpressionNode]) -> Option<TypeNode> {
+    let body = func.body_type.as_ref()?;
+    let mut context = CompilationContext::new();
+
+    for (param, argument) in func.params.iter().zip(arguments) {
+        context.add_variable(param.name.clone(), argument.clone());
+    }
+    for statement in &func.body_statements {
+        if let ASTNode::DeclareVar(var) = statement {
+            let value = TypeGeneration::evaluate_expression_in_context(&var.value, &context);
+            context.add_variable(var.name.clone(), value);
+        }
+    }
+
+    Some(bind_constraints(body, &context))
+}
+
+fn bind_constraints(type_node: &TypeNode, context: &CompilationContext) -> TypeNode {
+    match type_node {
+        TypeNode::Constrained { base_type, constraints } => TypeNode::Constrained {
+            base_type: Box::new(bind_constraints(base_type, context)),
+            constraints: constraints.iter()
+                .map(|constraint| ConstraintNode {
+                    constraint_type: constraint.constraint_type.clone(),
+                    value: constraint.value.as_ref()
+                        .map(|value| TypeGeneration::evaluate_expression_in_context(value, context)),
+                    location: constraint.location.clone(),
+                })
+                .collect(),
+        },
+        TypeNode::Array(inner) => TypeNode::Array(Box::new(bind_constraints(inner, context))),
+        TypeNode::Union(members) => TypeNode::Union(members.iter().map(|m| bind_constraints(m, context)).collect()),
+        TypeNode::Intersection(members) => TypeNode::Intersection(members.iter().map(|m| bind_constraints(m, context)).collect()),
+        TypeNode::Record { key, value } => TypeNode::Record {
+            key: Box::new(bind_constraints(key, context)),
+            value: Box::new(bind_constraints(value, context)),
+        },
+        TypeNode::Tuple(elements) => TypeNode::Tuple(
+            elements.iter()
+                .map(|element| TupleElement { element_type: bind_constraints(&element.element_type, context), ..element.clone() })
+                .collect(),
+        ),
+        _ => type_node.clone(),
+    }
+}
@@ -0,0 +1,552 @@
+/*!
+ * LSP Module
+ *
+ * `rel lsp` - a Language Server Protocol server over stdio. Provides live
+ * diagnostics, go-to-definition across imports, hover, completion and
+ * find-references for .rel documents.
+ */
+
+pub mod analysis;
+pub mod transport;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::compiler;
+use crate::config::ProjectConfig;
+use crate::diagnostics::{DiagnosticCode, DiagnosticsFormat};
+use crate::lexer::TokenType;
+use crate::resolver::ModuleResolver;
+use analysis::{Analysis, Diagnostic, Position, Symbol};
+
+/// Serve LSP requests on stdin/stdout until the client sends `exit`
+pub fn run_server() -> Result<(), Box<dyn std::error::Error>> {
+    let mut reader = io::stdin().lock();
+    let mut writer = io::stdout().lock();
+    let mut server = LanguageServer::new();
+
+    while let Some(message) = transport::read_message(&mut reader)? {
+        for reply in server.handle(&message) {
+            transport::write_message(&mut writer, &reply)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Where a symbol is declared
+struct Resolved {
+    path: PathBuf,
+    analysis: Analysis,
+    symbol: Symbol,
+}
+
+pub struct LanguageServer {
+    /// Open documents by canonical path; closed files are read from disk
+    documents: HashMap<PathBuf, String>,
+    /// Workspace root, searched for references
+    root: Option<PathBuf>,
+    exited: bool,
+}
+
+impl LanguageServer {
+    pub fn new() -> Self {
+        Self {
+            documents: HashMap::new(),
+            root: None,
+            exited: false,
+        }
+    }
+
+    /// Handle one client message, returning responses and notifications to send
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => vec![self.handle_request(method, id.clone(), &params)],
+            (Some(method), None) => self.handle_notification(method, &params),
+            // Responses to server-initiated requests are not used
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, id: Value, params: &Value) -> Value {
+        let result = match method {
+            "initialize" => self.initialize(params),
+            "shutdown" => Value::Null,
+            "textDocument/definition" => self.definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/completion" => self.completion(params),
+            "textDocument/references" => self.references(params),
+            _ => {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+                });
+            }
+        };
+
+        json!({ "jsonrpc": "2.0", "id": id, "result": result })
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let document = params.get("textDocument");
+        let path = document
+            .and_then(|d| d.get("uri"))
+            .and_then(Value::as_str)
+            .and_then(uri_to_path)
+            .map(|path| canonical(&path));
+
+        match (method, path) {
+            ("exit", _) => {
+                self.exited = true;
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(path)) => {
+                let text = document.and_then(|d| d.get("text")).and_then(Value::as_str).unwrap_or_default();
+                self.documents.insert(path, text.to_string());
+                self.publish_all()
+            }
+            ("textDocument/didChange", Some(path)) => {
+                // Full document sync: the last change holds the whole text
+                let text = params.get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let Some(text) = text {
+                    self.documents.insert(path, text.to_string());
+                }
+                self.publish_all()
+            }
+            ("textDocument/didSave", Some(_)) => self.publish_all(),
+            ("textDocument/didClose", Some(path)) => {
+                self.documents.remove(&path);
+                vec![publish_diagnostics(&path, &[])]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        self.root = params.get("rootUri")
+            .and_then(Value::as_str)
+            .and_then(uri_to_path)
+            .or_else(|| params.get("rootPath").and_then(Value::as_str).map(PathBuf::from));
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "hoverProvider": true,
+                "definitionProvider": true,
+                "referencesProvider": true,
+                "completionProvider": { "triggerCharacters": ["&", ":", "|"] },
+            },
+            "serverInfo": { "name": "rel", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    // ========================================================================
+    // SECTION: Diagnostics
+    // ========================================================================
+
+    /// Re-check every open document, since an edit can break its importers
+    fn publish_all(&self) -> Vec<Value> {
+        let mut paths: Vec<&PathBuf> = self.documents.keys().collect();
+        paths.sort();
+
+        paths.into_iter()
+            .map(|path| {
+                let analysis = Analysis::new(&self.documents[path]);
+                let imports = self.import_diagnostics(path, &analysis);
+                // A name that can't be imported is reported once, not also as unused
+                let quoted = |message: &str| message.split('\'').nth(1).map(String::from);
+                let broken: Vec<String> = imports.iter()
+                    .filter(|d| d.code == DiagnosticCode::NotExported)
+                    .filter_map(|d| quoted(&d.message))
+                    .collect();
+                let mut diagnostics: Vec<Diagnostic> = analysis.diagnostics.iter()
+                    .filter(|d| d.code != DiagnosticCode::UnusedImport || !quoted(&d.message).is_some_and(|name| broken.contains(&name)))
+                    .cloned()
+                    .collect();
+                diagnostics.extend(imports);
+                publish_diagnostics(path, &diagnostics)
+            })
+            .collect()
+    }
+
+    /// Missing modules and names the imported module does not export
+    fn import_diagnostics(&self, path: &Path, analysis: &Analysis) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for import in &analysis.imports {
+            let target = resolver_for(path)
+                .resolve_import_path(path, &import.path)
+                .ok()
+                .and_then(|target_path| self.analyze(&target_path));
+
+            let Some(target) = target else {
+                diagnostics.push(Diagnostic::error(import.path_span, DiagnosticCode::ModuleNotFound, format!("Cannot find module '{}'", import.path)));
+                continue;
+            };
+
+            // A broken module gets its own diagnostics; don't guess at its exports
+            if target.ast.is_none() || target.exports_all() {
+                continue;
+            }
+
+            let exports = target.exports();
+            for (item, span) in &import.items {
+                if !exports.contains(item) {
+                    diagnostics.push(Diagnostic::error(*span, DiagnosticCode::NotExported, format!("'{}' is not exported by '{}'", item, import.path)));
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    // ========================================================================
+    // SECTION: Navigation
+    // ========================================================================
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((path, analysis, position)) = self.document_position(params) else {
+            return Value::Null;
+        };
+        let Some(name) = analysis.name_at(position).map(|t| t.value.clone()) else {
+            return Value::Null;
+        };
+
+        match self.resolve(&path, analysis, &name, 0) {
+            Some(resolved) => location(&resolved.path, resolved.symbol.span),
+            None => Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((path, analysis, position)) = self.document_position(params) else {
+            return Value::Null;
+        };
+        let Some(token) = analysis.name_at(position).cloned() else {
+            return Value::Null;
+        };
+
+        let contents = match token.token_type {
+            TokenType::TypeName => Some(format!("`{}` — built-in type", token.value)),
+            TokenType::Constraint => Some(format!("`{}` — constraint", token.value)),
+            _ => {
+                let is_call = analysis.next_token(&token).is_some_and(|t| t.token_type == TokenType::LParen);
+                let arguments = if is_call { analysis.call_arguments(&token.value, token.line) } else { None };
+                self.resolve(&path, analysis, &token.value, 0)
+                    .and_then(|resolved| resolved.analysis.hover(&resolved.symbol.name, arguments.as_deref()))
+            }
+        };
+
+        match contents {
+            Some(value) => json!({ "contents": { "kind": "markdown", "value": value } }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let Some((path, analysis, position)) = self.document_position(params) else {
+            return Value::Null;
+        };
+
+        let text = self.read(&path).unwrap_or_default();
+        let line_prefix: String = text.lines()
+            .nth(position.line)
+            .unwrap_or_default()
+            .chars()
+            .take(position.character)
+            .collect();
+
+        // Aliased imports complete as the name this file uses
+        let imported: Vec<String> = analysis.imports.iter()
+            .flat_map(|import| import.locals.iter().cloned())
+            .collect();
+
+        json!({ "isIncomplete": false, "items": analysis.completions(&line_prefix, &imported) })
+    }
+
+    fn references(&self, params: &Value) -> Value {
+        let Some((path, analysis, position)) = self.document_position(params) else {
+            return Value::Null;
+        };
+        let Some(name) = analysis.name_at(position).map(|t| t.value.clone()) else {
+            return Value::Null;
+        };
+        let Some(declaration) = self.resolve(&path, analysis, &name, 0) else {
+            return json!([]);
+        };
+
+        let include_declaration = params.get("context")
+            .and_then(|c| c.get("includeDeclaration"))
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+
+        let mut locations = Vec::new();
+        for file in self.workspace_files(&declaration.path) {
+            let Some(analysis) = self.analyze(&file) else { continue };
+
+            // Other files only refer to the symbol when they import it from
+            // its module, possibly under another name
+            let declared = &declaration.symbol.name;
+            let local = match file == declaration.path {
+                true => Some(declared.clone()),
+                false => analysis.imports.iter()
+                    .filter(|import| resolver_for(&file).resolve_import_path(&file, &import.path).ok() == Some(declaration.path.clone()))
+                    .find_map(|import| import.local_name(declared).map(str::to_string)),
+            };
+            let Some(local) = local else { continue };
+
+            for span in analysis.occurrences(&local) {
+                if include_declaration || file != declaration.path || span != declaration.symbol.span {
+                    locations.push(location(&file, span));
+                }
+            }
+        }
+
+        Value::Array(locations)
+    }
+
+    /// Follow imports until the file that declares `name`
+    fn resolve(&self, path: &Path, analysis: Analysis, name: &str, depth: usize) -> Option<Resolved> {
+        if let Some(symbol) = analysis.symbol(name).cloned() {
+            return Some(Resolved { path: path.to_path_buf(), analysis, symbol });
+        }
+        if depth > 16 {
+            return None;
+        }
+
+        // An aliased import is looked up by the name its module exports
+        let (import, exported) = analysis.imports.iter()
+            .find_map(|import| import.exported_name(name).map(|exported| (import, exported)))?;
+        let target_path = resolver_for(path).resolve_import_path(path, &import.path).ok()?;
+        let target = self.analyze(&target_path)?;
+
+        self.resolve(&target_path, target, exported, depth + 1)
+    }
+
+    // ========================================================================
+    // SECTION: Documents
+    // ========================================================================
+
+    /// Open document text, falling back to the file on disk
+    fn read(&self, path: &Path) -> Option<String> {
+        match self.documents.get(path) {
+            Some(text) => Some(text.clone()),
+            None => fs::read_to_string(path).ok(),
+        }
+    }
+
+    fn analyze(&self, path: &Path) -> Option<Analysis> {
+        self.read(path).map(|text| Analysis::new(&text))
+    }
+
+    /// The `textDocument` and `position` of a request
+    fn document_position(&self, params: &Value) -> Option<(PathBuf, Analysis, Position)> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let path = canonical(&uri_to_path(uri)?);
+        let position = Position::from_json(params.get("position")?)?;
+        let analysis = self.analyze(&path)?;
+        Some((path, analysis, position))
+    }
+
+    /// Every .rel file under the workspace root plus open documents
+    fn workspace_files(&self, fallback_root: &Path) -> Vec<PathBuf> {
+        let root = self.root.clone()
+            .or_else(|| fallback_root.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        let finder = compiler::relCompiler::new(compiler::CompilerOptions {
+            input_dir: root.clone(),
+            output_dir: None,
+            watch: false,
+            diagnostics_format: DiagnosticsFormat::Human,
+            incremental: false,
+            jobs: 1,
+            config: Default::default(),
+        });
+
+        let mut files: Vec<PathBuf> = finder.find_rel_files(&root)
+            .unwrap_or_default()
+            .iter()
+            .map(|file| canonical(file))
+            .collect();
+        files.extend(self.documents.keys().cloned());
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+impl Default for LanguageServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// SECTION: Helpers
+// ============================================================================
+
+/// Resolver for imports in `path`, using aliases from the nearest rel.json
+fn resolver_for(path: &Path) -> ModuleResolver {
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let config = ProjectConfig::discover(&dir).ok().flatten().unwrap_or_default();
+    ModuleResolver::new(dir).with_config(&config)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn location(path: &Path, span: analysis::Span) -> Value {
+    json!({ "uri": path_to_uri(path), "range": span.to_json() })
+}
+
+fn publish_diagnostics(path: &Path, diagnostics: &[Diagnostic]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {
+            "uri": path_to_uri(path),
+            "diagnostics": diagnostics.iter().map(Diagnostic::to_json).collect::<Vec<_>>(),
+        },
+    })
+}
+
+/// `file:///a/b%20c.rel` -> `/a/b c.rel`
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let raw = encoded.as_bytes();
+    let mut bytes = Vec::with_capacity(raw.len());
+
+    let mut index = 0;
+    while index < raw.len() {
+        let escaped = (raw[index] == b'%')
+            .then(|| encoded.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                index += 3;
+            }
+            None => {
+                bytes.push(raw[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+    }
+
+    #[test]
+    fn test_diagnostics_and_definition_across_imports() {
+        let dir = std::env::temp_dir().join(format!("rel-lsp-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("base.rel"), "type Id = string & minLength(3)\nexport Id\n").unwrap();
+        let user = canonical(&dir).join("user.rel");
+        let user_uri = path_to_uri(&user);
+
+        let mut server = LanguageServer::new();
+        let published = server.handle(&notification("textDocument/didOpen", json!({
+            "textDocument": {
+                "uri": user_uri,
+                "text": "import { Id, Missing } from \"./base.rel\"\ndefine User {\n  id: Id\n}\nexport User\n",
+            }
+        })));
+
+        let diagnostics = published[0]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["message"], "'Missing' is not exported by './base.rel'");
+
+        let reply = server.handle(&request("textDocument/definition", json!({
+            "textDocument": { "uri": user_uri },
+            "position": { "line": 2, "character": 7 },
+        })));
+        let location = &reply[0]["result"];
+        assert!(location["uri"].as_str().unwrap().ends_with("/base.rel"));
+        assert_eq!(location["range"]["start"], json!({ "line": 0, "character": 5 }));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_aliased_imports_resolve_to_the_exported_name() {
+        let dir = std::env::temp_dir().join(format!("rel-lsp-alias-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("base.rel"), "define Timestamps {\n  createdAt: date\n}\nexport Timestamps\n").unwrap();
+        let user = canonical(&dir).join("user.rel");
+        let user_uri = path_to_uri(&user);
+
+        let mut server = LanguageServer::new();
+        let published = server.handle(&notification("textDocument/didOpen", json!({
+            "textDocument": {
+                "uri": user_uri,
+                "text": "import { Timestamps as TS } from \"./base.rel\"\ndefine User {\n  stamps: TS\n}\nexport User\n",
+            }
+        })));
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+        let at_alias = json!({ "textDocument": { "uri": user_uri }, "position": { "line": 2, "character": 11 } });
+
+        let definition = &server.handle(&request("textDocument/definition", at_alias.clone()))[0]["result"];
+        assert!(definition["uri"].as_str().unwrap().ends_with("/base.rel"));
+        assert_eq!(definition["range"]["start"], json!({ "line": 0, "character": 7 }));
+
+        let hover = &server.handle(&request("textDocument/hover", at_alias.clone()))[0]["result"];
+        assert!(hover["contents"]["value"].as_str().unwrap().contains("define Timestamps"), "{}", hover);
+
+        let references = &server.handle(&request("textDocument/references", at_alias))[0]["result"];
+        assert_eq!(references.as_array().unwrap().len(), 4, "{}", references);
+
+        let completion = &server.handle(&request("textDocument/completion", json!({
+            "textDocument": { "uri": user_uri },
+            "position": { "line": 2, "character": 10 },
+        })))[0]["result"];
+        let labels: Vec<&str> = completion["items"].as_array().unwrap().iter()
+            .filter_map(|item| item["label"].as_str())
+            .collect();
+        assert!(labels.contains(&"TS"));
+        assert!(!labels.contains(&"Timestamps"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
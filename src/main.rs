@@ -1,4 +1,4 @@
-/**
+/*!
  * rel Compiler - Main entry point
  */
 
@@ -9,6 +9,6 @@ fn main() {
 
     if let Err(e) = rel::commands::execute_command(cli) {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        std::process::exit(rel::diagnostics::exit_code(e.as_ref()));
     }
 }
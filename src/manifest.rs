@@ -0,0 +1,138 @@
+/*!
+ * Build Manifest - which generated files came from which sources
+ *
+ * Output paths are derived from input paths, so once a .rel file is renamed
+ * or deleted nothing else remembers its old .ts file. Every build records its
+ * outputs in `.rel-manifest.json` inside the output directory; outputs whose
+ * source has gone are pruned by `rel build` and `rel clean`.
+ */
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE: &str = ".rel-manifest.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Directory holding the manifest; outputs are relative to it
+    #[serde(skip)]
+    dir: PathBuf,
+    /// Output path, relative to `dir` -> the source it was generated from
+    outputs: BTreeMap<PathBuf, PathBuf>,
+    /// Sources built since loading -> their current output, relative to `dir`
+    #[serde(skip)]
+    built: BTreeMap<PathBuf, PathBuf>,
+}
+
+impl BuildManifest {
+    /// Load the manifest in `dir`; a missing or unreadable one starts empty
+    pub fn load(dir: &Path) -> Self {
+        let mut manifest: BuildManifest = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        manifest.dir = dir.to_path_buf();
+        manifest
+    }
+
+    /// Note that `source` generated `output`
+    pub fn record(&mut self, output: &Path, source: &Path) {
+        let output = output.strip_prefix(&self.dir).unwrap_or(output).to_path_buf();
+        self.outputs.insert(output.clone(), source.to_path_buf());
+        self.built.insert(source.to_path_buf(), output);
+    }
+
+    /// Delete outputs whose source no longer exists, or which a source built
+    /// since loading no longer writes; returns the deleted files
+    pub fn prune(&mut self) -> io::Result<Vec<PathBuf>> {
+        let stale: Vec<PathBuf> = self.outputs.iter()
+            .filter(|(output, source)| {
+                !source.is_file() || self.built.get(*source).is_some_and(|current| current != *output)
+            })
+            .map(|(output, _)| output.clone())
+            .collect();
+        self.remove(stale)
+    }
+
+    /// Delete every recorded output and the manifest itself
+    pub fn clean_all(&mut self) -> io::Result<Vec<PathBuf>> {
+        let all: Vec<PathBuf> = self.outputs.keys().cloned().collect();
+        let removed = self.remove(all)?;
+
+        let path = self.dir.join(MANIFEST_FILE);
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+        Ok(removed)
+    }
+
+    /// Write the manifest next to the outputs it lists
+    pub fn save(&self) -> io::Result<()> {
+        if self.outputs.is_empty() && !self.dir.join(MANIFEST_FILE).exists() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(self.dir.join(MANIFEST_FILE), text)
+    }
+
+    /// Directories a mirrored output left empty, up to the manifest's own
+    fn remove_empty_parents(&self, path: &Path) {
+        for dir in path.ancestors().skip(1).take_while(|dir| *dir != self.dir && dir.starts_with(&self.dir)) {
+            // Fails, and stops, at the first directory that still has files
+            if fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn remove(&mut self, outputs: Vec<PathBuf>) -> io::Result<Vec<PathBuf>> {
+        let mut removed = Vec::new();
+        for output in outputs {
+            self.outputs.remove(&output);
+            let path = self.dir.join(&output);
+            if path.is_file() {
+                fs::remove_file(&path)?;
+                self.remove_empty_parents(&path);
+                removed.push(path);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_removes_outputs_of_deleted_and_renamed_sources() {
+        let dir = std::env::temp_dir().join(format!("rel-manifest-test-{}", std::process::id()));
+        let out = dir.join("generated");
+        fs::create_dir_all(&out).unwrap();
+        let (kept, deleted) = (dir.join("user.rel"), dir.join("order.rel"));
+        fs::write(&kept, "").unwrap();
+        for name in ["user.ts", "order.ts", "old_user.ts"] {
+            fs::write(out.join(name), "").unwrap();
+        }
+
+        let mut manifest = BuildManifest::load(&out);
+        manifest.record(&out.join("old_user.ts"), &kept);
+        manifest.record(&out.join("order.ts"), &deleted);
+        manifest.save().unwrap();
+
+        // The next build writes user.rel to a new name; order.rel is gone
+        let mut manifest = BuildManifest::load(&out);
+        manifest.record(&out.join("user.ts"), &kept);
+        let mut removed = manifest.prune().unwrap();
+        removed.sort();
+        assert_eq!(removed, vec![out.join("old_user.ts"), out.join("order.ts")]);
+        assert!(out.join("user.ts").is_file());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*!
  * rel Parser - Converts tokens to AST
  * 
  * FILE STRUCTURE:
@@ -15,19 +15,29 @@
 use crate::ast::*;
 use crate::lexer::{Token, TokenType};
 use crate::ast::SourceLocation;
+use crate::printer::Printer;
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
     errors: Vec<ParseError>,
+    comments: Vec<Token>,   // Comment tokens, kept out of the main stream
+    next_comment: usize,    // First comment not yet attached to a node
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
+        // Comments (only present when the lexer retains them) are attached to
+        // nodes as a side channel so the grammar never has to skip them
+        let (comments, tokens) = tokens.into_iter()
+            .partition(|token| token.token_type == TokenType::Comment);
+
         Self {
             tokens,
             current: 0,
             errors: Vec::new(),
+            comments,
+            next_comment: 0,
         }
     }
 
@@ -35,6 +45,8 @@ impl Parser {
         let mut nodes = Vec::new();
 
         while !self.is_at_end() {
+            nodes.extend(self.take_comment_nodes());
+
             match self.parse_top_level() {
                 Ok(node) => nodes.push(node),
                 Err(error) => {
@@ -45,6 +57,9 @@ impl Parser {
             }
         }
 
+        // Comments after the last declaration
+        nodes.extend(self.take_comment_nodes());
+
         if self.errors.is_empty() {
             Ok(nodes)
         } else {
@@ -107,6 +122,9 @@ impl Parser {
         let mut fields = Vec::new();
         while !self.check(TokenType::RBrace) && !self.is_at_end() {
             if self.check(TokenType::When) {
+                let blank_line = self.blank_line_before();
+                let leading = self.take_leading_comments();
+                let when_token = self.peek().clone();
                 // Parse conditional block
                 let conditional = self.parse_conditional()?;
                 // Convert conditional to a field-like structure
@@ -118,7 +136,14 @@ impl Parser {
                     computed_value: None,
                     validations: Vec::new(),
                     conditionals: Vec::new(),
-                    location: SourceLocation::unknown(),
+                    discriminator: None,
+                    location: SourceLocation::new(when_token.line, when_token.column),
+                    comments: Box::new(FieldComments {
+                        leading,
+                        trailing: self.take_trailing_comment(),
+                        dangling: Vec::new(),
+                        blank_line,
+                    }),
                 };
                 fields.push(conditional_field);
             } else {
@@ -128,6 +153,7 @@ impl Parser {
             }
         }
 
+        self.attach_dangling_comments(&mut fields);
         self.consume(TokenType::RBrace, "Expected '}' after schema body")?;
 
         Ok(ASTNode::Schema(SchemaNode {
@@ -157,13 +183,54 @@ impl Parser {
     }
 
     fn parse_field(&mut self) -> Result<FieldNode, ParseError> {
+        let blank_line = self.blank_line_before();
+        let leading = self.take_leading_comments();
+        let discriminator = self.parse_field_decorator()?;
+
         // Check if this is a conditional field (starts with 'when')
-        if self.check(TokenType::When) {
-            return self.parse_conditional_field();
+        let mut field = if self.check(TokenType::When) && discriminator.is_none() {
+            self.parse_conditional_field()?
+        } else {
+            self.parse_plain_field()?
+        };
+
+        field.discriminator = discriminator;
+        field.comments.leading = leading;
+        field.comments.blank_line = blank_line;
+        if field.comments.trailing.is_none() {
+            field.comments.trailing = self.take_trailing_comment();
         }
+        Ok(field)
+    }
+
+    /// `@discriminator(kind)` on the line above a field: the field its union's
+    /// variants are told apart by
+    fn parse_field_decorator(&mut self) -> Result<Option<String>, ParseError> {
+        if !self.match_token(TokenType::At) {
+            return Ok(None);
+        }
+        if !(self.check(TokenType::Identifier) && self.peek().value == "discriminator") {
+            return Err(self.error("Expected 'discriminator' after '@' on a field"));
+        }
+        self.advance();
+        self.consume(TokenType::LParen, "Expected '(' after '@discriminator'")?;
+        let name = if self.check(TokenType::Constraint) {
+            self.advance().value
+        } else {
+            self.consume_identifier("Expected the name of the tag field")?
+        };
+        self.consume(TokenType::RParen, "Expected ')' after the tag field")?;
+        Ok(Some(name))
+    }
 
+    fn parse_plain_field(&mut self) -> Result<FieldNode, ParseError> {
         let start_token = self.peek().clone();
-        let name = self.consume_identifier("Expected field name")?;
+        // Constraint names such as `min` or `unique` are fine as field names
+        let name = if self.check(TokenType::Constraint) {
+            self.advance().value
+        } else {
+            self.consume_identifier("Expected field name")?
+        };
         self.consume(TokenType::Colon, "Expected ':' after field name")?;
 
         let field_type = self.parse_type()?;
@@ -183,12 +250,22 @@ impl Parser {
             None
         };
 
+        // A comment ending the field's own line, before any `when` that follows
+        let trailing = self.take_trailing_comment();
+
         let mut validations = Vec::new();
         let mut conditionals = Vec::new();
 
         // Parse conditionals and validations
-        while self.match_token(TokenType::When) {
-            conditionals.push(self.parse_conditional()?);
+        // Comments above a `when` belong to it, not to the first field inside
+        while self.check(TokenType::When) {
+            let blank_line = self.blank_line_before();
+            let comments = self.take_leading_comments();
+            self.advance();
+            let mut conditional = self.parse_conditional()?;
+            conditional.comments = comments;
+            conditional.blank_line = blank_line;
+            conditionals.push(conditional);
         }
 
         // Parse validations
@@ -205,7 +282,9 @@ impl Parser {
             computed_value,
             validations,
             conditionals,
+            discriminator: None,
             location: SourceLocation::new(start_token.line, start_token.column),
+            comments: Box::new(FieldComments { trailing, ..Default::default() }),
         })
     }
 
@@ -223,7 +302,9 @@ impl Parser {
             computed_value: None,
             validations: Vec::new(),
             conditionals: Vec::new(),
+            discriminator: None,
             location: SourceLocation::new(start_token.line, start_token.column),
+            comments: Box::default(),
         })
     }
 
@@ -277,11 +358,20 @@ impl Parser {
             base_type
         };
 
-        // Handle constraints
+        // Handle intersections (`User & Timestamps`), then constraints
+        let mut members = vec![base_type];
         let mut constraints = Vec::new();
         while self.match_token(TokenType::Ampersand) {
-            constraints.push(self.parse_constraint()?);
+            if constraints.is_empty() && self.at_intersection_member() {
+                members.push(self.parse_base_type()?);
+            } else {
+                constraints.push(self.parse_constraint()?);
+            }
         }
+        let base_type = match members.len() {
+            1 => members.remove(0),
+            _ => TypeNode::Intersection(members),
+        };
 
         if constraints.is_empty() {
             Ok(base_type)
@@ -293,6 +383,16 @@ impl Parser {
         }
     }
 
+    /// After `&`: another object type rather than a constraint. Names followed
+    /// by `(` stay constraints, so a misspelled one is still reported as such.
+    fn at_intersection_member(&self) -> bool {
+        if self.check(TokenType::LBrace) {
+            return true;
+        }
+        let is_call = self.tokens.get(self.current + 1).is_some_and(|token| token.token_type == TokenType::LParen);
+        self.check(TokenType::Identifier) && !is_call && constraint_type(&self.peek().value).is_none()
+    }
+
     fn parse_base_type(&mut self) -> Result<TypeNode, ParseError> {
         // Check for literal values (=value syntax)
         if self.match_token(TokenType::Equals) {
@@ -304,12 +404,32 @@ impl Parser {
         if self.check(TokenType::LBrace) {
             return self.parse_inline_object();
         }
+
+        if self.check(TokenType::LBracket) {
+            return self.parse_tuple();
+        }
+
+        // A parenthesized type, so constraints can apply to array items:
+        // `(string & minLength(2))[]`
+        if self.match_token(TokenType::LParen) {
+            let inner = self.parse_type()?;
+            self.consume(TokenType::RParen, "Expected ')' after type")?;
+            return Ok(inner);
+        }
         
         // Accept both Identifier and TypeName tokens
-        let type_name = if self.check(TokenType::TypeName) {
+        // (null/undefined are lexed as literal keywords but are also valid types)
+        let name_token = self.peek().clone();
+        let type_name = if self.check(TokenType::TypeName) || self.check(TokenType::Null) || self.check(TokenType::Undefined) {
             self.advance().value
         } else {
-            self.consume_identifier("Expected type name")?
+            let mut name = self.consume_identifier("Expected type name")?;
+            // A name from a namespace import: `billing.Invoice`
+            while self.match_token(TokenType::Dot) {
+                name.push('.');
+                name.push_str(&self.consume_identifier("Expected a name after '.'")?);
+            }
+            name
         };
 
         match type_name.as_str() {
@@ -332,7 +452,37 @@ impl Parser {
                         }
                     }
                     self.consume(TokenType::GreaterThan, "Expected '>' after generic type arguments")?;
-                    Ok(TypeNode::Generic(type_name, type_args))
+                    // Utility types: Partial<User>, Pick<User, id | email>
+                    let takes = match type_name.as_str() {
+                        "Partial" | "Required" => Some((1, "one object type")),
+                        "Pick" => Some((2, "a type and the fields to keep")),
+                        "Omit" => Some((2, "a type and the fields to leave out")),
+                        _ => None,
+                    };
+                    if let Some((count, description)) = takes.filter(|(count, _)| *count != type_args.len()) {
+                        return Err(ParseError {
+                            message: format!("'{}' takes {}", type_name, description),
+                            position: name_token.position,
+                            line: name_token.line,
+                            column: name_token.column,
+                            context: Some(format!("{} type argument(s) given, {} expected", type_args.len(), count)),
+                            file_path: None,
+                        });
+                    }
+                    if !matches!(type_name.as_str(), "record" | "Record" | "map" | "Map") {
+                        return Ok(TypeNode::Generic(type_name, type_args));
+                    }
+                    match <[TypeNode; 2]>::try_from(type_args) {
+                        Ok([key, value]) => Ok(TypeNode::Record { key: Box::new(key), value: Box::new(value) }),
+                        Err(type_args) => Err(ParseError {
+                            message: format!("'{}' takes a key type and a value type", type_name),
+                            position: name_token.position,
+                            line: name_token.line,
+                            column: name_token.column,
+                            context: Some(format!("{} type argument(s) given", type_args.len())),
+                            file_path: None,
+                        }),
+                    }
                 } else if self.check(TokenType::LParen) {
                     // Function call: Ranged(0, 100)
                     self.advance(); // consume '('
@@ -356,6 +506,36 @@ impl Parser {
         }
     }
     
+    /// `[number, number]`, with optional elements at the end (`boolean?`) and
+    /// a final rest element (`...string[]`)
+    fn parse_tuple(&mut self) -> Result<TypeNode, ParseError> {
+        self.consume(TokenType::LBracket, "Expected '['")?;
+
+        let mut elements: Vec<TupleElement> = Vec::new();
+        while !self.check(TokenType::RBracket) && !self.is_at_end() {
+            if elements.last().is_some_and(|element| element.rest) {
+                return Err(self.error("A rest element must be the last element of a tuple"));
+            }
+            let rest = self.match_token(TokenType::Ellipsis);
+            let element_type = self.parse_type()?;
+            if rest && !matches!(element_type, TypeNode::Array(_)) {
+                return Err(self.error("A rest element must be an array type, as in '...string[]'"));
+            }
+            let optional = !rest && self.match_token(TokenType::Question);
+            if !optional && !rest && elements.last().is_some_and(|element| element.optional) {
+                return Err(self.error("A required element can't follow an optional one"));
+            }
+            elements.push(TupleElement { element_type, optional, rest });
+
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RBracket, "Expected ']' after tuple elements")?;
+        Ok(TypeNode::Tuple(elements))
+    }
+
     fn parse_inline_object(&mut self) -> Result<TypeNode, ParseError> {
         self.consume(TokenType::LBrace, "Expected '{'")?;
         
@@ -367,56 +547,37 @@ impl Parser {
             // Optional comma between fields
             self.match_token(TokenType::Comma);
         }
-        
+
+        self.attach_dangling_comments(&mut fields);
         self.consume(TokenType::RBrace, "Expected '}' after inline object")?;
         
         Ok(TypeNode::InlineObject(fields))
     }
 
     fn parse_constraint(&mut self) -> Result<ConstraintNode, ParseError> {
-        // Accept Identifier, Constraint, and TypeName tokens (for dual-use tokens like 'positive')
-        let name = if self.check(TokenType::Constraint) {
-            self.advance().value
-        } else if self.check(TokenType::TypeName) {
+        let start_token = self.peek().clone();
+        // Accept Identifier, Constraint, and TypeName tokens (for dual-use tokens like 'positive'),
+        // plus the `in` and `null` keywords
+        let name = if self.check(TokenType::Constraint) || self.check(TokenType::TypeName) || self.check(TokenType::In) || self.check(TokenType::Null) {
             self.advance().value
         } else {
             self.consume_identifier("Expected constraint name")?
         };
-        let constraint_type = match name.as_str() {
-            "min" => ConstraintType::Min,
-            "max" => ConstraintType::Max,
-            "minLength" => ConstraintType::MinLength,
-            "maxLength" => ConstraintType::MaxLength,
-            "matches" => ConstraintType::Matches,
-            "contains" => ConstraintType::Contains,
-            "startsWith" => ConstraintType::StartsWith,
-            "endsWith" => ConstraintType::EndsWith,
-            "hasUppercase" => ConstraintType::HasUppercase,
-            "hasLowercase" => ConstraintType::HasLowercase,
-            "hasNumber" => ConstraintType::HasNumber,
-            "hasSpecialChar" => ConstraintType::HasSpecialChar,
-            "between" => ConstraintType::Between,
-            "in" => ConstraintType::In,
-            "notIn" => ConstraintType::NotIn,
-            "exists" => ConstraintType::Exists,
-            "empty" => ConstraintType::Empty,
-            "null" => ConstraintType::Null,
-            "future" => ConstraintType::Future,
-            "past" => ConstraintType::Past,
-            "before" => ConstraintType::Before,
-            "after" => ConstraintType::After,
-            "integer" => ConstraintType::Integer,
-            "positive" => ConstraintType::Positive,
-            "negative" => ConstraintType::Negative,
-            "float" => ConstraintType::Float,
-            "literal" => ConstraintType::Literal,
-            _ => return Err(self.error("Unknown constraint")),
+        let Some(constraint_type) = constraint_type(&name) else {
+            return Err(self.error("Unknown constraint"));
         };
 
+        // Several arguments, as in `between(1, 10)`, are kept as one array
         let value = if self.match_token(TokenType::LParen) {
-            let expr = self.parse_expression()?;
+            let mut arguments = vec![self.parse_expression()?];
+            while self.match_token(TokenType::Comma) {
+                arguments.push(self.parse_expression()?);
+            }
             self.consume(TokenType::RParen, "Expected ')' after constraint argument")?;
-            Some(expr)
+            match arguments.len() {
+                1 => arguments.pop(),
+                _ => Some(ExpressionNode::Array(arguments)),
+            }
         } else {
             None
         };
@@ -424,6 +585,7 @@ impl Parser {
         Ok(ConstraintNode {
             constraint_type,
             value,
+            location: SourceLocation::new(start_token.line, start_token.column),
         })
     }
     // ========================================================================
@@ -431,6 +593,7 @@ impl Parser {
     // ========================================================================
 
 fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
+    let start_token = self.peek().clone();
     let condition = self.parse_expression()?;
     self.consume(TokenType::LBrace, "Expected '{' after condition")?;
 
@@ -442,6 +605,7 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
         self.match_token(TokenType::Comma);
     }
 
+    self.attach_dangling_comments(&mut then_fields);
     self.consume(TokenType::RBrace, "Expected '}' after then block")?;
 
     // Create type node for then value
@@ -467,6 +631,7 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
                 // Optional comma between fields
                 self.match_token(TokenType::Comma);
             }
+            self.attach_dangling_comments(&mut else_fields);
             self.consume(TokenType::RBrace, "Expected '}' after else block")?;
 
             (Some(TypeNode::Object), else_fields)
@@ -481,6 +646,9 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
         else_value,
         then_fields,
         else_fields,
+        location: SourceLocation::new(start_token.line, start_token.column),
+        comments: Vec::new(),
+        blank_line: false,
     })
 }
 
@@ -502,12 +670,31 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
         // validate permissions.length() > 0 if role = admin
         // validate total = subtotal + tax - discount
 
-        let left = self.parse_expression()?;
+        let mut left = self.parse_expression()?;
+
+        // parse_expression already folds `field > value` into a BinaryOp,
+        // so split a comparison on a field reference back out
+        let mut comparison = None;
+        if let ExpressionNode::BinaryOp { left: lhs, operator, right } = &left {
+            let is_field = matches!(**lhs, ExpressionNode::Identifier(_) | ExpressionNode::FieldAccess(_));
+            let is_comparison = !matches!(operator,
+                BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Plus | BinaryOperator::Minus
+                | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo);
+            if is_field && is_comparison {
+                comparison = Some((operator.clone(), (**right).clone()));
+                left = (**lhs).clone();
+            }
+        }
 
         // Check for comparison operators
-        if self.is_comparison_operator() {
-            let operator = self.comparison_operator()?;
-            let right = self.parse_expression()?;
+        if comparison.is_some() || self.is_comparison_operator() {
+            let (operator, right) = match comparison {
+                Some(comparison) => comparison,
+                None => {
+                    let operator = self.comparison_operator()?;
+                    (operator, self.parse_expression()?)
+                }
+            };
 
             // Check for optional conditional part (if ...)
             let _condition = if self.match_token(TokenType::If) {
@@ -552,8 +739,8 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
             });
         }
 
-        // Default to custom validation
-        Ok(ValidationRule::Custom(format!("{:?}", left)))
+        // Default to custom validation, kept as source so it can be printed back
+        Ok(ValidationRule::Custom(Printer::print_expression(&left)))
     }
 
     // ========================================================================
@@ -756,31 +943,38 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
     // ========================================================================
 
     fn parse_import(&mut self) -> Result<ASTNode, ParseError> {
+        let start_token = self.peek().clone();
         self.consume(TokenType::Import, "Expected 'import'")?;
-        let items = self.parse_import_items()?;
-        self.consume(TokenType::From, "Expected 'from'")?;
-        let path = self.consume_string("Expected import path")?;
 
-        // Handle optional alias
-        let alias = if self.match_token(TokenType::As) {
-            Some(self.consume_identifier("Expected alias name")?)
+        // `import * as billing from "..."` or `import { A, B as C } from "..."`
+        let (items, alias) = if self.match_token(TokenType::Multiply) {
+            self.consume(TokenType::As, "Expected 'as' after '*'")?;
+            (Vec::new(), Some(self.consume_identifier("Expected namespace name")?))
         } else {
-            None
+            (self.parse_import_items()?, None)
         };
 
+        self.consume(TokenType::From, "Expected 'from'")?;
+        let path = self.consume_string("Expected import path")?;
+
         Ok(ASTNode::Import(ImportNode {
             path,
             items,
             alias,
+            location: SourceLocation::new(start_token.line, start_token.column),
         }))
     }
 
-    fn parse_import_items(&mut self) -> Result<Vec<String>, ParseError> {
+    fn parse_import_items(&mut self) -> Result<Vec<ImportItem>, ParseError> {
         let mut items = Vec::new();
         self.consume(TokenType::LBrace, "Expected '{' after import")?;
 
         loop {
-            items.push(self.consume_identifier("Expected import item")?);
+            let mut item = ImportItem::new(self.consume_identifier("Expected import item")?);
+            if self.match_token(TokenType::As) {
+                item.alias = Some(self.consume_identifier("Expected alias name")?);
+            }
+            items.push(item);
             if !self.match_token(TokenType::Comma) {
                 break;
             }
@@ -791,20 +985,39 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
     }
 
     fn parse_export(&mut self) -> Result<ASTNode, ParseError> {
+        let start_token = self.peek().clone();
         self.consume(TokenType::Export, "Expected 'export'")?;
-        let mut items = Vec::new();
+        let mut export = ExportNode::new(Vec::new());
+        export.location = SourceLocation::new(start_token.line, start_token.column);
+
+        // `export * from "..."` and `export { A, B } from "..."` re-export another module
+        if self.match_token(TokenType::Multiply) {
+            export.all = true;
+            self.consume(TokenType::From, "Expected 'from' after 'export *'")?;
+            export.from = Some(self.consume_string("Expected module path")?);
+            return Ok(ASTNode::Export(export));
+        }
 
+        let braced = self.match_token(TokenType::LBrace);
         loop {
-            items.push(self.consume_identifier("Expected export item")?);
+            export.items.push(self.consume_identifier("Expected export item")?);
             if !self.match_token(TokenType::Comma) {
                 break;
             }
         }
 
-        Ok(ASTNode::Export(ExportNode { items }))
+        if braced {
+            self.consume(TokenType::RBrace, "Expected '}' after export items")?;
+            if self.match_token(TokenType::From) {
+                export.from = Some(self.consume_string("Expected module path")?);
+            }
+        }
+
+        Ok(ASTNode::Export(export))
     }
 
     fn parse_enum(&mut self) -> Result<ASTNode, ParseError> {
+        let start_token = self.peek().clone();
         self.consume(TokenType::Enum, "Expected 'enum'")?;
         let name = self.consume_identifier("Expected enum name")?;
         self.consume(TokenType::LBrace, "Expected '{' after enum name")?;
@@ -818,10 +1031,15 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
 
         self.consume(TokenType::RBrace, "Expected '}' after enum values")?;
 
-        Ok(ASTNode::Enum(EnumNode { name, values }))
+        Ok(ASTNode::Enum(EnumNode {
+            name,
+            values,
+            location: SourceLocation::new(start_token.line, start_token.column),
+        }))
     }
 
     fn parse_type_alias(&mut self) -> Result<ASTNode, ParseError> {
+        let start_token = self.peek().clone();
         self.consume(TokenType::Type, "Expected 'type'")?;
         let name = self.consume_identifier("Expected type alias name")?;
         self.consume(TokenType::Equals, "Expected '=' after type alias name")?;
@@ -830,6 +1048,7 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
         Ok(ASTNode::TypeAlias(TypeAliasNode {
             name,
             type_definition,
+            location: SourceLocation::new(start_token.line, start_token.column),
         }))
     }
 
@@ -853,6 +1072,7 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
             self.match_token(TokenType::Comma);
         }
 
+        self.attach_dangling_comments(&mut fields);
         self.consume(TokenType::RBrace, "Expected '}' after mixin body")?;
 
         Ok(ASTNode::Mixin(MixinNode { name, fields }))
@@ -1101,16 +1321,101 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
 
     fn error(&self, message: &str) -> ParseError {
         let token = self.peek();
+        let found = match token.token_type {
+            TokenType::Eof => "unexpected end of file".to_string(),
+            TokenType::String => format!("found \"{}\"", token.value),
+            _ => format!("found '{}'", token.value),
+        };
         ParseError {
             message: message.to_string(),
             position: token.position,
             line: token.line,
             column: token.column,
-            context: None,
+            context: Some(found),
             file_path: None,
         }
     }
 
+    // ========================================================================
+    // SECTION: Comment Attachment
+    // ========================================================================
+
+    /// Take every pending comment that appears before the current token
+    fn take_leading_comments(&mut self) -> Vec<String> {
+        let position = self.peek().position;
+        let mut taken = Vec::new();
+
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.position >= position {
+                break;
+            }
+            taken.push(comment.value.clone());
+            self.next_comment += 1;
+        }
+
+        taken
+    }
+
+    /// Take the pending comments before the current token as top-level nodes,
+    /// noting which are followed by a blank line
+    fn take_comment_nodes(&mut self) -> Vec<ASTNode> {
+        let first = self.next_comment;
+        let texts = self.take_leading_comments();
+        texts.into_iter().enumerate()
+            .map(|(i, text)| {
+                let line = self.comments[first + i].line;
+                let next_line = match first + i + 1 {
+                    next if next < self.next_comment => self.comments[next].line,
+                    _ => self.peek().line,
+                };
+                ASTNode::Comment(CommentNode { text, blank_after: next_line > line + 1 })
+            })
+            .collect()
+    }
+
+    /// Whether a blank line sits between the previous token or comment and the
+    /// next one, which may be a pending comment
+    fn blank_line_before(&self) -> bool {
+        let Some(previous) = self.current.checked_sub(1).and_then(|i| self.tokens.get(i)) else {
+            return false;
+        };
+        let previous_line = self.comments[..self.next_comment].last()
+            .map_or(previous.line, |comment| comment.line.max(previous.line));
+        let next_line = self.comments.get(self.next_comment)
+            .filter(|comment| comment.position < self.peek().position)
+            .map_or(self.peek().line, |comment| comment.line);
+        next_line > previous_line + 1
+    }
+
+    /// Take the pending comment on the same line as the previous token, if
+    /// nothing but an optional comma sits between them
+    fn take_trailing_comment(&mut self) -> Option<String> {
+        let comment = self.comments.get(self.next_comment)?;
+        let previous = self.tokens.get(self.current.checked_sub(1)?)?;
+
+        let mut next = self.current;
+        if self.tokens.get(next).is_some_and(|t| t.token_type == TokenType::Comma) {
+            next += 1;
+        }
+        let before_next = self.tokens.get(next).is_none_or(|t| comment.position < t.position);
+
+        if comment.line == previous.line && before_next {
+            self.next_comment += 1;
+            Some(comment.value.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Comments left before a closing brace belong after the block's last field.
+    /// Empty blocks leave them pending for whatever comes next.
+    fn attach_dangling_comments(&mut self, fields: &mut [FieldNode]) {
+        if let Some(last) = fields.last_mut() {
+            let dangling = self.take_leading_comments();
+            last.comments.dangling.extend(dangling);
+        }
+    }
+
     fn synchronize(&mut self) {
         self.advance();
 
@@ -1130,4 +1435,40 @@ fn parse_conditional(&mut self) -> Result<ConditionalNode, ParseError> {
             }
         }
     }
-}
\ No newline at end of file
+}
+/// The constraint a name in `type & name(...)` refers to
+fn constraint_type(name: &str) -> Option<ConstraintType> {
+    match name {
+        "min" => Some(ConstraintType::Min),
+        "max" => Some(ConstraintType::Max),
+        "minLength" => Some(ConstraintType::MinLength),
+        "maxLength" => Some(ConstraintType::MaxLength),
+        "minItems" => Some(ConstraintType::MinItems),
+        "maxItems" => Some(ConstraintType::MaxItems),
+        "unique" => Some(ConstraintType::Unique),
+        "matches" => Some(ConstraintType::Matches),
+        "contains" => Some(ConstraintType::Contains),
+        "startsWith" => Some(ConstraintType::StartsWith),
+        "endsWith" => Some(ConstraintType::EndsWith),
+        "hasUppercase" => Some(ConstraintType::HasUppercase),
+        "hasLowercase" => Some(ConstraintType::HasLowercase),
+        "hasNumber" => Some(ConstraintType::HasNumber),
+        "hasSpecialChar" => Some(ConstraintType::HasSpecialChar),
+        "between" => Some(ConstraintType::Between),
+        "in" => Some(ConstraintType::In),
+        "notIn" => Some(ConstraintType::NotIn),
+        "exists" => Some(ConstraintType::Exists),
+        "empty" => Some(ConstraintType::Empty),
+        "null" => Some(ConstraintType::Null),
+        "future" => Some(ConstraintType::Future),
+        "past" => Some(ConstraintType::Past),
+        "before" => Some(ConstraintType::Before),
+        "after" => Some(ConstraintType::After),
+        "integer" => Some(ConstraintType::Integer),
+        "positive" => Some(ConstraintType::Positive),
+        "negative" => Some(ConstraintType::Negative),
+        "float" => Some(ConstraintType::Float),
+        "literal" => Some(ConstraintType::Literal),
+        _ => None,
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*!
  * Project Management Module
  *
  * Handles project initialization and setup.
@@ -1,16 +1,56 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::fs;
-use crate::ast::{ASTNode, ImportNode};
+use crate::ast::{ASTNode, ExportNode, FieldNode, ImportNode, SourceLocation, TypeNode};
+use crate::config::ProjectConfig;
+use crate::diagnostics::{self, Diagnostic, DiagnosticCode, Span};
 use crate::lexer::Lexer;
 use crate::parser::Parser;
-use crate::import_tracker::analyze_imports_exports;
+use crate::lint;
+use crate::parallel;
+use crate::validation::{intersections, records, unions};
+
+/// Directory of installed packages, searched for package-style imports
+pub const MODULES_DIR: &str = "rel_modules";
+
+/// A file's source text and AST, or the diagnostics that stopped it parsing
+type ParsedSource = Result<(String, Vec<ASTNode>), Vec<Diagnostic>>;
+
+/// A declaration: the file that declares it and its name there
+type Origin = (PathBuf, String);
+
+/// Names -> the declarations they refer to
+type Scope = HashMap<String, Origin>;
+
+/// Names used in one module -> their names in a merged AST
+type Renames = HashMap<String, String>;
+
+/// What the names of a resolved module refer to
+#[derive(Debug, Default)]
+struct ModuleScope {
+    /// Names usable in the module: its declarations, the local names of its
+    /// imports, and `ns.Type` for each name of a namespace import
+    names: Scope,
+    /// Names the module exports, re-exports included
+    exports: Scope,
+}
 
 pub struct ModuleResolver {
     /// Map of file path -> parsed AST
     modules: HashMap<PathBuf, Vec<ASTNode>>,
-    /// Set of files being processed (for circular dependency detection)
-    processing: HashSet<PathBuf>,
+    /// Map of file path -> its resolved dependencies, so a module shared by
+    /// several files is parsed once
+    dependencies: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Files currently being processed, in import order (for circular dependency detection)
+    processing: Vec<PathBuf>,
+    /// Files parsed ahead of resolution by `preload`, waiting to be picked up
+    parsed: HashMap<PathBuf, ParsedSource>,
+    /// Source text of every parsed file, used to place import diagnostics
+    sources: HashMap<PathBuf, String>,
+    /// Map of file path -> what its names and exports refer to
+    scopes: HashMap<PathBuf, ModuleScope>,
+    /// Project settings; aliases rewrite import paths
+    config: ProjectConfig,
     /// Root directory for resolving relative imports
     root_dir: PathBuf,
 }
@@ -19,108 +59,202 @@ impl ModuleResolver {
     pub fn new(root_dir: PathBuf) -> Self {
         Self {
             modules: HashMap::new(),
-            processing: HashSet::new(),
+            dependencies: HashMap::new(),
+            processing: Vec::new(),
+            parsed: HashMap::new(),
+            sources: HashMap::new(),
+            scopes: HashMap::new(),
+            config: ProjectConfig::default(),
             root_dir,
         }
     }
 
+    /// Resolve aliased imports (`@shared/...`) using rel.json settings
+    pub fn with_config(mut self, config: &ProjectConfig) -> Self {
+        self.config = config.clone();
+        self
+    }
+
+    /// Parse `files` on up to `jobs` threads ahead of `resolve_dependencies`,
+    /// which then only parses imports that were not in the list
+    pub fn preload(&mut self, files: &[PathBuf], jobs: usize) {
+        let pending: Vec<PathBuf> = files.iter()
+            .filter_map(|file| self.canonicalize_path(file).ok())
+            .filter(|path| !self.modules.contains_key(path) && !self.parsed.contains_key(path))
+            .collect();
+
+        let results = parallel::map(&pending, jobs, |path| parse_source(path));
+        self.parsed.extend(pending.into_iter().zip(results));
+    }
+
+    /// Forget `changed` files (or everything under changed directories) and
+    /// every resolution that went through them. Other parsed modules are kept,
+    /// so a long-running build only re-reads what was edited.
+    pub fn invalidate(&mut self, changed: &[PathBuf]) {
+        let changed: Vec<PathBuf> = changed.iter()
+            .map(|path| self.canonicalize_path(path).unwrap_or_else(|_| path.clone()))
+            .collect();
+        let is_changed = |path: &Path| changed.iter().any(|c| path.starts_with(c));
+
+        self.modules.retain(|path, _| !is_changed(path));
+        self.sources.retain(|path, _| !is_changed(path));
+        self.parsed.retain(|path, _| !is_changed(path));
+        self.dependencies.retain(|path, dependencies| !is_changed(path) && !dependencies.iter().any(|d| is_changed(d)));
+        let dependencies = &self.dependencies;
+        self.scopes.retain(|path, _| dependencies.contains_key(path));
+    }
+
     /// Resolve all dependencies for a given file
-    pub fn resolve_dependencies(&mut self, file_path: &Path) -> Result<Vec<PathBuf>, String> {
-        let canonical_path = self.canonicalize_path(file_path)?;
-        
-        // Check for circular dependencies
-        if self.processing.contains(&canonical_path) {
-            return Err(format!("Circular dependency detected: {:?}", canonical_path));
-        }
+    pub fn resolve_dependencies(&mut self, file_path: &Path) -> Result<Vec<PathBuf>, Vec<Diagnostic>> {
+        let canonical_path = self.canonicalize_path(file_path)
+            .map_err(|e| vec![Diagnostic::error(DiagnosticCode::FileReadError, e).with_file(file_path)])?;
 
         // If already processed, return cached result
-        if self.modules.contains_key(&canonical_path) {
-            return Ok(vec![canonical_path]);
+        if let Some(dependencies) = self.dependencies.get(&canonical_path) {
+            return Ok(dependencies.clone());
         }
 
-        self.processing.insert(canonical_path.clone());
+        self.processing.push(canonical_path.clone());
+
+        // Parse the file, unless an earlier build already did and it is unchanged
+        let parsed = match self.modules.get(&canonical_path) {
+            Some(ast) => Ok(ast.clone()),
+            None => self.parse_file(&canonical_path),
+        };
+        let ast = match parsed {
+            Ok(ast) => ast,
+            Err(diagnostics) => {
+                self.processing.pop();
+                return Err(diagnostics);
+            }
+        };
 
-        // Parse the file
-        let ast = self.parse_file(&canonical_path)?;
-        
         // Find all imports
         let imports = self.extract_imports(&ast);
-        
-        // Resolve each import recursively first
-        let mut import_paths = Vec::new();
+
+        // Resolve each import recursively first, reporting every broken import at once
+        let mut diagnostics = Vec::new();
         let mut dependencies = Vec::new();
         for import in &imports {
-            let import_path = self.resolve_import_path(&canonical_path, &import.path)?;
-            import_paths.push(import_path.clone());
-            let sub_deps = self.resolve_dependencies(&import_path)?;
-            
-            // Add sub-dependencies first (topological order)
-            for dep in sub_deps {
-                if !dependencies.contains(&dep) {
-                    dependencies.push(dep);
+            let import_path = match self.resolve_import_path(&canonical_path, &import.path) {
+                Ok(path) => path,
+                Err(e) => {
+                    let tried: Vec<String> = self.import_candidates(&canonical_path, &import.path).iter()
+                        .map(|candidate| format!("\n    {}", candidate.display()))
+                        .collect();
+                    diagnostics.push(
+                        Diagnostic::error(DiagnosticCode::ModuleNotFound, e)
+                            .with_file(&canonical_path)
+                            .with_label(self.path_span(&canonical_path, import), "imported here")
+                            .with_help(format!("looked for it at:{}", tried.concat()))
+                    );
+                    continue;
+                }
+            };
+
+            // Circular dependencies are reported at the import that closes the cycle
+            if let Some(cycle_start) = self.processing.iter().position(|p| p == &import_path) {
+                let chain: Vec<String> = self.processing[cycle_start..].iter()
+                    .chain(std::iter::once(&import_path))
+                    .map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default())
+                    .collect();
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticCode::CircularDependency, format!("Circular dependency on '{}'", import.path))
+                        .with_file(&canonical_path)
+                        .with_label(self.path_span(&canonical_path, import), "this import closes the cycle")
+                        .with_help(format!("import chain: {}", chain.join(" -> ")))
+                );
+                continue;
+            }
+
+            match self.resolve_dependencies(&import_path) {
+                Ok(sub_deps) => {
+                    // Add sub-dependencies first (topological order)
+                    for dep in sub_deps {
+                        if !dependencies.contains(&dep) {
+                            dependencies.push(dep);
+                        }
+                    }
                 }
+                Err(sub_diagnostics) => diagnostics.extend(sub_diagnostics),
             }
         }
-        
+
         // Verify imports after all dependencies are resolved
-        self.verify_imports(&canonical_path, &imports)?;
+        let mut scope = None;
+        if diagnostics.is_empty() {
+            diagnostics.extend(self.verify_imports(&canonical_path, &ast, &imports));
+            let (module_scope, conflicts) = self.module_scope(&canonical_path, &ast);
+            diagnostics.extend(conflicts);
+            scope = Some(module_scope);
+        }
+
+        self.processing.pop();
+        if !diagnostics.is_empty() {
+            return Err(diagnostics);
+        }
 
         // Add current file last
         dependencies.push(canonical_path.clone());
-        
+
         // Store parsed AST
-        self.modules.insert(canonical_path.clone(), ast);
-        self.processing.remove(&canonical_path);
+        self.dependencies.insert(canonical_path.clone(), dependencies.clone());
+        self.scopes.insert(canonical_path.clone(), scope.unwrap_or_default());
+        self.modules.insert(canonical_path, ast);
 
         Ok(dependencies)
     }
 
     /// Get the merged AST for all dependencies in correct order
     /// Only includes schemas that are in the export chain
-    pub fn get_merged_ast(&self, dependencies: &[PathBuf], main_file: &Path) -> Result<Vec<ASTNode>, String> {
+    pub fn get_merged_ast(&self, dependencies: &[PathBuf], main_file: &Path) -> Result<Vec<ASTNode>, Vec<Diagnostic>> {
         let mut merged = Vec::new();
         let mut seen_schemas = HashSet::new();
-        let mut required_schemas = HashSet::new();
 
         // Canonicalize main_file path to match what's in modules
         let main_file_canonical = self.canonicalize_path(main_file)
-            .map_err(|e| format!("Cannot canonicalize main file: {}", e))?;
-
-        // Get the main file's exports (what we actually want to export)
-        if let Some(main_ast) = self.modules.get(&main_file_canonical) {
-            for node in main_ast {
-                if let ASTNode::Export(export) = node {
-                    for name in &export.items {
-                        required_schemas.insert(name.clone());
-                    }
-                }
-            }
-        }
-        
+            .map_err(|e| vec![Diagnostic::error(DiagnosticCode::FileReadError, e).with_file(main_file)])?;
+
+        // Every module with the names it uses rewritten to their merged names,
+        // so aliased, namespaced and identically named imports stay distinct
+        let names = self.merged_names(dependencies, &main_file_canonical);
+        let modules: Vec<(&PathBuf, Vec<ASTNode>)> = dependencies.iter()
+            .filter_map(|dep| {
+                let ast = self.modules.get(dep)?;
+                let renames: Renames = self.scopes.get(dep)?.names.iter()
+                    .filter_map(|(name, origin)| names.get(origin).map(|merged| (name.clone(), merged.clone())))
+                    .collect();
+                Some((dep, ast.iter().cloned().map(|mut node| { rename_node(&mut node, &renames); node }).collect()))
+            })
+            .collect();
+
+        // The main file's exports, re-exports included, are what we actually want to export
+        let mut exports: Vec<String> = self.scopes.get(&main_file_canonical)
+            .map(|scope| scope.exports.values().filter_map(|origin| names.get(origin).cloned()).collect())
+            .unwrap_or_default();
+        exports.sort();
+        let mut required_schemas: HashSet<String> = exports.iter().cloned().collect();
 
         // Build dependency graph: which schemas depend on which imported types
         let mut schema_dependencies: HashMap<String, HashSet<String>> = HashMap::new();
-        
-        for dep in dependencies {
-            if let Some(ast) = self.modules.get(dep) {
-                // Analyze imports/exports and check for unused imports
-                let tracker = analyze_imports_exports(ast)
-                    .map_err(|e| format!("Error in {:?}: {}", dep, e))?;
-
-                for node in ast {
-                    match node {
-                        ASTNode::Schema(schema) => {
-                            let mut deps = HashSet::new();
-                            
-                            // Track what this schema depends on
-                            for field in &schema.fields {
-                                self.collect_type_dependencies(&field.field_type, &mut deps);
-                            }
-                            
-                            schema_dependencies.insert(schema.name.clone(), deps);
+
+        for (_, ast) in &modules {
+            for node in ast {
+                let mut deps = HashSet::new();
+                match node {
+                    ASTNode::Schema(schema) => {
+                        // Track what this schema depends on
+                        for field in &schema.fields {
+                            self.collect_type_dependencies(&field.field_type, &mut deps);
                         }
-                        _ => {}
+                        schema_dependencies.insert(schema.name.clone(), deps);
                     }
+                    // `type Audited = User & Timestamps` needs both schemas to merge them
+                    ASTNode::TypeAlias(alias) => {
+                        self.collect_type_dependencies(&alias.type_definition, &mut deps);
+                        schema_dependencies.insert(alias.name.clone(), deps);
+                    }
+                    _ => {}
                 }
             }
         }
@@ -139,41 +273,189 @@ impl ModuleResolver {
         }
 
         // Now collect only the required schemas
-        for dep in dependencies {
-            if let Some(ast) = self.modules.get(dep) {
-                let is_main_file = dep == &main_file_canonical;
-                
-                for node in ast {
-                    match node {
-                        ASTNode::Schema(schema) => {
-                            // Only include if it's required
-                            if required_schemas.contains(&schema.name) && !seen_schemas.contains(&schema.name) {
-                                seen_schemas.insert(schema.name.clone());
-                                merged.push(node.clone());
-                            }
-                        }
-                        ASTNode::Enum(enum_node) => {
-                            if required_schemas.contains(&enum_node.name) && !seen_schemas.contains(&enum_node.name) {
-                                seen_schemas.insert(enum_node.name.clone());
-                                merged.push(node.clone());
-                            }
+        for (_, ast) in modules {
+            for node in ast {
+                match &node {
+                    ASTNode::Schema(schema) => {
+                        // Only include if it's required
+                        if required_schemas.contains(&schema.name) && seen_schemas.insert(schema.name.clone()) {
+                            merged.push(node);
                         }
-                        // Only include exports from the main file
-                        ASTNode::Export(export) if is_main_file => {
-                            merged.push(node.clone());
+                    }
+                    ASTNode::Enum(enum_node) => {
+                        if required_schemas.contains(&enum_node.name) && seen_schemas.insert(enum_node.name.clone()) {
+                            merged.push(node);
                         }
-                        // Skip imports and exports from dependencies
-                        ASTNode::Import(_) | ASTNode::Export(_) => {}
-                        // Include other nodes
-                        _ => merged.push(node.clone()),
                     }
+                    // Imports are resolved, and exports are replaced by the main file's below
+                    ASTNode::Import(_) | ASTNode::Export(_) => {}
+                    // Include other nodes
+                    _ => merged.push(node),
                 }
             }
         }
 
+        if !exports.is_empty() {
+            merged.push(ASTNode::Export(ExportNode::new(exports)));
+        }
+
+        // Object types built from imported ones can only be checked now; the
+        // main file's own are reported here, its dependencies' in their builds
+        let conflicts: Vec<Diagnostic> = intersections::check_object_types(&merged).into_iter()
+            .filter(|d| d.file.as_deref() == Some(main_file_canonical.as_path()))
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+
         Ok(merged)
     }
 
+    /// The name each declaration gets in the merged AST. Whatever the main
+    /// file exports gets its exported name, and its other declarations keep
+    /// theirs; a dependency's declaration whose name is already taken gets a
+    /// numbered one (`User2`), since imports are inlined anyway.
+    fn merged_names(&self, dependencies: &[PathBuf], main_file: &Path) -> HashMap<Origin, String> {
+        let mut names: HashMap<Origin, String> = HashMap::new();
+        let mut taken = HashSet::new();
+
+        if let Some(scope) = self.scopes.get(main_file) {
+            let mut exports: Vec<(&String, &Origin)> = scope.exports.iter().collect();
+            exports.sort();
+            for (exported, origin) in exports {
+                if !names.contains_key(origin) && taken.insert(exported.clone()) {
+                    names.insert(origin.clone(), exported.clone());
+                }
+            }
+        }
+
+        let files = std::iter::once(main_file).chain(dependencies.iter().map(PathBuf::as_path).filter(|dep| *dep != main_file));
+        for file in files {
+            let Some(ast) = self.modules.get(file) else { continue };
+            for name in ast.iter().filter_map(declared_name) {
+                let origin = (file.to_path_buf(), name.to_string());
+                if names.contains_key(&origin) {
+                    continue;
+                }
+                let mut merged = name.to_string();
+                let mut suffix = 1;
+                while !taken.insert(merged.clone()) {
+                    suffix += 1;
+                    merged = format!("{}{}", name, suffix);
+                }
+                names.insert(origin, merged);
+            }
+        }
+
+        names
+    }
+
+    /// Work out what the names and exports of `file` refer to; its imports
+    /// are already resolved. Explicit exports win over `export *`; two star
+    /// exports offering different declarations under one name conflict.
+    fn module_scope(&self, file: &Path, ast: &[ASTNode]) -> (ModuleScope, Vec<Diagnostic>) {
+        let mut scope = ModuleScope::default();
+        let mut diagnostics = Vec::new();
+        let target = |path: &str| self.resolve_import_path(file, path).ok().and_then(|target| self.scopes.get(&target));
+
+        for name in ast.iter().filter_map(declared_name) {
+            scope.names.entry(name.to_string()).or_insert_with(|| (file.to_path_buf(), name.to_string()));
+        }
+
+        for node in ast {
+            let ASTNode::Import(import) = node else { continue };
+            let Some(imported) = target(&import.path) else { continue };
+            match &import.alias {
+                Some(namespace) => {
+                    for (name, origin) in &imported.exports {
+                        scope.names.entry(format!("{}.{}", namespace, name)).or_insert_with(|| origin.clone());
+                    }
+                }
+                None => {
+                    for item in &import.items {
+                        if let Some(origin) = imported.exports.get(&item.name) {
+                            scope.names.entry(item.local_name().to_string()).or_insert_with(|| origin.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let exports: Vec<&ExportNode> = ast.iter()
+            .filter_map(|node| match node {
+                ASTNode::Export(export) => Some(export),
+                _ => None,
+            })
+            .collect();
+
+        for export in exports.iter().filter(|export| !export.all) {
+            for item in &export.items {
+                let origin = match &export.from {
+                    Some(path) => target(path).and_then(|module| module.exports.get(item)).cloned(),
+                    // Functions and variables are exported by name too
+                    None => Some(scope.names.get(item).cloned().unwrap_or_else(|| (file.to_path_buf(), item.clone()))),
+                };
+                if let Some(origin) = origin {
+                    scope.exports.insert(item.clone(), origin);
+                }
+            }
+        }
+
+        // Name -> the module whose `export *` provided it
+        let mut starred: HashMap<String, &str> = HashMap::new();
+        for export in exports.iter().filter(|export| export.all) {
+            let Some(path) = &export.from else { continue };
+            let Some(module) = target(path) else { continue };
+            let mut names: Vec<(&String, &Origin)> = module.exports.iter().collect();
+            names.sort();
+
+            for (name, origin) in names {
+                match scope.exports.get(name) {
+                    None => {
+                        scope.exports.insert(name.clone(), origin.clone());
+                        starred.insert(name.clone(), path);
+                    }
+                    Some(existing) if existing == origin => {}
+                    Some(_) => {
+                        let Some(first) = starred.get(name) else { continue };
+                        let label = match export.as_import() {
+                            Some(import) => self.path_span(file, &import),
+                            None => Span::new(export.location.line, export.location.column, "export".len()),
+                        };
+                        diagnostics.push(
+                            Diagnostic::error(DiagnosticCode::ExportConflict, format!("'{}' is exported by both '{}' and '{}'", name, first, path))
+                                .with_file(file)
+                                .with_label(label, format!("'{}' conflicts with the one from '{}'", name, first))
+                                .with_help(format!("choose one explicitly: export {{ {} }} from \"{}\"", name, first))
+                        );
+                    }
+                }
+            }
+        }
+
+        (scope, diagnostics)
+    }
+
+    /// Lint one resolved file against its own source, at the levels set in
+    /// rel.json; `merged` is its merged AST, where records and unions of
+    /// imported schemas are checked too
+    pub fn lint(&self, file: &Path, merged: &[ASTNode]) -> Vec<Diagnostic> {
+        let (Some(ast), Some(source)) = (self.modules.get(file), self.sources.get(file)) else {
+            return Vec::new();
+        };
+        let mut diagnostics = lint::check(ast);
+        for diagnostic in records::check_records(merged).into_iter().chain(unions::check_discriminated_unions(merged)) {
+            let seen = diagnostics.iter().any(|d| d.code == diagnostic.code && d.primary == diagnostic.primary);
+            if diagnostic.file.as_deref() == Some(file) && !seen {
+                diagnostics.push(diagnostic);
+            }
+        }
+        self.config.apply_lint(lint::suppress(diagnostics, source))
+            .into_iter()
+            .map(|d| d.or_file(file))
+            .collect()
+    }
+
     /// Collect type dependencies (imported types used in a type definition)
     fn collect_type_dependencies(&self, type_node: &crate::ast::TypeNode, deps: &mut HashSet<String>) {
         use crate::ast::TypeNode;
@@ -196,6 +478,20 @@ impl ModuleResolver {
                     self.collect_type_dependencies(arg, deps);
                 }
             }
+            TypeNode::Record { key, value } => {
+                self.collect_type_dependencies(key, deps);
+                self.collect_type_dependencies(value, deps);
+            }
+            TypeNode::Tuple(elements) => {
+                for element in elements {
+                    self.collect_type_dependencies(&element.element_type, deps);
+                }
+            }
+            TypeNode::Intersection(members) => {
+                for member in members {
+                    self.collect_type_dependencies(member, deps);
+                }
+            }
             TypeNode::Constrained { base_type, .. } => {
                 self.collect_type_dependencies(base_type, deps);
             }
@@ -214,79 +510,178 @@ impl ModuleResolver {
         }
     }
 
-    fn parse_file(&self, path: &Path) -> Result<Vec<ASTNode>, String> {
-        let content = fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read file {:?}: {}", path, e))?;
-
-        let lexer = Lexer::new(&content);
-        let tokens = lexer.tokenize()
-            .map_err(|e| format!("Lexer error in {:?}: {:?}", path, e))?;
-
-        let mut parser = Parser::new(tokens);
-        let ast = parser.parse()
-            .map_err(|e| format!("Parser error in {:?}: {:?}", path, e))?;
-
+    fn parse_file(&mut self, path: &Path) -> Result<Vec<ASTNode>, Vec<Diagnostic>> {
+        let (content, ast) = self.parsed.remove(path).unwrap_or_else(|| parse_source(path))?;
+        self.sources.insert(path.to_path_buf(), content);
         Ok(ast)
     }
 
+    /// Imports, and the re-exports that act as imports of the modules they name
     fn extract_imports(&self, ast: &[ASTNode]) -> Vec<ImportNode> {
         ast.iter()
-            .filter_map(|node| {
-                if let ASTNode::Import(import) = node {
-                    Some(import.clone())
-                } else {
-                    None
-                }
+            .filter_map(|node| match node {
+                ASTNode::Import(import) => Some(import.clone()),
+                ASTNode::Export(export) => export.as_import(),
+                _ => None,
             })
             .collect()
     }
 
-    /// Verify that imported items are actually exported by the source file
-    fn verify_imports(&self, file_path: &Path, imports: &[ImportNode]) -> Result<(), String> {
+    /// Verify that imported items, and names used through a namespace import,
+    /// are actually exported by the source file
+    fn verify_imports(&self, file_path: &Path, ast: &[ASTNode], imports: &[ImportNode]) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
         for import in imports {
-            let import_path = self.resolve_import_path(file_path, &import.path)?;
-            
-            // Get the exports from the imported file
-            if let Some(imported_ast) = self.modules.get(&import_path) {
-                let mut exported_names = HashSet::new();
-                
-                for node in imported_ast {
-                    if let ASTNode::Export(export) = node {
-                        for name in &export.items {
-                            exported_names.insert(name.clone());
+            let Ok(import_path) = self.resolve_import_path(file_path, &import.path) else {
+                continue;
+            };
+
+            // Get the exports from the imported file, following its re-exports
+            if let Some(imported) = self.scopes.get(&import_path) {
+                let mut exported_names: Vec<String> = imported.exports.keys().cloned().collect();
+                exported_names.sort();
+
+                let help_for = |item: &str| match diagnostics::did_you_mean(item, exported_names.iter().map(String::as_str)) {
+                    Some(suggestion) => format!("did you mean '{}'?", suggestion),
+                    None if exported_names.is_empty() => format!("'{}' has no exports", import.path),
+                    None => format!("available exports: {}", exported_names.join(", ")),
+                };
+
+                // `billing.Invoice` is reported where it is used
+                if let Some(namespace) = &import.alias {
+                    for (name, location) in qualified_references(ast) {
+                        let Some(item) = name.strip_prefix(namespace.as_str()).and_then(|rest| rest.strip_prefix('.')) else {
+                            continue;
+                        };
+                        if !exported_names.iter().any(|exported| exported == item) {
+                            diagnostics.push(
+                                Diagnostic::error(DiagnosticCode::NotExported, format!("'{}' is not exported by '{}'", item, import.path))
+                                    .with_file(file_path)
+                                    .with_location(&location, format!("'{}' is used here", name))
+                                    .with_help(help_for(item))
+                            );
                         }
                     }
                 }
-                
+
                 // Check each imported item
                 for item in &import.items {
-                    if !exported_names.contains(item) {
-                        return Err(format!(
-                            "No module '{}' exported in '{}'\nAvailable exports: {}",
-                            item,
-                            import.path,
-                            if exported_names.is_empty() {
-                                "none".to_string()
-                            } else {
-                                exported_names.iter().cloned().collect::<Vec<_>>().join(", ")
-                            }
-                        ));
+                    let item = item.name.as_str();
+                    if exported_names.iter().any(|exported| exported == item) {
+                        continue;
                     }
+
+                    diagnostics.push(
+                        Diagnostic::error(DiagnosticCode::NotExported, format!("'{}' is not exported by '{}'", item, import.path))
+                            .with_file(file_path)
+                            .with_label(self.item_span(file_path, import, item), "not exported")
+                            .with_help(help_for(item))
+                    );
                 }
             }
         }
-        Ok(())
+
+        diagnostics
     }
 
-    fn resolve_import_path(&self, current_file: &Path, import_path: &str) -> Result<PathBuf, String> {
-        // Get the directory of the current file
-        let current_dir = current_file.parent()
-            .ok_or_else(|| format!("Cannot get parent directory of {:?}", current_file))?;
+    /// Span of an imported name, searched for on the import's own line
+    fn item_span(&self, file_path: &Path, import: &ImportNode, item: &str) -> Span {
+        self.find_on_import_line(file_path, import, |line, from| {
+            line[from..].match_indices(item)
+                .map(|(i, _)| from + i)
+                .find(|&i| {
+                    let before = line[..i].chars().next_back();
+                    let after = line[i + item.len()..].chars().next();
+                    !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                        && !after.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                })
+                .map(|i| (i, item.len()))
+        })
+    }
 
-        // Resolve relative path
-        let resolved = current_dir.join(import_path);
-        
-        self.canonicalize_path(&resolved)
+    /// Span of the quoted module path in an import
+    fn path_span(&self, file_path: &Path, import: &ImportNode) -> Span {
+        self.find_on_import_line(file_path, import, |line, from| {
+            ['"', '\''].iter()
+                .find_map(|q| line[from..].find(&format!("{}{}{}", q, import.path, q)))
+                .map(|i| (from + i, import.path.len() + 2))
+        })
+    }
+
+    /// Apply `find` to the import's line (from the `import` keyword on) and turn the
+    /// byte offset it returns into a span, falling back to the keyword itself
+    fn find_on_import_line(
+        &self,
+        file_path: &Path,
+        import: &ImportNode,
+        find: impl Fn(&str, usize) -> Option<(usize, usize)>,
+    ) -> Span {
+        let location = &import.location;
+        let fallback = Span::new(location.line, location.column, "import".len());
+
+        let Some(line) = self.sources.get(file_path).and_then(|s| s.lines().nth(location.line.wrapping_sub(1))) else {
+            return fallback;
+        };
+        let from = line.char_indices().nth(location.column.saturating_sub(1)).map_or(line.len(), |(i, _)| i);
+
+        match find(line, from) {
+            Some((offset, length)) => Span::new(location.line, line[..offset].chars().count() + 1, length),
+            None => fallback,
+        }
+    }
+
+    /// The file an import refers to: the first of `import_candidates` that exists
+    pub fn resolve_import_path(&self, current_file: &Path, import_path: &str) -> Result<PathBuf, String> {
+        self.import_candidates(current_file, import_path)
+            .into_iter()
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| format!("Cannot find module '{}'", import_path))
+    }
+
+    /// Every location an import may refer to, in lookup order. Relative paths
+    /// (`./x.rel`) and aliases name one place; package-style paths
+    /// (`@acme/common/money`) are looked up next to the importing file, in each
+    /// search path from rel.json, then in `rel_modules/` directories from the
+    /// importing file up to the project root. Paths without `.rel` also match
+    /// `<path>.rel` and `<path>/index.rel`.
+    pub fn import_candidates(&self, current_file: &Path, import_path: &str) -> Vec<PathBuf> {
+        let current_dir = current_file.parent().unwrap_or(Path::new(""));
+        let is_relative = import_path.starts_with("./") || import_path.starts_with("../") || Path::new(import_path).is_absolute();
+
+        let bases: Vec<PathBuf> = if let Some(aliased) = self.config.resolve_alias(import_path) {
+            vec![aliased]
+        } else if is_relative {
+            vec![current_dir.join(import_path)]
+        } else {
+            let project_root = self.config.path.as_ref().map(|_| self.config.root.as_path());
+            let module_dirs = current_dir.ancestors()
+                .take_while(|dir| project_root.is_none_or(|root| dir.starts_with(root)))
+                .map(|dir| dir.join(MODULES_DIR).join(import_path));
+
+            std::iter::once(current_dir.join(import_path))
+                .chain(self.config.paths.iter().map(|dir| dir.join(import_path)))
+                .chain(module_dirs)
+                .collect()
+        };
+
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        for base in bases {
+            let variants = if base.extension().is_some_and(|ext| ext == "rel") {
+                vec![base]
+            } else {
+                let mut with_extension = base.clone().into_os_string();
+                with_extension.push(".rel");
+                vec![PathBuf::from(with_extension), base.join("index.rel")]
+            };
+            for variant in variants {
+                let candidate = self.canonicalize_path(&variant).unwrap_or(variant);
+                if !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+        candidates
     }
 
     fn canonicalize_path(&self, path: &Path) -> Result<PathBuf, String> {
@@ -311,6 +706,207 @@ impl ModuleResolver {
     }
 }
 
+/// Read and parse one file, returning its source text with the AST
+fn parse_source(path: &Path) -> ParsedSource {
+    let content = fs::read_to_string(path).map_err(|e| {
+        vec![Diagnostic::error(DiagnosticCode::FileReadError, format!("Failed to read file: {}", e)).with_file(path)]
+    })?;
+
+    let lexer = Lexer::new(&content);
+    let tokens = lexer.tokenize()
+        .map_err(|errors| errors.iter().map(|e| Diagnostic::from(e).with_file(path)).collect::<Vec<_>>())?;
+
+    let mut parser = Parser::new(tokens);
+    let mut ast = parser.parse()
+        .map_err(|errors| errors.iter().map(|e| Diagnostic::from(e).with_file(path)).collect::<Vec<_>>())?;
+
+    // Later phases work on merged ASTs, so every location remembers its file
+    let file = path.display().to_string();
+    for node in &mut ast {
+        stamp_file(node, &file);
+    }
+
+    Ok((content, ast))
+}
+
+/// Record `file` on every location in a top-level node
+fn stamp_file(node: &mut ASTNode, file: &str) {
+    let stamp = |location: &mut SourceLocation| location.file_path = Some(file.to_string());
+    match node {
+        ASTNode::Schema(schema) => {
+            stamp(&mut schema.location);
+            stamp_fields(&mut schema.fields, file);
+        }
+        ASTNode::Import(import) => stamp(&mut import.location),
+        ASTNode::Enum(enum_node) => stamp(&mut enum_node.location),
+        ASTNode::TypeAlias(alias) => {
+            stamp(&mut alias.location);
+            stamp_type(&mut alias.type_definition, file);
+        }
+        _ => {}
+    }
+}
+
+fn stamp_fields(fields: &mut [FieldNode], file: &str) {
+    for field in fields {
+        field.location.file_path = Some(file.to_string());
+        stamp_type(&mut field.field_type, file);
+        for conditional in &mut field.conditionals {
+            stamp_fields(&mut conditional.then_fields, file);
+            stamp_fields(&mut conditional.else_fields, file);
+        }
+    }
+}
+
+fn stamp_type(type_node: &mut TypeNode, file: &str) {
+    match type_node {
+        TypeNode::InlineObject(fields) => stamp_fields(fields, file),
+        TypeNode::Array(inner) | TypeNode::Record { value: inner, .. } => stamp_type(inner, file),
+        TypeNode::Tuple(elements) => elements.iter_mut().for_each(|element| stamp_type(&mut element.element_type, file)),
+        TypeNode::Union(types) | TypeNode::Intersection(types) => types.iter_mut().for_each(|t| stamp_type(t, file)),
+        _ => {}
+    }
+}
+
+/// The name a top-level declaration introduces
+fn declared_name(node: &ASTNode) -> Option<&str> {
+    match node {
+        ASTNode::Schema(schema) => Some(&schema.name),
+        ASTNode::Enum(enum_node) => Some(&enum_node.name),
+        ASTNode::TypeAlias(alias) => Some(&alias.name),
+        ASTNode::Mixin(mixin) => Some(&mixin.name),
+        ASTNode::DeclareType(declare_type) => Some(&declare_type.name),
+        _ => None,
+    }
+}
+
+/// Every `ns.Type` name in a file's schemas and type aliases, with where it is used
+fn qualified_references(ast: &[ASTNode]) -> Vec<(String, SourceLocation)> {
+    fn collect(fields: &[FieldNode], references: &mut Vec<(String, SourceLocation)>) {
+        for field in fields {
+            let mut names = Vec::new();
+            type_names(&field.field_type, &mut names);
+            references.extend(names.into_iter().filter(|name| name.contains('.')).map(|name| (name, field.location.clone())));
+            for conditional in &field.conditionals {
+                collect(&conditional.then_fields, references);
+                collect(&conditional.else_fields, references);
+            }
+        }
+    }
+
+    let mut references = Vec::new();
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) => collect(&schema.fields, &mut references),
+            ASTNode::Mixin(mixin) => collect(&mixin.fields, &mut references),
+            ASTNode::TypeAlias(alias) => {
+                let mut names = Vec::new();
+                type_names(&alias.type_definition, &mut names);
+                references.extend(names.into_iter().filter(|name| name.contains('.')).map(|name| (name, alias.location.clone())));
+            }
+            _ => {}
+        }
+    }
+    references
+}
+
+/// Every type name referenced by a type, nested object fields included
+fn type_names(type_node: &TypeNode, names: &mut Vec<String>) {
+    match type_node {
+        TypeNode::Identifier(name) => names.push(name.clone()),
+        TypeNode::Generic(name, args) => {
+            names.push(name.clone());
+            args.iter().for_each(|arg| type_names(arg, names));
+        }
+        TypeNode::Array(inner) => type_names(inner, names),
+        TypeNode::Record { key, value } => {
+            type_names(key, names);
+            type_names(value, names);
+        }
+        TypeNode::Tuple(elements) => elements.iter().for_each(|element| type_names(&element.element_type, names)),
+        TypeNode::Union(types) | TypeNode::Intersection(types) => types.iter().for_each(|t| type_names(t, names)),
+        TypeNode::Constrained { base_type, .. } => type_names(base_type, names),
+        TypeNode::Conditional(cond) => {
+            type_names(&cond.then_value, names);
+            if let Some(else_value) = &cond.else_value {
+                type_names(else_value, names);
+            }
+        }
+        TypeNode::InlineObject(fields) => fields.iter().for_each(|field| type_names(&field.field_type, names)),
+        _ => {}
+    }
+}
+
+/// Rewrite the names a top-level node declares and refers to through `scope`
+fn rename_node(node: &mut ASTNode, scope: &Renames) {
+    let rename = |name: &mut String| {
+        if let Some(merged) = scope.get(name.as_str()) {
+            *name = merged.clone();
+        }
+    };
+    match node {
+        ASTNode::Schema(schema) => {
+            rename(&mut schema.name);
+            schema.extends.iter_mut().for_each(rename);
+            schema.mixins.iter_mut().for_each(rename);
+            rename_fields(&mut schema.fields, scope);
+        }
+        ASTNode::Enum(enum_node) => rename(&mut enum_node.name),
+        ASTNode::TypeAlias(alias) => {
+            rename(&mut alias.name);
+            rename_type(&mut alias.type_definition, scope);
+        }
+        ASTNode::Mixin(mixin) => {
+            rename(&mut mixin.name);
+            rename_fields(&mut mixin.fields, scope);
+        }
+        ASTNode::DeclareType(declare_type) => {
+            rename(&mut declare_type.name);
+            rename_type(&mut declare_type.type_def, scope);
+        }
+        _ => {}
+    }
+}
+
+fn rename_fields(fields: &mut [FieldNode], scope: &Renames) {
+    for field in fields {
+        rename_type(&mut field.field_type, scope);
+        for conditional in &mut field.conditionals {
+            rename_fields(&mut conditional.then_fields, scope);
+            rename_fields(&mut conditional.else_fields, scope);
+        }
+    }
+}
+
+fn rename_type(type_node: &mut TypeNode, scope: &Renames) {
+    match type_node {
+        TypeNode::Identifier(name) | TypeNode::Generic(name, _) => {
+            if let Some(merged) = scope.get(name.as_str()) {
+                *name = merged.clone();
+            }
+            if let TypeNode::Generic(_, args) = type_node {
+                args.iter_mut().for_each(|arg| rename_type(arg, scope));
+            }
+        }
+        TypeNode::Array(inner) => rename_type(inner, scope),
+        TypeNode::Record { key, value } => {
+            rename_type(key, scope);
+            rename_type(value, scope);
+        }
+        TypeNode::Tuple(elements) => elements.iter_mut().for_each(|element| rename_type(&mut element.element_type, scope)),
+        TypeNode::Union(types) | TypeNode::Intersection(types) => types.iter_mut().for_each(|t| rename_type(t, scope)),
+        TypeNode::Constrained { base_type, .. } => rename_type(base_type, scope),
+        TypeNode::Conditional(cond) => {
+            rename_type(&mut cond.then_value, scope);
+            if let Some(else_value) = &mut cond.else_value {
+                rename_type(else_value, scope);
+            }
+        }
+        TypeNode::InlineObject(fields) => rename_fields(fields, scope),
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -369,4 +965,140 @@ mod tests {
         let mut rec_stack2 = std::collections::HashSet::new();
         assert!(!has_cycle("X", &acyclic_deps, &mut visited2, &mut rec_stack2));
     }
+
+    #[test]
+    fn test_package_imports_search_paths_and_rel_modules() {
+        let dir = std::env::temp_dir().join(format!("rel-resolver-test-{}", std::process::id()));
+        for sub in ["schemas/api", "vendor/lib", "rel_modules/@acme/common"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let dir = dir.canonicalize().unwrap();
+        let importer = dir.join("schemas/api/order.rel");
+        std::fs::write(&importer, "").unwrap();
+        std::fs::write(dir.join("vendor/lib/index.rel"), "").unwrap();
+        std::fs::write(dir.join("rel_modules/@acme/common/money.rel"), "").unwrap();
+
+        let mut config = ProjectConfig::default();
+        config.root = dir.clone();
+        config.path = Some(dir.join("rel.json"));
+        config.paths = vec![dir.join("vendor")];
+        let resolver = ModuleResolver::new(dir.clone()).with_config(&config);
+
+        assert_eq!(resolver.resolve_import_path(&importer, "@acme/common/money").unwrap(), dir.join("rel_modules/@acme/common/money.rel"));
+        assert_eq!(resolver.resolve_import_path(&importer, "lib").unwrap(), dir.join("vendor/lib/index.rel"));
+        assert_eq!(resolver.resolve_import_path(&importer, "./order.rel").unwrap(), importer);
+
+        // Not found: every place tried, stopping at the project root
+        assert!(resolver.resolve_import_path(&importer, "@acme/missing").is_err());
+        let tried = resolver.import_candidates(&importer, "@acme/missing");
+        assert_eq!(tried.first(), Some(&dir.join("schemas/api/@acme/missing.rel")));
+        assert!(tried.contains(&dir.join("vendor/@acme/missing/index.rel")));
+        assert!(tried.contains(&dir.join("schemas/rel_modules/@acme/missing.rel")));
+        assert_eq!(tried.last(), Some(&dir.join("rel_modules/@acme/missing/index.rel")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_aliased_and_namespaced_imports_keep_same_named_schemas_apart() {
+        let dir = std::env::temp_dir().join(format!("rel-resolver-alias-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        std::fs::write(dir.join("auth.rel"), "define User {\n  email: string\n}\nexport User\n").unwrap();
+        std::fs::write(dir.join("billing.rel"), "define User {\n  login: string\n}\ndefine Invoice {\n  owner: User\n}\nexport User, Invoice\n").unwrap();
+        let main = dir.join("account.rel");
+        std::fs::write(&main, "import * as billing from \"./billing.rel\"\nimport { User as AuthUser } from \"./auth.rel\"\n\
+            define User {\n  name: string\n}\n\
+            define Account {\n  me: User\n  auth: AuthUser\n  invoice: billing.Invoice\n}\nexport Account\n").unwrap();
+
+        let mut resolver = ModuleResolver::new(dir.clone());
+        let dependencies = resolver.resolve_dependencies(&main).unwrap();
+        let merged = resolver.get_merged_ast(&dependencies, &main).unwrap();
+
+        let schemas: HashMap<&str, &crate::ast::SchemaNode> = merged.iter()
+            .filter_map(|node| match node {
+                ASTNode::Schema(schema) => Some((schema.name.as_str(), schema)),
+                _ => None,
+            })
+            .collect();
+        let field_type = |schema: &str, field: &str| {
+            schemas[schema].fields.iter().find(|f| f.name == field).map(|f| f.field_type.clone()).unwrap()
+        };
+        let fields_of = |type_node: TypeNode| match type_node {
+            TypeNode::Identifier(name) => schemas[name.as_str()].fields[0].name.clone(),
+            other => panic!("expected a schema reference, got {:?}", other),
+        };
+
+        assert_eq!(fields_of(field_type("Account", "me")), "name");
+        assert_eq!(fields_of(field_type("Account", "auth")), "email");
+        let TypeNode::Identifier(invoice) = field_type("Account", "invoice") else { panic!() };
+        assert_eq!(fields_of(field_type(&invoice, "owner")), "login");
+        assert_eq!(schemas.len(), 5);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_types_used_only_in_tuples_are_imported() {
+        let dir = std::env::temp_dir().join(format!("rel-resolver-tuple-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        std::fs::write(dir.join("geo.rel"), "define Point {\n  x: number\n}\ndefine Label {\n  text: string\n}\nexport Point, Label\n").unwrap();
+        let main = dir.join("route.rel");
+        std::fs::write(&main, "import { Point, Label } from \"./geo.rel\"\n\
+            define Route {\n  stops: [Point, ...Label[]]\n}\nexport Route\n").unwrap();
+
+        let mut resolver = ModuleResolver::new(dir.clone());
+        let dependencies = resolver.resolve_dependencies(&main).unwrap();
+        let merged = resolver.get_merged_ast(&dependencies, &main).unwrap();
+
+        let schemas: Vec<&str> = merged.iter()
+            .filter_map(|node| match node {
+                ASTNode::Schema(schema) => Some(schema.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(schemas, vec!["Point", "Label", "Route"]);
+        assert!(resolver.lint(&main, &[]).iter().all(|d| d.code != crate::diagnostics::DiagnosticCode::UnusedImport));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_re_exports_are_followed_and_star_conflicts_reported() {
+        let dir = std::env::temp_dir().join(format!("rel-resolver-reexport-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        std::fs::write(dir.join("models/user.rel"), "define User {\n  id: number\n}\ndefine Secret {\n  key: string\n}\nexport User, Secret\n").unwrap();
+        std::fs::write(dir.join("models/plan.rel"), "define Plan {\n  price: number\n}\nexport Plan\n").unwrap();
+        std::fs::write(dir.join("models/index.rel"), "export { User } from \"./user.rel\"\nexport * from \"./plan.rel\"\n").unwrap();
+        let main = dir.join("account.rel");
+        std::fs::write(&main, "import { User, Plan } from \"./models\"\ndefine Account {\n  user: User\n  plan: Plan\n}\nexport Account\n").unwrap();
+
+        let mut resolver = ModuleResolver::new(dir.clone());
+        let dependencies = resolver.resolve_dependencies(&main).unwrap();
+        let merged = resolver.get_merged_ast(&dependencies, &main).unwrap();
+        let mut schemas: Vec<&str> = merged.iter()
+            .filter_map(|node| match node {
+                ASTNode::Schema(schema) => Some(schema.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        schemas.sort();
+        assert_eq!(schemas, vec!["Account", "Plan", "User"]);
+
+        // The barrel itself exports what it re-exports, and only that
+        let barrel = dir.join("models/index.rel");
+        let dependencies = resolver.resolve_dependencies(&barrel).unwrap();
+        let merged = resolver.get_merged_ast(&dependencies, &barrel).unwrap();
+        assert!(merged.contains(&ASTNode::Export(ExportNode::new(vec!["Plan".to_string(), "User".to_string()]))));
+
+        std::fs::write(dir.join("models/other.rel"), "define Plan {\n  tier: string\n}\nexport Plan\n").unwrap();
+        std::fs::write(dir.join("models/all.rel"), "export * from \"./plan.rel\"\nexport * from \"./other.rel\"\n").unwrap();
+        let diagnostics = resolver.resolve_dependencies(&dir.join("models/all.rel")).unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::ExportConflict);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
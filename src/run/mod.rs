@@ -1,15 +1,16 @@
-/**
+/*!
  * Run Module
  *
  * Handles execution of .rel files with the interpreter.
  */
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::lexer;
 use crate::parser;
 use crate::interpreter;
+use crate::diagnostics::{Diagnostic, DiagnosticsError};
 
 /// Run a .rel file with the interpreter
 pub fn run_file(input: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
@@ -21,32 +22,25 @@ pub fn run_file(input: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     // Tokenize
     let lexer = lexer::Lexer::new(&content);
     let tokens = lexer.tokenize().map_err(|errors| {
-        let error_messages: Vec<String> = errors
-            .iter()
-            .map(|e| format!("Line {}, Col {}: {}", e.line, e.column, e.message))
-            .collect();
-        format!("Tokenization failed:\n{}", error_messages.join("\n"))
+        report(input, "Tokenization failed", errors.iter().map(Diagnostic::from).collect())
     })?;
 
     // Parse
     let mut parser = parser::Parser::new(tokens);
     let ast_nodes = parser.parse().map_err(|errors| {
-        let error_messages: Vec<String> = errors
-            .iter()
-            .map(|e| format!("Line {}, Col {}: {}", e.line, e.column, e.message))
-            .collect();
-        format!("Parsing failed:\n{}", error_messages.join("\n"))
+        report(input, "Parsing failed", errors.iter().map(Diagnostic::from).collect())
     })?;
 
     // Execute
     let mut interpreter = interpreter::Interpreter::new();
     interpreter.execute(&ast_nodes).map_err(|errors| {
-        let error_messages: Vec<String> = errors
-            .iter()
-            .map(|e| format!("Line {}, Col {}: {}", e.line, e.column, e.message))
-            .collect();
-        format!("Runtime error:\n{}", error_messages.join("\n"))
+        report(input, "Runtime error", errors.iter().map(Diagnostic::from).collect())
     })?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Attach `input` to the diagnostics of a failed phase
+fn report(input: &Path, phase: &str, diagnostics: Vec<Diagnostic>) -> DiagnosticsError {
+    DiagnosticsError::new(phase, diagnostics.into_iter().map(|d| d.with_file(input)).collect())
+}
@@ -0,0 +1,344 @@
+/*!
+ * Build Session - one long-running build that rebuilds only what changed
+ *
+ * `rel build` runs a single session build. `rel watch` and `rel build --watch`
+ * keep the session alive, so parsed modules and the import graph carry over
+ * from one rebuild to the next.
+ */
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::compiler::relCompiler;
+use crate::diagnostics::{self, Diagnostic};
+use crate::resolver::ModuleResolver;
+
+// ============================================================================
+// SECTION: Dependency Graph
+// ============================================================================
+
+/// Which files each compiled input imports, directly or transitively,
+/// itself included, and which inputs failed; paths are canonical
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    imports: HashMap<PathBuf, Vec<PathBuf>>,
+    failed: BTreeSet<PathBuf>,
+}
+
+impl DependencyGraph {
+    pub fn record(&mut self, input: &Path, dependencies: Vec<PathBuf>) {
+        self.imports.insert(canonical(input), dependencies);
+    }
+
+    pub fn remove(&mut self, input: &Path) {
+        let input = canonical(input);
+        self.imports.remove(&input);
+        self.failed.remove(&input);
+    }
+
+    /// Every file some input depends on
+    pub fn files(&self) -> BTreeSet<&PathBuf> {
+        self.imports.values().flatten().collect()
+    }
+
+    /// Inputs whose last build failed
+    pub fn failed(&self) -> impl Iterator<Item = &PathBuf> {
+        self.failed.iter()
+    }
+
+    pub fn set_failed(&mut self, input: &Path, failed: bool) {
+        match failed {
+            true => self.failed.insert(canonical(input)),
+            false => self.failed.remove(&canonical(input)),
+        };
+    }
+
+    /// Inputs that depend on any of `changed`, which may name files or directories
+    pub fn affected(&self, changed: &[PathBuf]) -> Vec<PathBuf> {
+        let changed: Vec<PathBuf> = changed.iter().map(|path| canonical(path)).collect();
+        let mut affected: Vec<PathBuf> = self.imports.iter()
+            .filter(|(_, dependencies)| {
+                dependencies.iter().any(|dependency| changed.iter().any(|path| dependency.starts_with(path)))
+            })
+            .map(|(input, _)| input.clone())
+            .collect();
+        affected.sort();
+        affected
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+// ============================================================================
+// SECTION: Summary
+// ============================================================================
+
+/// What one build or rebuild did
+#[derive(Debug, Default)]
+pub struct BuildSummary {
+    pub compiled: usize,
+    pub up_to_date: usize,
+    /// Outputs deleted because their input went away
+    pub removed: usize,
+    /// Warnings, plus errors when the build failed
+    pub diagnostics: Vec<Diagnostic>,
+    pub elapsed: Duration,
+}
+
+impl BuildSummary {
+    pub fn failed(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+}
+
+/// "2 compiled, 5 up to date in 18ms (1 warning)"
+impl fmt::Display for BuildSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} compiled", self.compiled)?;
+        if self.up_to_date > 0 {
+            write!(f, ", {} up to date", self.up_to_date)?;
+        }
+        if self.removed > 0 {
+            write!(f, ", {} removed", self.removed)?;
+        }
+        write!(f, " in {}ms", self.elapsed.as_millis())?;
+        if !self.diagnostics.is_empty() {
+            write!(f, " ({})", diagnostics::summary(&self.diagnostics))?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SECTION: Session
+// ============================================================================
+
+pub struct BuildSession<'a> {
+    compiler: &'a relCompiler,
+    /// Inputs as canonical paths, so they compare equal to watcher events
+    inputs: Vec<PathBuf>,
+    /// Parsed modules, kept between rebuilds and invalidated as files change
+    resolver: ModuleResolver,
+    graph: DependencyGraph,
+    /// The .rel files found by the last scan
+    files: BTreeSet<PathBuf>,
+}
+
+impl<'a> BuildSession<'a> {
+    pub fn new(compiler: &'a relCompiler, resolver: ModuleResolver) -> Self {
+        let inputs = compiler.inputs().iter().map(|input| canonical(input)).collect();
+        Self {
+            compiler,
+            inputs,
+            resolver,
+            graph: DependencyGraph::default(),
+            files: BTreeSet::new(),
+        }
+    }
+
+    /// The inputs this session compiles
+    pub fn inputs(&self) -> &[PathBuf] {
+        &self.inputs
+    }
+
+    /// Build every input; a missing input is an error, failing files are
+    /// reported in the summary
+    pub fn build(&mut self) -> Result<BuildSummary, Box<dyn std::error::Error>> {
+        let start = Instant::now();
+
+        self.files.clear();
+        for input in &self.inputs {
+            self.files.extend(self.compiler.input_files(input)?);
+        }
+
+        let files: Vec<PathBuf> = self.files.iter().cloned().collect();
+        let mut summary = self.compiler.compile_files(&files, &mut self.resolver, &mut self.graph)?;
+        summary.elapsed = start.elapsed();
+        Ok(summary)
+    }
+
+    /// Rebuild after `changed` files or directories were modified, created or
+    /// deleted: new files, their importers, and files that failed last time.
+    /// Returns None when the change affects nothing.
+    pub fn rebuild(&mut self, changed: &[PathBuf]) -> Result<Option<BuildSummary>, Box<dyn std::error::Error>> {
+        let start = Instant::now();
+        let scanned = self.scan();
+        let previous = std::mem::replace(&mut self.files, scanned);
+
+        // A new file may collide with one that is not being rebuilt
+        let all: Vec<PathBuf> = self.files.iter().cloned().collect();
+        self.compiler.check_outputs(&all)?;
+
+        // Deleted or renamed-away inputs; the build prunes their outputs
+        let removed: Vec<PathBuf> = previous.difference(&self.files).cloned().collect();
+        for file in &removed {
+            self.graph.remove(file);
+        }
+
+        let mut stale = changed.to_vec();
+        stale.extend(removed.iter().cloned());
+        self.resolver.invalidate(&stale);
+
+        // A change elsewhere may have fixed a file that failed last time
+        let mut affected: BTreeSet<PathBuf> = self.files.difference(&previous).cloned().collect();
+        affected.extend(self.graph.affected(&stale));
+        affected.extend(self.graph.failed().cloned());
+        affected.retain(|file| self.files.contains(file));
+
+        if affected.is_empty() && removed.is_empty() {
+            return Ok(None);
+        }
+
+        let files: Vec<PathBuf> = affected.into_iter().collect();
+        let mut summary = self.compiler.compile_files(&files, &mut self.resolver, &mut self.graph)?;
+        summary.elapsed = start.elapsed();
+        Ok(Some(summary))
+    }
+
+    /// Directories outside the inputs that hold imported files, such as
+    /// aliased shared schemas; watchers should follow these too
+    pub fn import_dirs(&self) -> BTreeSet<PathBuf> {
+        self.graph.files().into_iter()
+            .filter(|file| !self.inputs.iter().any(|input| file.starts_with(input)))
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect()
+    }
+
+    /// Every .rel file currently under the inputs; unlike `build`, a missing
+    /// input simply has no files
+    fn scan(&self) -> BTreeSet<PathBuf> {
+        let mut files = BTreeSet::new();
+        for input in &self.inputs {
+            if input.is_dir() {
+                files.extend(self.compiler.find_rel_files(input).unwrap_or_default());
+            } else if input.is_file() {
+                files.insert(input.clone());
+            }
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::CompilerOptions;
+
+    #[test]
+    fn test_affected_follows_imports_and_directories() {
+        let mut graph = DependencyGraph::default();
+        graph.record(Path::new("/project/schemas/user.rel"), vec![
+            PathBuf::from("/project/shared/address.rel"),
+            PathBuf::from("/project/schemas/user.rel"),
+        ]);
+        graph.record(Path::new("/project/schemas/tag.rel"), vec![PathBuf::from("/project/schemas/tag.rel")]);
+
+        assert_eq!(graph.affected(&[PathBuf::from("/project/shared/address.rel")]), vec![PathBuf::from("/project/schemas/user.rel")]);
+        assert_eq!(graph.affected(&[PathBuf::from("/project/shared")]), vec![PathBuf::from("/project/schemas/user.rel")]);
+        assert_eq!(graph.affected(&[PathBuf::from("/project/schemas")]).len(), 2);
+        assert!(graph.affected(&[PathBuf::from("/project/other.rel")]).is_empty());
+    }
+
+    fn compiler_for(dir: &Path) -> relCompiler {
+        relCompiler::new(CompilerOptions {
+            input_dir: dir.join("schemas"),
+            output_dir: Some(dir.join("out")),
+            watch: false,
+            diagnostics_format: diagnostics::DiagnosticsFormat::Human,
+            incremental: true,
+            jobs: 1,
+            config: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_cached_files_report_their_warnings_again() {
+        let dir = std::env::temp_dir().join(format!("rel-session-cache-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        // `Draft` is never used, which `unused-schema` warns about by default
+        std::fs::write(dir.join("schemas/user.rel"), "define Draft {\n  id: uuid\n}\ndefine User {\n  id: uuid\n}\nexport User\n").unwrap();
+
+        let compiler = compiler_for(&dir);
+        let first = compiler.session().build().unwrap();
+        assert_eq!((first.compiled, first.up_to_date), (1, 0));
+        assert_eq!(first.diagnostics.len(), 1);
+
+        let second = compiler.session().build().unwrap();
+        assert_eq!((second.compiled, second.up_to_date), (0, 1));
+        assert_eq!(second.diagnostics, first.diagnostics);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_records_of_imported_schemas_are_checked() {
+        let dir = std::env::temp_dir().join(format!("rel-session-records-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        let write = |name: &str, source: &str| std::fs::write(dir.join("schemas").join(name), source).unwrap();
+        write("address.rel", "define Address {\n  city: string\n}\nexport Address\n");
+
+        // An imported schema as a value builds, with a warning that its fields aren't checked
+        write("book.rel", "import { Address } from \"./address.rel\"\ndefine Book {\n  byId: record<string, Address>\n}\nexport Book\n");
+        let summary = compiler_for(&dir).session().build().unwrap();
+        assert!(!summary.failed());
+        let codes: Vec<&str> = summary.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(codes, vec!["REL0306"]);
+        assert!(summary.diagnostics[0].file.as_ref().is_some_and(|file| file.ends_with("book.rel")));
+
+        // As a key, it fails the build
+        write("book.rel", "import { Address } from \"./address.rel\"\ndefine Book {\n  byAddress: record<Address, string>\n}\nexport Book\n");
+        let summary = compiler_for(&dir).session().build().unwrap();
+        assert!(summary.failed());
+        assert!(summary.diagnostics.iter().any(|d| d.code.as_str() == "REL0307"), "{:?}", summary.diagnostics);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_unions_of_imported_schemas_are_checked_and_generated() {
+        let dir = std::env::temp_dir().join(format!("rel-session-unions-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        let write = |name: &str, source: &str| std::fs::write(dir.join("schemas").join(name), source).unwrap();
+        let codes = |summary: &BuildSummary| summary.diagnostics.iter().map(|d| d.code.as_str()).collect::<Vec<_>>();
+        write("card.rel", "define Card {\n  kind: =card\n  number: string\n}\nexport Card\n");
+        write("bank.rel", "define Bank {\n  kind: =bank\n  iban: string\n}\nexport Bank\n");
+        let order = |decorator: &str| format!(
+            "import {{ Card }} from \"./card.rel\"\nimport {{ Bank }} from \"./bank.rel\"\ndefine Order {{\n{}  payment: Card | Bank\n  backup: Card | null\n}}\nexport Order\n",
+            decorator
+        );
+
+        // Imported variants are expanded by their tags, and `Card | null` is
+        // inlined with a warning that null is rejected
+        write("order.rel", &order("  @discriminator(kind)\n"));
+        let summary = compiler_for(&dir).session().build().unwrap();
+        assert!(!summary.failed());
+        assert_eq!(codes(&summary), vec!["REL0306"]);
+        let output = std::fs::read_to_string(dir.join("out/order.ts")).unwrap();
+        assert!(output.contains("kind: \"=card|=bank\","), "{}", output);
+        assert!(output.contains("iban: \"when kind === bank *? string : any?\","), "{}", output);
+        assert!(output.contains("backup: {\n    kind: \"=card\",\n    number: \"string\",\n  },"), "{}", output);
+
+        // Without a discriminator they can't be told apart
+        write("order.rel", &order(""));
+        let summary = compiler_for(&dir).session().build().unwrap();
+        assert!(summary.failed());
+        assert!(codes(&summary).contains(&"REL0308"), "{:?}", summary.diagnostics);
+
+        // A tag missing from, or repeated by, an imported variant is an error
+        write("order.rel", &order("  @discriminator(kind)\n"));
+        write("bank.rel", "define Bank {\n  iban: string\n}\nexport Bank\n");
+        let summary = compiler_for(&dir).session().build().unwrap();
+        assert!(summary.failed());
+        assert!(codes(&summary).contains(&"REL0308"), "{:?}", summary.diagnostics);
+        write("bank.rel", "define Bank {\n  kind: =card\n  iban: string\n}\nexport Bank\n");
+        let summary = compiler_for(&dir).session().build().unwrap();
+        assert!(summary.failed());
+        assert!(codes(&summary).contains(&"REL0309"), "{:?}", summary.diagnostics);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,483 @@
+/*!
+ * Constraint Checks
+ *
+ * Looks at every `type & constraint & ...` chain on its own: constraints
+ * that don't apply to their base type, `matches` patterns that aren't valid
+ * regexes, constraints that can never all hold, and ones that add nothing.
+ * Each finding points at the constraint itself rather than the whole field.
+ */
+
+use regex::Regex;
+
+use crate::ast::{ASTNode, ConstraintNode, ConstraintType, ExpressionNode, FieldNode, TypeNode, UnaryOperator};
+use crate::diagnostics::{Diagnostic, DiagnosticCode, Span};
+use crate::generator::ConstraintGeneration;
+use crate::printer::{self, Printer};
+
+/// What a base type holds, as far as constraints are concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Number,
+    Date,
+    Boolean,
+    List,
+}
+
+impl Kind {
+    fn of(type_node: &TypeNode) -> Option<Kind> {
+        match type_node {
+            TypeNode::String => Some(Kind::Text),
+            TypeNode::Number => Some(Kind::Number),
+            TypeNode::Boolean => Some(Kind::Boolean),
+            TypeNode::Array(_) => Some(Kind::List),
+            TypeNode::Identifier(name) => match name.as_str() {
+                "positive" | "negative" | "integer" | "float" | "int" | "double" => Some(Kind::Number),
+                "date" => Some(Kind::Date),
+                "array" => Some(Kind::List),
+                "email" | "url" | "uuid" | "phone" | "ip" | "json" | "hexcolor" | "base64" | "jwt" | "semver"
+                | "slug" | "text" | "password" | "username" => Some(Kind::Text),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Kind::Text => "strings",
+            Kind::Number => "numbers",
+            Kind::Date => "dates",
+            Kind::Boolean => "booleans",
+            Kind::List => "arrays",
+        }
+    }
+}
+
+/// The kinds a constraint makes sense on; `None` means any
+fn applies_to(constraint_type: &ConstraintType) -> Option<&'static [Kind]> {
+    use ConstraintType::*;
+    match constraint_type {
+        // On strings and arrays, min/max bound the length
+        Min | Max | Between => Some(&[Kind::Number, Kind::Text, Kind::List]),
+        MinLength | MaxLength => Some(&[Kind::Text, Kind::List]),
+        MinItems | MaxItems | Unique => Some(&[Kind::List]),
+        Contains => Some(&[Kind::Text, Kind::List]),
+        Matches | StartsWith | EndsWith | HasUppercase | HasLowercase | HasNumber | HasSpecialChar => Some(&[Kind::Text]),
+        Integer | Positive | Negative | Float => Some(&[Kind::Number]),
+        Future | Past | Before | After => Some(&[Kind::Date]),
+        In | NotIn | Exists | Empty | Null | Literal => None,
+    }
+}
+
+// ============================================================================
+// SECTION: Entry Point
+// ============================================================================
+
+/// Every constraint problem in one file's declarations
+pub fn check_constraints(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) => check_fields(&schema.fields, &mut diagnostics),
+            ASTNode::Mixin(mixin) => check_fields(&mixin.fields, &mut diagnostics),
+            ASTNode::TypeAlias(alias) => check_type(&alias.type_definition, &mut diagnostics),
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+fn check_fields(fields: &[FieldNode], diagnostics: &mut Vec<Diagnostic>) {
+    for field in fields {
+        check_type(&field.field_type, diagnostics);
+        for conditional in &field.conditionals {
+            check_fields(&conditional.then_fields, diagnostics);
+            check_fields(&conditional.else_fields, diagnostics);
+        }
+    }
+}
+
+fn check_type(type_node: &TypeNode, diagnostics: &mut Vec<Diagnostic>) {
+    match type_node {
+        TypeNode::Constrained { base_type, constraints } => {
+            check_chain(base_type, constraints, diagnostics);
+            check_type(base_type, diagnostics);
+        }
+        TypeNode::Array(inner) => check_type(inner, diagnostics),
+        TypeNode::Record { key, value } => {
+            check_type(key, diagnostics);
+            check_type(value, diagnostics);
+        }
+        TypeNode::Tuple(elements) => elements.iter().for_each(|element| check_type(&element.element_type, diagnostics)),
+        TypeNode::Union(types) | TypeNode::Intersection(types) | TypeNode::Generic(_, types) => {
+            types.iter().for_each(|t| check_type(t, diagnostics))
+        }
+        TypeNode::InlineObject(fields) => check_fields(fields, diagnostics),
+        TypeNode::Conditional(conditional) => {
+            check_fields(&conditional.then_fields, diagnostics);
+            check_fields(&conditional.else_fields, diagnostics);
+            if let Some(else_value) = &conditional.else_value {
+                check_type(else_value, diagnostics);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check one `base & constraint & ...` chain
+fn check_chain(base_type: &TypeNode, constraints: &[ConstraintNode], diagnostics: &mut Vec<Diagnostic>) {
+    let kind = Kind::of(base_type);
+
+    let mut inapplicable = Vec::new();
+    for constraint in constraints {
+        if let (Some(kind), Some(kinds)) = (kind, applies_to(&constraint.constraint_type)) {
+            if !kinds.contains(&kind) {
+                inapplicable.push(constraint);
+                let applicable: Vec<&str> = kinds.iter().map(|k| k.describe()).collect();
+                diagnostics.push(
+                    Diagnostic::error(
+                        DiagnosticCode::InapplicableConstraint,
+                        format!("'{}' does not apply to {}", name(constraint), Printer::new().print_type(base_type)),
+                    )
+                    .with_label(span(constraint), format!("only applies to {}", applicable.join(" and ")))
+                    .with_help("remove the constraint or change the field's type")
+                );
+                continue;
+            }
+        }
+
+        if constraint.constraint_type == ConstraintType::Matches {
+            if let Some(error) = constraint.value.as_ref().and_then(regex_error) {
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticCode::InvalidRegex, "Invalid regular expression in 'matches'")
+                        .with_label(span(constraint), error)
+                        .with_help("fix the pattern; raw strings like r\"^\\d+$\" avoid escaping backslashes")
+                );
+            }
+        }
+    }
+
+    // What the generator leaves out; inapplicable constraints are already errors,
+    // and chains on aliases are judged by what the alias resolves to
+    let unsupported = match kind {
+        Some(_) => ConstraintGeneration::constraint_schema(base_type, "", constraints, &|_| String::new()).unsupported,
+        None => Vec::new(),
+    };
+    for unsupported in unsupported {
+        if inapplicable.iter().any(|constraint| std::ptr::eq(*constraint, unsupported.constraint)) {
+            continue;
+        }
+        diagnostics.push(
+            Diagnostic::error(
+                DiagnosticCode::UnsupportedConstraint,
+                format!("'{}' can't be expressed in the TypeScript output", name(unsupported.constraint)),
+            )
+            .with_label(span(unsupported.constraint), unsupported.reason)
+            .with_help("it is left out of the generated validator; check it in application code, or remove it")
+        );
+    }
+
+    diagnostics.extend(conflicts(kind, constraints));
+    diagnostics.extend(redundancies(base_type, constraints));
+}
+
+/// Why a pattern can't compile, if it can't. Look-around and backreferences
+/// are valid in the generated validators even though `regex` rejects them.
+fn regex_error(pattern: &ExpressionNode) -> Option<String> {
+    let (ExpressionNode::RawString(pattern) | ExpressionNode::String(pattern)) = pattern else { return None };
+    match Regex::new(pattern) {
+        Ok(_) => None,
+        Err(regex::Error::Syntax(message)) if message.contains("not supported") => None,
+        Err(regex::Error::Syntax(message)) => Some(
+            message.lines().last().unwrap_or_default().trim_start_matches("error: ").to_string()
+        ),
+        Err(error) => Some(error.to_string()),
+    }
+}
+
+// ============================================================================
+// SECTION: Contradictions
+// ============================================================================
+
+fn conflicts(kind: Option<Kind>, constraints: &[ConstraintNode]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let conflict = |later: &ConstraintNode, earlier: &ConstraintNode, reason: String| {
+        Diagnostic::error(DiagnosticCode::ConflictingConstraints, "Constraints can never all hold")
+            .with_label(span(later), reason)
+            .with_secondary(span(earlier), format!("conflicts with '{}'", describe(earlier)))
+            .with_help("no value satisfies both; remove or change one of them")
+    };
+
+    for (index, later) in constraints.iter().enumerate() {
+        if let (Some(low), Some(high)) = (lower(later), upper(later)) {
+            if later.constraint_type == ConstraintType::Between && low > high {
+                diagnostics.push(
+                    Diagnostic::error(DiagnosticCode::ConflictingConstraints, "Constraint can never hold")
+                        .with_label(span(later), format!("{} is greater than {}", format_number(low), format_number(high)))
+                        .with_help("put the lower bound first")
+                );
+            }
+        }
+        for earlier in &constraints[..index] {
+            if let Some(reason) = contradiction(kind, earlier, later) {
+                diagnostics.push(conflict(later, earlier, reason));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Why two constraints exclude each other, if they do
+fn contradiction(kind: Option<Kind>, a: &ConstraintNode, b: &ConstraintNode) -> Option<String> {
+    use ConstraintType::*;
+    let (a_value, b_value) = (a.value.as_ref().and_then(number), b.value.as_ref().and_then(number));
+
+    // Put the pair in a fixed order so each rule is written once
+    let pairs = [(a, a_value, b, b_value), (b, b_value, a, a_value)];
+    for (x, x_value, y, y_value) in pairs {
+        let reason = match (&x.constraint_type, &y.constraint_type) {
+            // `between(a, b)` bounds like `min(a) & max(b)`
+            (Min | Between, Max | Between) => match (lower(x), upper(y)) {
+                (Some(low), Some(high)) if low > high => Some(format!("{} is greater than {}", describe(x), describe(y))),
+                _ => None,
+            },
+            (MinLength, MaxLength) | (MinItems, MaxItems) => match (x_value, y_value) {
+                (Some(low), Some(high)) if low > high => Some(format!("{} is greater than {}", describe(x), describe(y))),
+                _ => None,
+            },
+            (MinLength, Max) | (Min, MaxLength) if kind != Some(Kind::Number) => match (x_value, y_value) {
+                (Some(low), Some(high)) if low > high => Some(format!("{} is greater than {}", describe(x), describe(y))),
+                _ => None,
+            },
+            // On arrays every length bound counts items
+            (MinItems, Max | MaxLength) | (Min | MinLength, MaxItems) => match (x_value, y_value) {
+                (Some(low), Some(high)) if low > high => Some(format!("{} is greater than {}", describe(x), describe(y))),
+                _ => None,
+            },
+            (Positive, Negative) => Some("a number can't be both positive and negative".to_string()),
+            (Integer, Float) => Some("a number can't be both an integer and a float".to_string()),
+            (Future, Past) => Some("a date can't be both in the future and in the past".to_string()),
+            (Positive, Max) if kind == Some(Kind::Number) => match y_value {
+                Some(high) if high <= 0.0 => Some(format!("positive numbers are never at most {}", format_number(high))),
+                _ => None,
+            },
+            (Negative, Min) if kind == Some(Kind::Number) => match y_value {
+                Some(low) if low >= 0.0 => Some(format!("negative numbers are never at least {}", format_number(low))),
+                _ => None,
+            },
+            (After, Before) => match (string(x), string(y)) {
+                (Some(after), Some(before)) if after >= before => Some(format!("no date is after {} and before {}", after, before)),
+                _ => None,
+            },
+            (Empty, MinLength) | (Empty, Min) | (Empty, MinItems) if kind != Some(Kind::Number) => match y_value {
+                Some(low) if low > 0.0 => Some(format!("an empty value is shorter than {}", describe(y))),
+                _ => None,
+            },
+            (Literal, Literal) if x.value != y.value => Some("a value can't equal two different literals".to_string()),
+            _ => None,
+        };
+        if reason.is_some() {
+            return reason;
+        }
+    }
+    None
+}
+
+// ============================================================================
+// SECTION: Redundancies
+// ============================================================================
+
+fn redundancies(base_type: &TypeNode, constraints: &[ConstraintNode]) -> Vec<Diagnostic> {
+    use ConstraintType::*;
+    let mut diagnostics = Vec::new();
+    let redundant = |constraint: &ConstraintNode, reason: String| {
+        Diagnostic::error(DiagnosticCode::RedundantConstraint, format!("'{}' has no effect", describe(constraint)))
+            .with_label(span(constraint), reason)
+            .with_help("remove it")
+    };
+
+    for (index, constraint) in constraints.iter().enumerate() {
+        let value = constraint.value.as_ref().and_then(number);
+
+        // A repeat, or a weaker bound than one set elsewhere; `between(a, b)`
+        // counts as `min(a) & max(b)`, and is only weaker when both of its
+        // bounds are
+        let tighter = |bound: fn(&ConstraintNode) -> Option<f64>, own: f64, beats: fn(f64, f64) -> bool| {
+            constraints.iter().enumerate()
+                .find(|(other_index, other)| {
+                    *other_index != index && bound(other).is_some_and(|other| beats(other, own) || (other == own && *other_index < index))
+                })
+                .map(|(_, other)| other)
+        };
+        let stronger: Vec<&ConstraintNode> = match &constraint.constraint_type {
+            Min | Max | Between => {
+                let low = lower(constraint).map(|own| tighter(lower, own, |other, own| other > own));
+                let high = upper(constraint).map(|own| tighter(upper, own, |other, own| other < own));
+                match (low, high) {
+                    (Some(Some(only)), None) | (None, Some(Some(only))) => vec![only],
+                    (Some(Some(first)), Some(Some(second))) if std::ptr::eq(first, second) => vec![first],
+                    (Some(Some(first)), Some(Some(second))) => vec![first, second],
+                    _ => Vec::new(),
+                }
+            }
+            _ => constraints.iter().enumerate()
+                .find(|(other_index, other)| {
+                    if *other_index == index || other.constraint_type != constraint.constraint_type {
+                        return false;
+                    }
+                    let other_value = other.value.as_ref().and_then(number);
+                    match (&constraint.constraint_type, value, other_value) {
+                        (MinLength | MinItems, Some(own), Some(other)) => other > own || (other == own && *other_index < index),
+                        (MaxLength | MaxItems, Some(own), Some(other)) => other < own || (other == own && *other_index < index),
+                        _ => other.value == constraint.value && *other_index < index,
+                    }
+                })
+                .map(|(_, other)| vec![other])
+                .unwrap_or_default(),
+        };
+        if !stronger.is_empty() {
+            let reason = match stronger.as_slice() {
+                [other] if other.constraint_type == constraint.constraint_type && other.value == constraint.value => {
+                    "repeats an earlier constraint".to_string()
+                }
+                [other] => format!("'{}' already requires more", describe(other)),
+                _ => format!("'{}' and '{}' already require more", describe(stronger[0]), describe(stronger[1])),
+            };
+            diagnostics.push(redundant(constraint, reason));
+            continue;
+        }
+
+        // Implied by a bound or by the base type itself
+        let implied_by = |kind: ConstraintType, holds: fn(f64) -> bool| {
+            constraints.iter().find(|other| other.constraint_type == kind && other.value.as_ref().and_then(number).is_some_and(holds))
+        };
+        let reason = match (&constraint.constraint_type, base_type) {
+            (Positive, TypeNode::Identifier(name)) if name == "positive" => Some("the type is already positive".to_string()),
+            (Negative, TypeNode::Identifier(name)) if name == "negative" => Some("the type is already negative".to_string()),
+            (Integer, TypeNode::Identifier(name)) if name == "integer" || name == "int" => Some("the type is already an integer".to_string()),
+            (Positive, TypeNode::Number) => implied_by(Min, |low| low > 0.0).map(|min| format!("'{}' already requires it", describe(min))),
+            (Negative, TypeNode::Number) => implied_by(Max, |high| high < 0.0).map(|max| format!("'{}' already requires it", describe(max))),
+            (MinLength, _) if value == Some(0.0) => Some("every length is at least 0".to_string()),
+            (MinItems, _) if value == Some(0.0) => Some("every array has at least 0 items".to_string()),
+            (Min, _) if value == Some(0.0) && Kind::of(base_type).is_some_and(|kind| kind != Kind::Number) => {
+                Some("every length is at least 0".to_string())
+            }
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            diagnostics.push(redundant(constraint, reason));
+        }
+    }
+    diagnostics
+}
+
+// ============================================================================
+// SECTION: Helpers
+// ============================================================================
+
+fn number(expression: &ExpressionNode) -> Option<f64> {
+    match expression {
+        ExpressionNode::Number(value) => Some(*value),
+        ExpressionNode::UnaryOp { operator: UnaryOperator::Negate, operand } => number(operand).map(|value| -value),
+        _ => None,
+    }
+}
+
+/// The lowest value a bound allows: `min(a)` or `between(a, b)`
+fn lower(constraint: &ConstraintNode) -> Option<f64> {
+    match (&constraint.constraint_type, &constraint.value) {
+        (ConstraintType::Min, Some(value)) => number(value),
+        (ConstraintType::Between, Some(ExpressionNode::Array(bounds))) => bounds.first().and_then(number),
+        _ => None,
+    }
+}
+
+/// The highest value a bound allows: `max(b)` or `between(a, b)`
+fn upper(constraint: &ConstraintNode) -> Option<f64> {
+    match (&constraint.constraint_type, &constraint.value) {
+        (ConstraintType::Max, Some(value)) => number(value),
+        (ConstraintType::Between, Some(ExpressionNode::Array(bounds))) if bounds.len() == 2 => number(&bounds[1]),
+        _ => None,
+    }
+}
+
+fn string(constraint: &ConstraintNode) -> Option<&str> {
+    match &constraint.value {
+        Some(ExpressionNode::String(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn format_number(value: f64) -> String {
+    match value.fract() == 0.0 {
+        true => format!("{}", value as i64),
+        false => value.to_string(),
+    }
+}
+
+fn name(constraint: &ConstraintNode) -> &'static str {
+    printer::constraint_name(&constraint.constraint_type)
+}
+
+/// The constraint as written, e.g. `min(3)`
+fn describe(constraint: &ConstraintNode) -> String {
+    Printer::print_constraint(constraint)
+}
+
+fn span(constraint: &ConstraintNode) -> Span {
+    Span::new(constraint.location.line, constraint.location.column, name(constraint).chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn check(source: &str) -> Vec<(DiagnosticCode, usize, usize)> {
+        let tokens = Lexer::new(source).tokenize().expect("tokenize");
+        let ast = Parser::new(tokens).parse().expect("parse");
+        check_constraints(&ast).iter()
+            .map(|d| {
+                let span = &d.primary.as_ref().unwrap().span;
+                (d.code, span.line, span.column)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_constraint_problems_point_at_the_constraint() {
+        let source = r#"
+define Checks {
+  age: number & min(200) & max(13)
+  name: string & minLength(5) & maxLength(2)
+  sign: number & positive & negative
+  zip: string & matches(r"[")
+  ahead: lookahead & matches(r"^(?=a)")
+  count: number & minLength(2)
+  title: string & future
+  score: number & min(1) & min(5) & positive
+  tags: string[] & min(1) & max(5)
+  due: date & future
+  range: number & between(10, 1)
+  band: number & min(5) & between(1, 3)
+  wide: number & min(0) & between(1, 10)
+}
+"#;
+        assert_eq!(check(source), vec![
+            (DiagnosticCode::ConflictingConstraints, 3, 28),
+            (DiagnosticCode::ConflictingConstraints, 4, 33),
+            (DiagnosticCode::ConflictingConstraints, 5, 29),
+            (DiagnosticCode::InvalidRegex, 6, 17),
+            (DiagnosticCode::InapplicableConstraint, 8, 19),
+            (DiagnosticCode::InapplicableConstraint, 9, 19),
+            (DiagnosticCode::RedundantConstraint, 10, 19),
+            (DiagnosticCode::RedundantConstraint, 10, 37),
+            (DiagnosticCode::UnsupportedConstraint, 12, 15),
+            (DiagnosticCode::ConflictingConstraints, 13, 19),
+            (DiagnosticCode::ConflictingConstraints, 14, 27),
+            (DiagnosticCode::RedundantConstraint, 15, 18),
+        ]);
+    }
+}
@@ -0,0 +1,245 @@
+/*!
+ * Intersections
+ *
+ * `User & Timestamps & { note: string }` merges object types into one at
+ * compile time. Members are schemas, inline objects, utility types such as
+ * `Partial<User>`, or aliases of any of these; a field both sides define must
+ * have the same type on both. The merge is shared by the checks here and by
+ * the generator, which expands the result like any other schema.
+ */
+
+use std::collections::HashMap;
+
+use crate::ast::{ASTNode, FieldNode, SourceLocation, TypeNode};
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::printer::Printer;
+use crate::validation::utility_types;
+
+/// What an intersection member's name refers to
+pub enum Member {
+    Object(Vec<FieldNode>),
+    /// Declared, but not an object type (an enum, `type Id = string`)
+    Other,
+    /// Not declared here; imported or undefined, and reported elsewhere
+    Unknown,
+}
+
+/// Merge the fields of `members` in order. A field both sides define keeps
+/// its first declaration, and is optional only if it is optional everywhere.
+pub fn merge(
+    members: &[TypeNode],
+    location: &SourceLocation,
+    lookup: &dyn Fn(&str) -> Member,
+) -> (Vec<FieldNode>, Vec<Diagnostic>) {
+    let mut fields: Vec<FieldNode> = Vec::new();
+    // Field name -> the member it came from
+    let mut sources: HashMap<String, String> = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for member in members {
+        let described = Printer::new().print_type(member);
+        let (resolved, problems) = resolve(member, location, lookup);
+        diagnostics.extend(problems);
+        let member_fields = match resolved {
+            Member::Object(member_fields) => member_fields,
+            Member::Other => {
+                diagnostics.push(not_an_object(&described, location));
+                continue;
+            }
+            Member::Unknown => continue,
+        };
+        let source = match member {
+            TypeNode::InlineObject(_) => "the inline object".to_string(),
+            _ => format!("'{}'", described),
+        };
+
+        for field in member_fields {
+            let Some(existing) = fields.iter_mut().find(|f| f.name == field.name) else {
+                sources.insert(field.name.clone(), source.clone());
+                fields.push(field);
+                continue;
+            };
+            let (before, after) = (Printer::new().print_type(&existing.field_type), Printer::new().print_type(&field.field_type));
+            if before == after {
+                existing.optional &= field.optional;
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticCode::ConflictingIntersection,
+                    format!("{} and {} both define '{}'", sources[&field.name], source, field.name),
+                )
+                .with_location(location, format!("'{}' is {} on one side and {} on the other", field.name, before, after))
+                .with_help("give the field the same type on both sides, or rename one of them")
+            );
+        }
+    }
+
+    (fields, diagnostics)
+}
+
+/// Whether `type_node` builds an object from other types, rather than naming
+/// or spelling one out
+pub fn builds_object(type_node: &TypeNode) -> bool {
+    match type_node {
+        TypeNode::Intersection(_) => true,
+        TypeNode::Generic(name, _) => utility_types::is_utility_type(name),
+        _ => false,
+    }
+}
+
+/// What an object type expression stands for: a name, an inline object, an
+/// intersection or a utility type. Anything else is `Other`.
+pub fn resolve(type_node: &TypeNode, location: &SourceLocation, lookup: &dyn Fn(&str) -> Member) -> (Member, Vec<Diagnostic>) {
+    match type_node {
+        TypeNode::InlineObject(fields) => (Member::Object(fields.clone()), Vec::new()),
+        TypeNode::Identifier(name) => (lookup(name), Vec::new()),
+        TypeNode::Intersection(members) => {
+            let (fields, diagnostics) = merge(members, location, lookup);
+            (Member::Object(fields), diagnostics)
+        }
+        TypeNode::Generic(name, args) if utility_types::is_utility_type(name) => {
+            utility_types::apply(name, args, location, lookup)
+        }
+        _ => (Member::Other, Vec::new()),
+    }
+}
+
+pub(crate) fn not_an_object(described: &str, location: &SourceLocation) -> Diagnostic {
+    Diagnostic::error(DiagnosticCode::NotAnObjectType, format!("'{}' is not an object type", described))
+        .with_location(location, "only object types can be intersected or passed to a utility type")
+        .with_help("use a schema, an inline object `{ ... }`, or an alias of one")
+}
+
+// ============================================================================
+// SECTION: Object Types In A File
+// ============================================================================
+
+/// Resolve member names against one AST: schemas, and aliases of inline
+/// objects, intersections or utility types
+pub fn lookup_in(ast: &[ASTNode]) -> impl Fn(&str) -> Member + '_ {
+    move |name| lookup_at_depth(ast, name, 0)
+}
+
+fn lookup_at_depth(ast: &[ASTNode], name: &str, depth: usize) -> Member {
+    // Aliases that refer to themselves stop here rather than recursing forever
+    if depth > 32 {
+        return Member::Unknown;
+    }
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) if schema.name == name => return Member::Object(schema.fields.clone()),
+            ASTNode::Enum(enum_node) if enum_node.name == name => return Member::Other,
+            ASTNode::TypeAlias(alias) if alias.name == name => {
+                let lookup = |name: &str| lookup_at_depth(ast, name, depth + 1);
+                return resolve(&alias.type_definition, &alias.location, &lookup).0;
+            }
+            _ => {}
+        }
+    }
+    Member::Unknown
+}
+
+// ============================================================================
+// SECTION: Checks
+// ============================================================================
+
+/// Intersections and utility types in `ast` that can't be built. Types this
+/// AST doesn't declare are skipped, so a single file only reports what it
+/// can see; the merged AST of a build sees its imports too.
+pub fn check_object_types(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let lookup = lookup_in(ast);
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) => check_fields(&schema.fields, &lookup, &mut diagnostics),
+            ASTNode::Mixin(mixin) => check_fields(&mixin.fields, &lookup, &mut diagnostics),
+            ASTNode::TypeAlias(alias) => check_type(&alias.type_definition, &alias.location, &lookup, &mut diagnostics),
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+fn check_fields(fields: &[FieldNode], lookup: &dyn Fn(&str) -> Member, diagnostics: &mut Vec<Diagnostic>) {
+    for field in fields {
+        check_type(&field.field_type, &field.location, lookup, diagnostics);
+        for conditional in &field.conditionals {
+            check_fields(&conditional.then_fields, lookup, diagnostics);
+            check_fields(&conditional.else_fields, lookup, diagnostics);
+        }
+    }
+}
+
+fn check_type(type_node: &TypeNode, location: &SourceLocation, lookup: &dyn Fn(&str) -> Member, diagnostics: &mut Vec<Diagnostic>) {
+    match type_node {
+        TypeNode::Intersection(members) => {
+            diagnostics.extend(merge(members, location, lookup).1);
+            // Problems in the members themselves are part of the merge's
+            for member in members {
+                if let TypeNode::InlineObject(fields) = member {
+                    check_fields(fields, lookup, diagnostics);
+                }
+            }
+        }
+        TypeNode::Generic(name, _) if utility_types::is_utility_type(name) => {
+            diagnostics.extend(resolve(type_node, location, lookup).1);
+        }
+        TypeNode::InlineObject(fields) => check_fields(fields, lookup, diagnostics),
+        TypeNode::Array(inner) | TypeNode::Record { value: inner, .. } => check_type(inner, location, lookup, diagnostics),
+        TypeNode::Tuple(elements) => {
+            elements.iter().for_each(|element| check_type(&element.element_type, location, lookup, diagnostics))
+        }
+        TypeNode::Union(types) => types.iter().for_each(|t| check_type(t, location, lookup, diagnostics)),
+        TypeNode::Constrained { base_type, .. } => check_type(base_type, location, lookup, diagnostics),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Vec<ASTNode> {
+        let tokens = Lexer::new(source).tokenize().expect("tokenize");
+        Parser::new(tokens).parse().expect("parse")
+    }
+
+    #[test]
+    fn test_intersections_merge_fields_and_report_conflicts() {
+        let source = r#"
+define User {
+  id: uuid
+  name: string
+  note: string?
+}
+define Timestamps {
+  createdAt: date
+  note: string
+}
+define Legacy {
+  id: number
+}
+enum Role {
+  admin
+}
+type Audited = User & Timestamps & { deletedAt: date? }
+type Broken = User & Legacy & Role
+"#;
+        let ast = parse(source);
+        let lookup = lookup_in(&ast);
+        let Member::Object(fields) = lookup("Audited") else { panic!("Audited is an object") };
+        let merged: Vec<(&str, bool)> = fields.iter().map(|f| (f.name.as_str(), f.optional)).collect();
+        assert_eq!(merged, vec![("id", false), ("name", false), ("note", false), ("createdAt", false), ("deletedAt", true)]);
+
+        let found: Vec<(DiagnosticCode, usize)> = check_object_types(&ast).iter()
+            .map(|d| (d.code, d.primary.as_ref().unwrap().span.line))
+            .collect();
+        assert_eq!(found, vec![
+            (DiagnosticCode::ConflictingIntersection, 18),
+            (DiagnosticCode::NotAnObjectType, 18),
+        ]);
+    }
+}
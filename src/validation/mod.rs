@@ -1,167 +1,169 @@
-/**
+/*!
  * Validation Module
  *
  * Handles file validation and semantic checking.
  */
 
+pub mod constraints;
+pub mod intersections;
+pub mod records;
+pub mod tuples;
+pub mod unions;
+pub mod utility_types;
+
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::lexer;
 use crate::parser;
 use crate::compiler;
 use crate::ast;
+use crate::config::ProjectConfig;
+use crate::lint;
+use crate::diagnostics::{self, Diagnostic, DiagnosticCode, DiagnosticsError, Span};
 
-/// Validate AST nodes for naming conventions and best practices
-pub fn validate_ast(ast_nodes: &[ast::ASTNode]) -> Result<(), Vec<String>> {
-    let mut errors = Vec::new();
-
-    for node in ast_nodes {
-        match node {
-            ast::ASTNode::Schema(schema) => {
-                // Check schema naming conventions
-                if !schema.name.chars().next().unwrap_or(' ').is_uppercase() {
-                    errors.push(format!("Schema '{}' should start with uppercase letter", schema.name));
-                }
+/// Lex and parse one file, with problems converted to diagnostics; returns
+/// the AST along with the source it came from
+fn parse_file(file_path: &Path) -> Result<(Vec<ast::ASTNode>, String), Vec<Diagnostic>> {
+    let content = fs::read_to_string(file_path).map_err(|e| {
+        vec![Diagnostic::error(DiagnosticCode::FileReadError, format!("Failed to read file: {}", e)).with_file(file_path)]
+    })?;
 
-                // Check field naming conventions
-                for field in &schema.fields {
-                    if field.name.chars().next().unwrap_or(' ').is_uppercase() {
-                        errors.push(format!("Field '{}' in schema '{}' should start with lowercase letter", field.name, schema.name));
-                    }
-                }
-            }
-            ast::ASTNode::Enum(enum_node) => {
-                // Check enum naming conventions
-                if !enum_node.name.chars().next().unwrap_or(' ').is_uppercase() {
-                    errors.push(format!("Enum '{}' should start with uppercase letter", enum_node.name));
-                }
-            }
-            ast::ASTNode::TypeAlias(type_alias) => {
-                // Check type alias naming conventions
-                if !type_alias.name.chars().next().unwrap_or(' ').is_uppercase() {
-                    errors.push(format!("Type alias '{}' should start with uppercase letter", type_alias.name));
-                }
-            }
-            _ => {}
-        }
-    }
+    let lexer = lexer::Lexer::new(&content);
+    let tokens = lexer.tokenize()
+        .map_err(|errors| errors.iter().map(|e| Diagnostic::from(e).with_file(file_path)).collect::<Vec<_>>())?;
 
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        Err(errors)
-    }
+    let mut parser = parser::Parser::new(tokens);
+    let ast_nodes = parser.parse()
+        .map_err(|errors| errors.iter().map(|e| Diagnostic::from(e).with_file(file_path)).collect::<Vec<_>>())?;
+    Ok((ast_nodes, content))
 }
 
 /// Check .rel files without generating output
-pub fn check_files(input: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Checking rel files...");
-
-    let compiler = compiler::relCompiler::new(compiler::CompilerOptions {
-        input_dir: input.clone(),
-        output_dir: None,
-        watch: false,
-    });
+pub fn check_files(options: compiler::CompilerOptions) -> Result<Vec<Diagnostic>, Box<dyn std::error::Error>> {
+    let format = options.diagnostics_format;
+    let progress = |message: String| if format.is_human() { println!("{}", message) };
+    progress("Checking rel files...".to_string());
 
     // For check, we just validate parsing without generating output
-    let rel_files = compiler.find_rel_files(input)?;
+    let rel_files = collect_files(options)?.1;
+    let mut diagnostics = Vec::new();
 
     for file_path in rel_files {
-        println!("Checking: {:?}", file_path);
-
-        let content = fs::read_to_string(&file_path)?;
-        let lexer = lexer::Lexer::new(&content);
-        let tokens = lexer.tokenize().map_err(|errors| {
-            format!("Tokenization failed for {:?}: {:?}", file_path, errors)
-        })?;
-
-        let mut parser = parser::Parser::new(tokens);
-        parser.parse().map_err(|errors| {
-            format!("Parsing failed for {:?}: {:?}", file_path, errors)
-        })?;
+        progress(format!("Checking: {:?}", file_path));
+
+        if let Err(errors) = parse_file(&file_path) {
+            diagnostics.extend(errors);
+        }
     }
 
-    println!("✅ All files checked successfully!");
-    Ok(())
+    if !diagnostics.is_empty() {
+        return Err(DiagnosticsError::new("Check failed", diagnostics).into());
+    }
+
+    progress("✅ All files checked successfully!".to_string());
+    Ok(Vec::new())
 }
 
 /// Validate .rel files with enhanced semantic validation
-pub fn validate_files(input: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Validating rel files with enhanced validation...");
-
-    let compiler = compiler::relCompiler::new(compiler::CompilerOptions {
-        input_dir: input.clone(),
-        output_dir: None,
-        watch: false,
-    });
+pub fn validate_files(options: compiler::CompilerOptions) -> Result<Vec<Diagnostic>, Box<dyn std::error::Error>> {
+    let format = options.diagnostics_format;
+    let progress = |message: String| if format.is_human() { println!("{}", message) };
+    progress("Validating rel files with enhanced validation...".to_string());
 
     // For validation, we compile to check for semantic errors
-    let rel_files = compiler.find_rel_files(input)?;
+    let (config, rel_files) = collect_files(options)?;
+    let mut diagnostics = Vec::new();
 
     for file_path in rel_files {
-        println!("Validating: {:?}", file_path);
-
-        let content = fs::read_to_string(&file_path)?;
-        let lexer = lexer::Lexer::new(&content);
-        let tokens = lexer.tokenize().map_err(|errors| {
-            format!("Tokenization failed for {:?}: {:?}", file_path, errors)
-        })?;
+        progress(format!("Validating: {:?}", file_path));
 
-        let mut parser = parser::Parser::new(tokens);
-        let ast_nodes = parser.parse().map_err(|errors| {
-            format!("Parsing failed for {:?}: {:?}", file_path, errors)
-        })?;
+        let (ast_nodes, content) = match parse_file(&file_path) {
+            Ok(parsed) => parsed,
+            Err(errors) => {
+                diagnostics.extend(errors);
+                continue;
+            }
+        };
 
         // Additional semantic validation
         // Check for undefined types, circular references, etc.
-        if let Err(semantic_errors) = perform_semantic_validation(&ast_nodes) {
-            for error in semantic_errors {
-                println!("  Semantic error at line {}, col {}: {}",
-                    error.line,
-                    error.column,
-                    error.message
-                );
-            }
-            return Err(format!("Semantic validation failed for {:?}", file_path).into());
+        let mut file_errors = perform_semantic_validation(&ast_nodes).err().unwrap_or_default();
+        // Lints are enforced by `rel build`, so report them here too
+        file_errors.extend(lint::lint_file(&ast_nodes, &content, &config));
+
+        let failed = file_errors.iter().any(Diagnostic::is_error);
+        diagnostics.extend(file_errors.into_iter().map(|d| d.or_file(&file_path)));
+        if !failed {
+            progress("✅ File is syntactically and semantically valid".to_string());
         }
+    }
 
-        println!("✅ File is syntactically and semantically valid");
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return Err(DiagnosticsError::new("Validation failed", diagnostics).into());
     }
 
-    println!("✅ All files validated successfully!");
-    Ok(())
+    progress("✅ All files validated successfully!".to_string());
+    Ok(diagnostics)
+}
+
+/// Every .rel file named by the inputs, plus the project config that selected them
+fn collect_files(options: compiler::CompilerOptions) -> Result<(ProjectConfig, Vec<PathBuf>), Box<dyn std::error::Error>> {
+    let config = options.config.clone();
+    let compiler = compiler::relCompiler::new(options);
+
+    let mut rel_files = Vec::new();
+    for input in compiler.inputs() {
+        rel_files.extend(compiler.input_files(&input)?);
+    }
+    Ok((config, rel_files))
 }
 
 /// Perform semantic validation on AST nodes
-fn perform_semantic_validation(ast_nodes: &[ast::ASTNode]) -> Result<(), Vec<ast::ParseError>> {
+pub fn perform_semantic_validation(ast_nodes: &[ast::ASTNode]) -> Result<(), Vec<Diagnostic>> {
     let mut errors = Vec::new();
     let mut defined_types = std::collections::HashSet::new();
-    let mut used_types = std::collections::HashSet::new();
+    // First use of each referenced type, in source order
+    let mut used_types: Vec<(String, ast::SourceLocation, &str)> = Vec::new();
+    let mut record_uses = |type_node: &ast::TypeNode, location: &ast::SourceLocation, label: &'static str| {
+        let mut names = std::collections::HashSet::new();
+        collect_used_types(type_node, &mut names);
+        let mut names: Vec<String> = names.into_iter().collect();
+        names.sort();
+        for name in names {
+            if !used_types.iter().any(|(used, _, _)| *used == name) {
+                used_types.push((name, location.clone(), label));
+            }
+        }
+    };
 
     // Collect defined types and check for basic semantic issues
     for node in ast_nodes {
         match node {
             ast::ASTNode::Schema(schema) => {
                 defined_types.insert(schema.name.clone());
+                defined_types.extend(schema.generics.iter().cloned());
 
                 // Check for duplicate field names
-                let mut field_names = std::collections::HashSet::new();
+                let mut field_names: Vec<&ast::FieldNode> = Vec::new();
                 for field in &schema.fields {
-                    if !field_names.insert(field.name.clone()) {
-                        errors.push(ast::ParseError {
-                            message: format!("Duplicate field name '{}' in schema '{}'", field.name, schema.name),
-                            position: 0,
-                            // Use actual line numbers from AST node location
-                            line: field.location.line,
-                            column: field.location.column,
-                            context: Some(format!("Field '{}' already defined in schema '{}'", field.name, schema.name)),
-                            file_path: field.location.file_path.clone(),
-                        });
+                    if let Some(first) = field_names.iter().find(|f| f.name == field.name) {
+                        let first_span = Span::new(first.location.line, first.location.column, first.name.chars().count());
+                        errors.push(
+                            Diagnostic::error(
+                                DiagnosticCode::DuplicateField,
+                                format!("Duplicate field name '{}' in schema '{}'", field.name, schema.name),
+                            )
+                            .with_location(&field.location, "redefined here")
+                            .with_secondary(first_span, format!("'{}' first defined here", field.name))
+                            .with_help("rename or remove one of the fields")
+                        );
+                    } else {
+                        field_names.push(field);
                     }
 
                     // Collect used types
-                    collect_used_types(&field.field_type, &mut used_types);
+                    record_uses(&field.field_type, &field.location, "used in the type of this field");
                 }
             }
             ast::ASTNode::Enum(enum_node) => {
@@ -171,36 +173,50 @@ fn perform_semantic_validation(ast_nodes: &[ast::ASTNode]) -> Result<(), Vec<ast
                 let mut values = std::collections::HashSet::new();
                 for value in &enum_node.values {
                     if !values.insert(value.clone()) {
-                        errors.push(ast::ParseError {
-                            message: format!("Duplicate enum value '{}' in enum '{}'", value, enum_node.name),
-                            position: 0,
-                            line: 0,
-                            column: 0,
-                            context: Some(format!("Value '{}' already defined in enum '{}'", value, enum_node.name)),
-                            file_path: None,
-                        });
+                        errors.push(
+                            Diagnostic::error(
+                                DiagnosticCode::DuplicateEnumValue,
+                                format!("Duplicate enum value '{}' in enum '{}'", value, enum_node.name),
+                            )
+                            .with_location(&enum_node.location, format!("'{}' is listed more than once", value))
+                            .with_help("remove the repeated value")
+                        );
                     }
                 }
             }
             ast::ASTNode::TypeAlias(type_alias) => {
                 defined_types.insert(type_alias.name.clone());
-                collect_used_types(&type_alias.type_definition, &mut used_types);
+                record_uses(&type_alias.type_definition, &type_alias.location, "used in this type alias");
+            }
+            // Imported names are checked by the module resolver
+            ast::ASTNode::Import(import) => {
+                defined_types.extend(import.local_names().into_iter().map(String::from));
+            }
+            ast::ASTNode::Mixin(mixin) => {
+                defined_types.insert(mixin.name.clone());
+            }
+            ast::ASTNode::DeclareType(declare_type) => {
+                defined_types.insert(declare_type.name.clone());
             }
             _ => {} // Other node types don't define types
         }
     }
 
     // Check for undefined types
-    for used_type in &used_types {
-        if !defined_types.contains(used_type) && !is_builtin_type(used_type) {
-            errors.push(ast::ParseError {
-                message: format!("Undefined type '{}'", used_type),
-                position: 0,
-                line: 0,
-                column: 0,
-                context: Some(format!("Type '{}' is not defined in this scope", used_type)),
-                file_path: None,
-            });
+    for (used_type, location, label) in &used_types {
+        // `ns.Type` is checked against the exports of the module `ns` names
+        let namespaced = used_type.split_once('.').is_some_and(|(namespace, _)| defined_types.contains(namespace));
+        if !defined_types.contains(used_type) && !lexer::Lexer::is_builtin_type(used_type) && !namespaced {
+            let candidates = defined_types.iter().map(String::as_str).chain(lexer::BUILTIN_TYPES.iter().copied());
+            let help = match diagnostics::did_you_mean(used_type, candidates) {
+                Some(suggestion) => format!("did you mean '{}'?", suggestion),
+                None => format!("define '{}' in this file or import it from another module", used_type),
+            };
+            errors.push(
+                Diagnostic::error(DiagnosticCode::UndefinedType, format!("Undefined type '{}'", used_type))
+                    .with_location(location, *label)
+                    .with_help(help)
+            );
         }
     }
 
@@ -222,15 +238,41 @@ fn collect_used_types(type_node: &ast::TypeNode, used_types: &mut std::collectio
         }
         ast::TypeNode::Union(types) => {
             for t in types {
+                // Lowercase members like `admin | user` are literal values
+                if let ast::TypeNode::Identifier(name) = t {
+                    if name.starts_with(|c: char| c.is_lowercase()) && !lexer::Lexer::is_builtin_type(name) {
+                        continue;
+                    }
+                }
                 collect_used_types(t, used_types);
             }
         }
+        // `Pick<User, id | email>` names fields of User, not types
+        ast::TypeNode::Generic(name, type_args) if utility_types::is_utility_type(name) => {
+            if let Some(source) = type_args.first() {
+                collect_used_types(source, used_types);
+            }
+        }
         ast::TypeNode::Generic(name, type_args) => {
             used_types.insert(name.clone());
             for arg in type_args {
                 collect_used_types(arg, used_types);
             }
         }
+        ast::TypeNode::Record { key, value } => {
+            collect_used_types(key, used_types);
+            collect_used_types(value, used_types);
+        }
+        ast::TypeNode::Tuple(elements) => {
+            for element in elements {
+                collect_used_types(&element.element_type, used_types);
+            }
+        }
+        ast::TypeNode::Intersection(members) => {
+            for member in members {
+                collect_used_types(member, used_types);
+            }
+        }
         ast::TypeNode::Conditional(conditional) => {
             collect_used_types(&conditional.then_value, used_types);
             if let Some(else_type) = &conditional.else_value {
@@ -241,11 +283,75 @@ fn collect_used_types(type_node: &ast::TypeNode, used_types: &mut std::collectio
     }
 }
 
-/// Check if a type name is a built-in type
-fn is_builtin_type(name: &str) -> bool {
-    matches!(
-        name,
-        "string" | "number" | "boolean" | "object" | "array" | "date" | "email" | "url" |
-        "uuid" | "positive" | "negative" | "integer" | "float" | "any" | "unknown" | "null" | "undefined"
-    )
-}
\ No newline at end of file
+/// Every type written in `ast` with where it is written: field types, those
+/// of nested objects and branches included, and type alias definitions
+pub(crate) fn declared_types(ast_nodes: &[ast::ASTNode]) -> Vec<(&ast::TypeNode, &ast::SourceLocation)> {
+    let mut typed = Vec::new();
+    for node in ast_nodes {
+        match node {
+            ast::ASTNode::Schema(schema) => field_types(&schema.fields, &mut typed),
+            ast::ASTNode::Mixin(mixin) => field_types(&mixin.fields, &mut typed),
+            ast::ASTNode::TypeAlias(alias) => typed.push((&alias.type_definition, &alias.location)),
+            _ => {}
+        }
+    }
+    typed
+}
+
+/// Every field's type with where the field is, nested objects and branches included
+fn field_types<'a>(fields: &'a [ast::FieldNode], typed: &mut Vec<(&'a ast::TypeNode, &'a ast::SourceLocation)>) {
+    for field in fields {
+        typed.push((&field.field_type, &field.location));
+        if let ast::TypeNode::InlineObject(nested) = &field.field_type {
+            field_types(nested, typed);
+        }
+        for conditional in &field.conditionals {
+            field_types(&conditional.then_fields, typed);
+            field_types(&conditional.else_fields, typed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn semantic_errors(source: &str) -> Vec<(DiagnosticCode, String)> {
+        let tokens = lexer::Lexer::new(source).tokenize().expect("tokenize");
+        let ast = parser::Parser::new(tokens).parse().expect("parse");
+        perform_semantic_validation(&ast).err().unwrap_or_default().into_iter()
+            .map(|d| (d.code, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_types_inside_records_must_be_defined() {
+        let errors = semantic_errors(r#"
+define Address {
+  city: string
+}
+define Lookup {
+  byId: record<uuid, Address>
+  missing: record<string, Adress>
+}
+"#);
+        assert_eq!(errors, vec![
+            (DiagnosticCode::UndefinedType, "Undefined type 'Adress'".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_validate_accepts_every_format_type() {
+        let diagnostics = validate_files(compiler::CompilerOptions {
+            input_dir: PathBuf::from("__tests__/all-format-types.rel"),
+            output_dir: None,
+            watch: false,
+            diagnostics_format: diagnostics::DiagnosticsFormat::Json,
+            incremental: false,
+            jobs: 1,
+            config: Default::default(),
+        })
+        .expect("every format type is a known type");
+        assert!(diagnostics.iter().all(|d| d.code != DiagnosticCode::UndefinedType), "{:?}", diagnostics);
+    }
+}
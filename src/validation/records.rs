@@ -0,0 +1,155 @@
+/*!
+ * Records
+ *
+ * `record<K, V>` keys are strings or numbers at runtime, so objects, lists
+ * and booleans can't be keys. Values go into the record's type string, which
+ * has no room for an object's fields: a schema or inline object value is
+ * generated as `object`, and reported so the loss isn't silent.
+ */
+
+use std::collections::HashSet;
+
+use crate::ast::{ASTNode, TypeNode};
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::printer::Printer;
+use crate::validation::declared_types;
+use crate::validation::intersections::{self, Member};
+
+/// Check every record in `ast`; schemas declared elsewhere are unknown here,
+/// and are checked again once imports are merged
+pub fn check_records(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let lookup = intersections::lookup_in(ast);
+    let mixins: HashSet<&str> = ast.iter()
+        .filter_map(|node| match node {
+            ASTNode::Mixin(mixin) => Some(mixin.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let is_object = |name: &str| mixins.contains(name) || matches!(lookup(name), Member::Object(_));
+
+    let mut diagnostics = Vec::new();
+    for (type_node, location) in declared_types(ast) {
+        let mut found = Vec::new();
+        records(type_node, &mut found);
+        for (key, value) in found {
+            if let Some(reason) = key_problem(key, &is_object) {
+                diagnostics.push(
+                    Diagnostic::error(
+                        DiagnosticCode::InvalidRecordKey,
+                        format!("'{}' can't be a record key", Printer::new().print_type(key)),
+                    )
+                    .with_location(location, reason)
+                    .with_help("use string, number, a string format such as uuid, or an enum")
+                );
+            }
+            if let Some(reason) = object_value(value, &is_object) {
+                diagnostics.push(
+                    Diagnostic::error(
+                        DiagnosticCode::UnsupportedConstraint,
+                        "the fields of record values that are objects can't be expressed in the TypeScript output",
+                    )
+                    .with_location(location, reason)
+                    .with_help("values are only checked to be objects; check their fields in application code")
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+/// The key and value types of every record in a type, outside nested objects
+fn records<'a>(type_node: &'a TypeNode, found: &mut Vec<(&'a TypeNode, &'a TypeNode)>) {
+    match type_node {
+        TypeNode::Record { key, value } => {
+            found.push((key, value));
+            records(value, found);
+        }
+        TypeNode::Array(inner) => records(inner, found),
+        TypeNode::Tuple(elements) => elements.iter().for_each(|element| records(&element.element_type, found)),
+        TypeNode::Union(types) | TypeNode::Intersection(types) | TypeNode::Generic(_, types) => {
+            types.iter().for_each(|t| records(t, found))
+        }
+        TypeNode::Constrained { base_type, .. } => records(base_type, found),
+        _ => {}
+    }
+}
+
+/// Why a type can't key a record, if it can't
+fn key_problem(key: &TypeNode, is_object: &dyn Fn(&str) -> bool) -> Option<String> {
+    match key {
+        TypeNode::Boolean => Some("booleans can't be keys".to_string()),
+        TypeNode::Null | TypeNode::Undefined => Some("keys can't be missing".to_string()),
+        TypeNode::Object | TypeNode::InlineObject(_) | TypeNode::Record { .. } => Some("objects can't be keys".to_string()),
+        TypeNode::Array(_) | TypeNode::Tuple(_) => Some("arrays can't be keys".to_string()),
+        TypeNode::Identifier(name) => match name.as_str() {
+            "boolean" => Some("booleans can't be keys".to_string()),
+            "object" | "json" | "record" => Some("objects can't be keys".to_string()),
+            "array" => Some("arrays can't be keys".to_string()),
+            "date" => Some("dates can't be keys; use a string".to_string()),
+            name if is_object(name) => Some(format!("'{}' is an object", name)),
+            _ => None,
+        },
+        TypeNode::Union(types) => types.iter().find_map(|t| key_problem(t, is_object)),
+        TypeNode::Constrained { base_type, .. } => key_problem(base_type, is_object),
+        _ => None,
+    }
+}
+
+/// Why a record value is generated as `object`, if it is; records nested in
+/// the value are checked on their own
+fn object_value(value: &TypeNode, is_object: &dyn Fn(&str) -> bool) -> Option<String> {
+    match value {
+        TypeNode::InlineObject(_) => Some("an inline object is generated as `object`".to_string()),
+        TypeNode::Identifier(name) if is_object(name) => Some(format!("'{}' is generated as `object`", name)),
+        TypeNode::Intersection(_) | TypeNode::Generic(..) if intersections::builds_object(value) => {
+            Some(format!("'{}' is generated as `object`", Printer::new().print_type(value)))
+        }
+        TypeNode::Array(inner) => object_value(inner, is_object),
+        TypeNode::Union(types) => types.iter().find_map(|t| object_value(t, is_object)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// Each finding's code, with its label for values and its message for keys
+    fn findings(source: &str) -> Vec<(DiagnosticCode, String)> {
+        let ast = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+        check_records(&ast).into_iter()
+            .map(|d| match d.code {
+                DiagnosticCode::UnsupportedConstraint => (d.code, d.primary.unwrap().message),
+                _ => (d.code, d.message),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_record_keys_must_be_strings_or_numbers_and_object_values_are_reported() {
+        let found = findings(r#"
+define Address {
+  city: string
+}
+enum Role {
+  admin
+}
+define Lookup {
+  byId: record<uuid, Address>
+  byRole: Map<Role, number>
+  byAddress: record<Address, string>
+  flags: record<string, record<boolean, string>>
+  points: record<string, { x: number }[]>
+  counts: record<string, int>
+}
+"#);
+        assert_eq!(found, vec![
+            (DiagnosticCode::UnsupportedConstraint, "'Address' is generated as `object`".to_string()),
+            (DiagnosticCode::InvalidRecordKey, "'Address' can't be a record key".to_string()),
+            (DiagnosticCode::InvalidRecordKey, "'boolean' can't be a record key".to_string()),
+            (DiagnosticCode::UnsupportedConstraint, "an inline object is generated as `object`".to_string()),
+        ]);
+    }
+}
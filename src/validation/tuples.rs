@@ -0,0 +1,104 @@
+/*!
+ * Tuples
+ *
+ * Type strings have no tuples, so `[string, int, boolean?]` is generated as an
+ * array of any of its element types, `(string|int|boolean)[](2,3)`: the
+ * length is checked, but not which type sits at which position. A tuple whose
+ * elements differ in type is reported, so the loss isn't silent.
+ */
+
+use crate::ast::{ASTNode, TupleElement, TypeNode};
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::printer::Printer;
+use crate::validation::declared_types;
+
+/// Check every tuple in `ast`
+pub fn check_tuples(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (type_node, location) in declared_types(ast) {
+        let mut found = Vec::new();
+        tuples(type_node, &mut found);
+        for elements in found {
+            let items = item_types(elements);
+            if items.len() < 2 {
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticCode::UnsupportedConstraint,
+                    "the element types of a tuple can't be expressed by position in the TypeScript output",
+                )
+                .with_location(location, format!("each item may be any of {}", items.join(", ")))
+                .with_help("the generated validator checks the length and the item types, but not their order; the TypeScript type keeps each position")
+            );
+        }
+    }
+    diagnostics
+}
+
+/// The element lists of every tuple in a type, outside nested objects
+fn tuples<'a>(type_node: &'a TypeNode, found: &mut Vec<&'a [TupleElement]>) {
+    match type_node {
+        TypeNode::Tuple(elements) => {
+            found.push(elements);
+            elements.iter().for_each(|element| tuples(&element.element_type, found));
+        }
+        TypeNode::Array(inner) => tuples(inner, found),
+        TypeNode::Record { key, value } => {
+            tuples(key, found);
+            tuples(value, found);
+        }
+        TypeNode::Union(types) | TypeNode::Intersection(types) | TypeNode::Generic(_, types) => {
+            types.iter().for_each(|t| tuples(t, found))
+        }
+        TypeNode::Constrained { base_type, .. } => tuples(base_type, found),
+        _ => {}
+    }
+}
+
+/// The distinct types an item can have, in order; a rest element contributes
+/// its item type
+fn item_types(elements: &[TupleElement]) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for element in elements {
+        let item = match (&element.element_type, element.rest) {
+            (TypeNode::Array(inner), true) => inner.as_ref(),
+            (element_type, _) => element_type,
+        };
+        let item = Printer::new().print_type(item);
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_tuples_of_mixed_types_are_reported() {
+        let source = r#"
+define Geo {
+  point: [number, number]
+  route: [number, ...number[]]
+  row: [string, int, boolean?]
+  legs: record<string, [string, ...number[]]>
+}
+"#;
+        let ast = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+        let labels: Vec<(usize, String)> = check_tuples(&ast).into_iter()
+            .map(|d| {
+                let label = d.primary.unwrap();
+                (label.span.line, label.message)
+            })
+            .collect();
+        assert_eq!(labels, vec![
+            (5, "each item may be any of string, int, boolean".to_string()),
+            (6, "each item may be any of string, number".to_string()),
+        ]);
+    }
+}
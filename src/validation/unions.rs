@@ -0,0 +1,308 @@
+/*!
+ * Discriminated Union Checks
+ *
+ * A field whose type is a union of schemas needs `@discriminator(field)` so
+ * the generator can expand each variant and pick it by its tag. Every variant
+ * must declare the tag as a literal (`kind: =card`), and no two variants may
+ * share one. Variants resolve like intersection members, so aliases and
+ * utility types count; imported ones are unknown in their own file, and are
+ * checked once imports are merged. A single schema with `null` or `undefined`
+ * is inlined, and what it leaves out is reported.
+ */
+
+use std::collections::HashMap;
+
+use crate::ast::{ASTNode, FieldNode, SchemaNode, SourceLocation, TypeNode};
+use crate::diagnostics::{Diagnostic, DiagnosticCode, Span};
+use crate::printer::Printer;
+use crate::validation::intersections::{self, Member};
+
+pub fn check_discriminated_unions(ast: &[ASTNode]) -> Vec<Diagnostic> {
+    let lookup = intersections::lookup_in(ast);
+    let schemas: HashMap<&str, &SchemaNode> = ast.iter()
+        .filter_map(|node| match node {
+            ASTNode::Schema(schema) => Some((schema.name.as_str(), schema)),
+            _ => None,
+        })
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for node in ast {
+        match node {
+            ASTNode::Schema(schema) => check_fields(&schema.fields, &schemas, &lookup, &mut diagnostics),
+            ASTNode::Mixin(mixin) => check_fields(&mixin.fields, &schemas, &lookup, &mut diagnostics),
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+/// Whether a union member only allows the value to be missing
+pub fn is_empty_member(member: &TypeNode) -> bool {
+    matches!(member, TypeNode::Null | TypeNode::Undefined)
+        || matches!(member, TypeNode::Identifier(name) if name == "null" || name == "undefined")
+}
+
+fn check_fields(
+    fields: &[FieldNode],
+    schemas: &HashMap<&str, &SchemaNode>,
+    lookup: &dyn Fn(&str) -> Member,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for field in fields {
+        match &field.discriminator {
+            Some(tag) => check_variants(field, tag, schemas, lookup, diagnostics),
+            None => check_undiscriminated(field, lookup, diagnostics),
+        }
+        if let TypeNode::InlineObject(nested) = &field.field_type {
+            check_fields(nested, schemas, lookup, diagnostics);
+        }
+        for conditional in &field.conditionals {
+            check_fields(&conditional.then_fields, schemas, lookup, diagnostics);
+            check_fields(&conditional.else_fields, schemas, lookup, diagnostics);
+        }
+    }
+}
+
+fn check_variants(
+    field: &FieldNode,
+    tag: &str,
+    schemas: &HashMap<&str, &SchemaNode>,
+    lookup: &dyn Fn(&str) -> Member,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let invalid = |message: String| Diagnostic::error(DiagnosticCode::InvalidDiscriminator, message);
+
+    let TypeNode::Union(members) = &field.field_type else {
+        diagnostics.push(
+            invalid(format!("'@discriminator({})' is on '{}', which is not a union", tag, field.name))
+                .with_location(&field.location, "expected a union of schemas")
+                .with_help("remove the decorator, or make the field's type `A | B`")
+        );
+        return;
+    };
+
+    // Tag text -> the variant and tag field that declared it first
+    let mut seen: HashMap<String, (String, &FieldNode)> = HashMap::new();
+    let mut variants = Vec::new();
+    for member in members {
+        let name = Printer::new().print_type(member);
+        match intersections::resolve(member, &field.location, lookup).0 {
+            Member::Object(fields) => variants.push((name, fields)),
+            Member::Other => diagnostics.push(
+                invalid(format!("'{}' is not a schema", name))
+                    .with_location(&field.location, format!("in the union discriminated by '{}'", tag))
+                    .with_help("every member of a discriminated union must be a schema with a tag")
+            ),
+            // Imported, and checked once merged; or undefined, which is reported elsewhere
+            Member::Unknown => {}
+        }
+    }
+
+    for (name, fields) in &variants {
+        let tag_field = fields.iter().find(|f| f.name == tag);
+        let Some((tag_field, TypeNode::Literal(value))) = tag_field.map(|f| (f, &f.field_type)) else {
+            let mut diagnostic = invalid(format!("'{}' has no literal '{}' tag", name, tag))
+                .with_location(&field.location, format!("'{}' can't be told apart from the other variants", name))
+                .with_help(format!("add `{}: ={}` to '{}'", tag, name.to_lowercase(), name));
+            if let Some(schema) = schemas.get(name.as_str()).filter(|schema| schema.location.file_path == field.location.file_path) {
+                diagnostic = diagnostic.with_secondary(Span::at(schema.location.line, schema.location.column), "variant declared here");
+            }
+            diagnostics.push(diagnostic);
+            continue;
+        };
+
+        let text = Printer::print_expression(value);
+        match seen.get(&text) {
+            Some((other, other_field)) => {
+                // Point at the tag when it is in this file; an imported
+                // variant's tag is reported at the union
+                let local = |f: &FieldNode| f.location.file_path == field.location.file_path;
+                let (location, label) = match local(tag_field) {
+                    true => (&tag_field.location, "duplicate tag".to_string()),
+                    false => (&field.location, format!("'{}' repeats the tag of '{}'", name, other)),
+                };
+                let mut diagnostic = Diagnostic::error(
+                    DiagnosticCode::DuplicateDiscriminator,
+                    format!("'{}' and '{}' both have {} '{}'", other, name, tag, text),
+                )
+                .with_location(location, label)
+                .with_help(format!("give '{}' a tag of its own", name));
+                if local(other_field) {
+                    diagnostic = diagnostic.with_secondary(Span::at(other_field.location.line, other_field.location.column), "first used here");
+                }
+                diagnostics.push(diagnostic);
+            }
+            None => {
+                seen.insert(text, (name.clone(), tag_field));
+            }
+        }
+    }
+
+    check_selected_objects(field, tag, &variants, lookup, diagnostics);
+}
+
+/// A field that only some variants have, or that differs between them, is
+/// selected by the tag inside a type string, where an object is only `object`
+fn check_selected_objects(
+    field: &FieldNode,
+    tag: &str,
+    variants: &[(String, Vec<FieldNode>)],
+    lookup: &dyn Fn(&str) -> Member,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut order: Vec<&str> = Vec::new();
+    let mut by_name: HashMap<&str, Vec<&FieldNode>> = HashMap::new();
+    for variant_field in variants.iter().flat_map(|(_, fields)| fields).filter(|f| f.name != tag) {
+        if !by_name.contains_key(variant_field.name.as_str()) {
+            order.push(&variant_field.name);
+        }
+        by_name.entry(&variant_field.name).or_default().push(variant_field);
+    }
+
+    for name in order {
+        let entries = &by_name[name];
+        let printed: Vec<String> = entries.iter().map(|f| Printer::new().print_type(&f.field_type)).collect();
+        let expanded = entries.len() == variants.len() && printed.iter().all(|p| *p == printed[0]);
+        if expanded {
+            continue;
+        }
+        let Some(object) = entries.iter().position(|f| is_object(&f.field_type, &field.location, lookup)) else { continue };
+        diagnostics.push(
+            Diagnostic::error(
+                DiagnosticCode::UnsupportedConstraint,
+                format!("the fields of '{}' in the variants of '{}' can't be expressed in the TypeScript output", name, field.name),
+            )
+            .with_location(&field.location, format!("'{}' is selected by the tag, so '{}' is generated as `object`", name, printed[object]))
+            .with_help("give the field the same type in every variant, or check its fields in application code")
+        );
+    }
+}
+
+/// Whether a type is an object, or a list of them
+fn is_object(type_node: &TypeNode, location: &SourceLocation, lookup: &dyn Fn(&str) -> Member) -> bool {
+    match type_node {
+        TypeNode::Array(inner) => is_object(inner, location, lookup),
+        _ => matches!(intersections::resolve(type_node, location, lookup).0, Member::Object(_)),
+    }
+}
+
+/// Two or more schemas in a union can't be told apart without a tag; a single
+/// one next to `null` or `undefined` is generated as that schema alone
+fn check_undiscriminated(field: &FieldNode, lookup: &dyn Fn(&str) -> Member, diagnostics: &mut Vec<Diagnostic>) {
+    let TypeNode::Union(members) = &field.field_type else { return };
+    let variants: Vec<String> = members.iter()
+        .filter(|member| matches!(intersections::resolve(member, &field.location, lookup).0, Member::Object(_)))
+        .map(|member| Printer::new().print_type(member))
+        .collect();
+
+    match variants.as_slice() {
+        [] => {}
+        [variant] => {
+            let empty: Vec<String> = members.iter()
+                .filter(|member| is_empty_member(member))
+                .map(|member| Printer::new().print_type(member))
+                .collect();
+            if empty.is_empty() || empty.len() + 1 != members.len() {
+                return;
+            }
+            diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticCode::UnsupportedConstraint,
+                    format!("'{}' can't be expressed next to an object in the TypeScript output", empty.join(" | ")),
+                )
+                .with_location(&field.location, format!("generated as '{}' alone, so {} is rejected", variant, empty.join(" and ")))
+                .with_help(format!("write `{}?: {}` if the value may be missing instead", field.name, variant))
+            );
+        }
+        _ => diagnostics.push(
+            Diagnostic::error(DiagnosticCode::InvalidDiscriminator, "Union of schemas needs a discriminator")
+                .with_location(&field.location, format!("'{}' can't be told apart without a tag", variants.join("', '")))
+                .with_help(format!("add `@discriminator(kind)` above '{}' and a `kind: =...` field to each variant", field.name))
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn check(source: &str) -> Vec<(DiagnosticCode, usize, usize)> {
+        let tokens = Lexer::new(source).tokenize().expect("tokenize");
+        let ast = Parser::new(tokens).parse().expect("parse");
+        check_discriminated_unions(&ast).iter()
+            .map(|d| {
+                let span = &d.primary.as_ref().unwrap().span;
+                (d.code, span.line, span.column)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_variants_need_distinct_tags() {
+        let source = r#"
+define Card {
+  kind: =card
+  number: string
+}
+define Bank {
+  iban: string
+}
+define Wire {
+  kind: =card
+}
+define Order {
+  @discriminator(kind)
+  payment: Card | Bank | Wire
+  fallback: Card | Bank
+  extra: Card | null
+}
+"#;
+        assert_eq!(check(source), vec![
+            (DiagnosticCode::InvalidDiscriminator, 14, 3),
+            (DiagnosticCode::DuplicateDiscriminator, 10, 3),
+            (DiagnosticCode::InvalidDiscriminator, 15, 3),
+            (DiagnosticCode::UnsupportedConstraint, 16, 3),
+        ]);
+    }
+
+    #[test]
+    fn test_objects_selected_by_the_tag_are_reported() {
+        let source = r#"
+define Address {
+  city: string
+}
+define Card {
+  kind: =card
+  billing: Address
+  shipping: Address
+}
+define Bank {
+  kind: =bank
+  billing: string
+  shipping: Address
+}
+define Cash {
+  kind: =cash
+}
+define Order {
+  @discriminator(kind)
+  payment: Card | Bank
+  @discriminator(kind)
+  refund: Card | Cash
+}
+"#;
+        let tokens = Lexer::new(source).tokenize().expect("tokenize");
+        let ast = Parser::new(tokens).parse().expect("parse");
+        let labels: Vec<String> = check_discriminated_unions(&ast).into_iter()
+            .map(|d| d.primary.unwrap().message)
+            .collect();
+        assert_eq!(labels, vec![
+            "'billing' is selected by the tag, so 'Address' is generated as `object`",
+            "'billing' is selected by the tag, so 'Address' is generated as `object`",
+            "'shipping' is selected by the tag, so 'Address' is generated as `object`",
+        ]);
+    }
+}
@@ -0,0 +1,123 @@
+/*!
+ * Utility Types
+ *
+ * `Partial<User>`, `Required<User>`, `Pick<User, id | email>` and
+ * `Omit<User, password>` derive an object type from another at compile time,
+ * so near-copies of a schema don't have to be kept in sync by hand. The
+ * fields named in `Pick` and `Omit` must exist in the source type.
+ */
+
+use crate::ast::{ExpressionNode, SourceLocation, TypeNode};
+use crate::diagnostics::{self, Diagnostic, DiagnosticCode};
+use crate::printer::Printer;
+use crate::validation::intersections::{self, Member};
+
+pub fn is_utility_type(name: &str) -> bool {
+    matches!(name, "Partial" | "Required" | "Pick" | "Omit")
+}
+
+/// The fields `name<args>` leaves, with any problems deriving them. `Unknown`
+/// when the source type isn't declared where we're looking.
+pub fn apply(
+    name: &str,
+    args: &[TypeNode],
+    location: &SourceLocation,
+    lookup: &dyn Fn(&str) -> Member,
+) -> (Member, Vec<Diagnostic>) {
+    let Some(source) = args.first() else { return (Member::Other, Vec::new()) };
+    let (resolved, mut diagnostics) = intersections::resolve(source, location, lookup);
+    let mut fields = match resolved {
+        Member::Object(fields) => fields,
+        Member::Other => {
+            diagnostics.push(intersections::not_an_object(&Printer::new().print_type(source), location));
+            return (Member::Other, diagnostics);
+        }
+        Member::Unknown => return (Member::Unknown, diagnostics),
+    };
+
+    match name {
+        "Partial" => fields.iter_mut().for_each(|field| field.optional = true),
+        "Required" => fields.iter_mut().for_each(|field| field.optional = false),
+        _ => {
+            let keys = args.get(1).map(key_names).unwrap_or_default();
+            for key in &keys {
+                if fields.iter().any(|field| &field.name == key) {
+                    continue;
+                }
+                let help = match diagnostics::did_you_mean(key, fields.iter().map(|field| field.name.as_str())) {
+                    Some(suggestion) => format!("did you mean '{}'?", suggestion),
+                    None => format!("'{}' has no field named '{}'", Printer::new().print_type(source), key),
+                };
+                diagnostics.push(
+                    Diagnostic::error(
+                        DiagnosticCode::UnknownField,
+                        format!("'{}' names '{}', which is not a field of '{}'", name, key, Printer::new().print_type(source)),
+                    )
+                    .with_location(location, "unknown field")
+                    .with_help(help)
+                );
+            }
+            let keep = name == "Pick";
+            fields.retain(|field| keys.contains(&field.name) == keep);
+        }
+    }
+
+    (Member::Object(fields), diagnostics)
+}
+
+/// The field names in the second argument of `Pick` or `Omit`: `id | email`
+pub fn key_names(type_node: &TypeNode) -> Vec<String> {
+    match type_node {
+        TypeNode::Union(members) => members.iter().flat_map(key_names).collect(),
+        TypeNode::Identifier(name) => vec![name.clone()],
+        TypeNode::Literal(ExpressionNode::String(name)) => vec![name.clone()],
+        // A field named like a built-in type, such as `date`
+        other => vec![Printer::new().print_type(other)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::ASTNode;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::validation::intersections::{check_object_types, lookup_in};
+
+    fn parse(source: &str) -> Vec<ASTNode> {
+        let tokens = Lexer::new(source).tokenize().expect("tokenize");
+        Parser::new(tokens).parse().expect("parse")
+    }
+
+    #[test]
+    fn test_utility_types_derive_fields_from_their_source() {
+        let source = r#"
+define User {
+  id: uuid
+  email: email
+  password: string
+  name: string?
+}
+type Create = Omit<User, id>
+type Update = Partial<Pick<User, email | name>>
+type Full = Required<User>
+type Typo = Omit<User, pasword>
+"#;
+        let ast = parse(source);
+        let lookup = lookup_in(&ast);
+        let fields = |name: &str| match lookup(name) {
+            Member::Object(fields) => fields.iter().map(|f| (f.name.clone(), f.optional)).collect::<Vec<_>>(),
+            _ => panic!("{} is an object type", name),
+        };
+        let owned = |expected: &[(&str, bool)]| expected.iter().map(|(n, o)| (n.to_string(), *o)).collect::<Vec<_>>();
+
+        assert_eq!(fields("Create"), owned(&[("email", false), ("password", false), ("name", true)]));
+        assert_eq!(fields("Update"), owned(&[("email", true), ("name", true)]));
+        assert_eq!(fields("Full"), owned(&[("id", false), ("email", false), ("password", false), ("name", false)]));
+
+        let found: Vec<(DiagnosticCode, usize)> = check_object_types(&ast).iter()
+            .map(|d| (d.code, d.primary.as_ref().unwrap().span.line))
+            .collect();
+        assert_eq!(found, vec![(DiagnosticCode::UnknownField, 11)]);
+    }
+}
@@ -1,64 +1,191 @@
-/**
+/*!
  * Watch Module
  *
- * Handles file watching and automatic recompilation.
+ * Drives a build session from file system events, for both `rel watch` and
+ * `rel build --watch`. Bursts of events are debounced into one rebuild, and
+ * the session recompiles only the files a change affects.
  */
 
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
 use std::path::PathBuf;
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::compiler::{relCompiler, CompilerOptions};
+use crate::diagnostics;
+use crate::session::{BuildSession, BuildSummary};
+
+/// Quiet period that ends a burst of events, such as an editor's save sequence
+const DEBOUNCE: Duration = Duration::from_millis(100);
 
 /// Watch .rel files for changes and recompile automatically
-pub fn watch_files(input: &PathBuf, output: Option<&PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Watching rel files in: {:?} for changes", input);
+pub fn watch_files(options: CompilerOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let compiler = relCompiler::new(options);
+    let mut session = compiler.session();
+    println!("Watching rel files in: {:?} for changes", session.inputs());
 
     let (tx, rx) = channel();
 
     let mut watcher = RecommendedWatcher::new(tx, Config::default())?;
 
-    watcher.watch(input, RecursiveMode::Recursive)?;
+    for input in session.inputs() {
+        watcher.watch(input, RecursiveMode::Recursive)?;
+    }
+
+    let mut watched_dirs = BTreeSet::new();
+    report(session.build());
+    watch_imports(&mut watcher, &session, &mut watched_dirs);
 
     println!("👀 Watching for file changes... Press Ctrl+C to stop");
 
+    while let Some(changed) = next_batch(&rx) {
+        for path in changed.iter().filter(|path| path.extension().is_some_and(|ext| ext == "rel")) {
+            println!("🔄 File changed: {:?}", path);
+        }
+
+        match session.rebuild(&changed) {
+            Ok(None) => continue,
+            Ok(Some(summary)) => report(Ok(summary)),
+            Err(e) => report(Err(e)),
+        }
+        watch_imports(&mut watcher, &session, &mut watched_dirs);
+    }
+
+    Ok(())
+}
+
+/// One line per rebuild; a file that fails keeps its last good output
+fn report(result: Result<BuildSummary, Box<dyn std::error::Error>>) {
+    match result {
+        Ok(summary) => {
+            diagnostics::emit(&summary.diagnostics);
+            if summary.failed() {
+                eprintln!("❌ Build failed: {} (keeping the last good output)", summary);
+            } else {
+                println!("✅ Built: {}", summary);
+            }
+        }
+        Err(e) => {
+            diagnostics::emit(diagnostics::diagnostics_of(e.as_ref()));
+            eprintln!("❌ Build failed: {}", e);
+        }
+    }
+}
+
+/// Also watch directories holding imports from outside the inputs, such as
+/// aliased shared schemas; directories rather than files, because editors
+/// often save by replacing the file
+fn watch_imports(watcher: &mut RecommendedWatcher, session: &BuildSession, watched_dirs: &mut BTreeSet<PathBuf>) {
+    for dir in session.import_dirs() {
+        if watched_dirs.contains(&dir) {
+            continue;
+        }
+        match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                watched_dirs.insert(dir);
+            }
+            Err(e) => eprintln!("Could not watch {:?}: {}", dir, e),
+        }
+    }
+}
+
+/// Block until a .rel file or directory changes, then collect events until
+/// they stop for `DEBOUNCE`; returns the changed paths, or None once the
+/// watcher has shut down
+fn next_batch(rx: &Receiver<notify::Result<Event>>) -> Option<Vec<PathBuf>> {
+    let mut changed: Vec<PathBuf> = Vec::new();
+    let mut next = rx.recv().ok()?;
+
     loop {
-        match rx.recv() {
-            Ok(Ok(event)) => {
-                match event {
-                    notify::Event { kind: notify::EventKind::Modify(_), paths, .. } => {
-                        for path in paths {
-                            if let Some(ext) = path.extension() {
-                                if ext == "rel" {
-                                    println!("🔄 File changed: {:?}", path);
-                                    println!("🔨 Recompiling...");
-
-                                    let options = crate::compiler::CompilerOptions {
-                                        input_dir: input.clone(),
-                                        output_dir: output.cloned(),
-                                        watch: false,
-                                    };
-
-                                    let compiler = crate::compiler::relCompiler::new(options);
-                                    if let Err(e) = compiler.compile() {
-                                        eprintln!("❌ Compilation failed: {}", e);
-                                    } else {
-                                        println!("✅ Compilation successful");
-                                    }
-                                }
-                            }
-                        }
+        match next {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                // Generated .ts files may live under the input; only sources matter
+                for path in event.paths {
+                    let relevant = path.extension().is_none_or(|ext| ext == "rel");
+                    if relevant && !changed.contains(&path) {
+                        changed.push(path);
                     }
-                    _ => {} // Ignore other event types
                 }
             }
-            Ok(Err(e)) => {
-                eprintln!("Watch event error: {:?}", e);
-            }
-            Err(e) => {
-                eprintln!("Watch error: {:?}", e);
-                break;
-            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Watch event error: {:?}", e),
         }
+
+        next = match rx.recv_timeout(DEBOUNCE) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) if changed.is_empty() => rx.recv().ok()?,
+            Err(RecvTimeoutError::Timeout) => return Some(changed),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        };
     }
+}
 
-    Ok(())
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use std::fs;
+    use std::path::Path;
+
+    use crate::diagnostics::DiagnosticsFormat;
+
+    fn event(kind: EventKind, path: &str) -> notify::Result<Event> {
+        Ok(Event::new(kind).add_path(PathBuf::from(path)))
+    }
+
+    #[test]
+    fn test_a_burst_of_events_is_one_batch_of_sources() {
+        let (tx, rx) = channel();
+        tx.send(event(EventKind::Create(CreateKind::File), "/project/user.rel")).unwrap();
+        tx.send(event(EventKind::Modify(ModifyKind::Any), "/project/user.rel")).unwrap();
+        tx.send(event(EventKind::Modify(ModifyKind::Any), "/project/out/user.ts")).unwrap();
+        tx.send(event(EventKind::Access(AccessKind::Any), "/project/tag.rel")).unwrap();
+        tx.send(event(EventKind::Modify(ModifyKind::Any), "/project/shared")).unwrap();
+
+        assert_eq!(next_batch(&rx), Some(vec![PathBuf::from("/project/user.rel"), PathBuf::from("/project/shared")]));
+        drop(tx);
+        assert_eq!(next_batch(&rx), None);
+    }
+
+    #[test]
+    fn test_rebuild_compiles_only_the_importers_of_a_change() {
+        let dir = std::env::temp_dir().join(format!("rel-watch-test-{}", std::process::id()));
+        let schemas = dir.join("schemas");
+        fs::create_dir_all(&schemas).unwrap();
+        let write = |name: &str, source: &str| fs::write(schemas.join(name), source).unwrap();
+        write("address.rel", "define Address {\n  city: string\n}\nexport Address\n");
+        write("user.rel", "import { Address } from \"./address.rel\"\ndefine User {\n  address: Address\n}\nexport User\n");
+        write("tag.rel", "define Tag {\n  name: string\n}\nexport Tag\n");
+
+        let compiler = relCompiler::new(CompilerOptions {
+            input_dir: schemas.clone(),
+            output_dir: Some(dir.join("out")),
+            watch: true,
+            diagnostics_format: DiagnosticsFormat::Human,
+            incremental: false,
+            jobs: 1,
+            config: Default::default(),
+        });
+        let mut session = compiler.session();
+        assert_eq!(session.build().unwrap().compiled, 3);
+
+        // Editing an import rebuilds it and the file that imports it
+        write("address.rel", "define Address {\n  city: string\n  zip: string\n}\nexport Address\n");
+        let summary = session.rebuild(&[schemas.join("address.rel")]).unwrap().expect("a rebuild");
+        assert_eq!(summary.compiled, 2);
+        let user = fs::read_to_string(dir.join("out/user.ts")).unwrap();
+        assert!(user.contains("zip: \"string\""), "{}", user);
+
+        // A broken import fails its importer, and fixing it rebuilds both again
+        write("address.rel", "define Address {\n  city:\n}\nexport Address\n");
+        assert!(session.rebuild(&[schemas.join("address.rel")]).unwrap().expect("a rebuild").failed());
+        write("address.rel", "define Address {\n  city: string\n}\nexport Address\n");
+        assert!(!session.rebuild(&[schemas.join("address.rel")]).unwrap().expect("a rebuild").failed());
+
+        // A change to something no input imports does nothing
+        assert!(session.rebuild(&[Path::new("/elsewhere/other.rel").to_path_buf()]).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}